@@ -0,0 +1,1342 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{LineWriter, Write as _};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use toydag_core::audit;
+use toydag_core::coinbase::Coinbase;
+use toydag_core::consensus::{ConsensusProtocol, Ghostdag, LongestChain, Spectre};
+use toydag_core::daa::Daa;
+use toydag_core::events::DagEvent;
+use toydag_core::forensics::{self, Forensics};
+use toydag_core::ingest;
+use toydag_core::integrity;
+use toydag_core::knight::KMode;
+use toydag_core::log::{self, Logger, Verbosity};
+use toydag_core::merge_depth::MergeDepth;
+use toydag_core::metrics;
+use toydag_core::receipts::Receipt;
+use toydag_core::stitch::{Antichain, MergeAll, RateLimited, StitchPolicy, TopByBlueScore};
+use toydag_core::store::{BlockStore, FileStore, Persister};
+use toydag_core::softfork::SoftFork;
+use toydag_core::step::{Step, Stepper};
+use toydag_core::{BLOCK_VERSION, Color, FINALITY_DEPTH, Genesis, K, ToyDag};
+use toydag_sim::alerts::ChainQualityDetector;
+use toydag_sim::balance;
+use toydag_sim::checkpoint::Checkpoint;
+use toydag_sim::confirmations::ConfirmationTracker;
+use toydag_sim::freeloader;
+use toydag_sim::frontier;
+use toydag_sim::genesis;
+use toydag_sim::kaspa;
+use toydag_sim::mempool::{self, MempoolConfig};
+use toydag_sim::network::{self, Network, NetworkConfig};
+use toydag_sim::nodes::{self, NodeSim, NodeSimConfig, SyncPlan};
+use toydag_sim::quality;
+use toydag_sim::replay::{self, LogEntry, Recorder};
+use toydag_sim::scenario::Scenario;
+use toydag_sim::softfork::{self, RolloutConfig};
+use toydag_sim::daa::{self, HashratePhase};
+use toydag_sim::dual::{self, Engine};
+use toydag_sim::experiment::{self, Grid};
+use toydag_sim::timewarp::{self, WarpConfig};
+use toydag_sim::topology::Topology;
+use toydag_sim::{bench, consensus, knight, simulation_step, stitch};
+use toydag_viz::export;
+use toydag_viz::report::RunReport;
+#[cfg(feature = "rpc")]
+use toydag_viz::rpc;
+#[cfg(feature = "tui")]
+use toydag_viz::tui;
+
+mod repl;
+
+const RECEIPT_KEY: u64 = 0x006b_6173_7061; // Toy signing key for finality receipts
+
+#[derive(Parser)]
+#[command(about = "Toy GHOSTDAG simulator with StitchBot")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Also write the end-of-run summary as CSV
+    #[arg(long)]
+    stats_csv: Option<PathBuf>,
+
+    /// Write per-block metrics rows (CSV, or Parquet for `.parquet` paths)
+    #[arg(long)]
+    metrics_out: Option<PathBuf>,
+
+    /// Blue-score depth at which a tx counts as confirmed
+    #[arg(long, default_value_t = 10)]
+    confirm_depth: u64,
+
+    /// Reject blocks that merge below this blue-score depth
+    #[arg(long)]
+    merge_depth: Option<u64>,
+
+    /// Reject blocks merging more than this many blocks besides their selected parent
+    #[arg(long)]
+    mergeset_limit: Option<usize>,
+
+    /// Reject blocks with more parents than this; StitchBot and templates prune to fit
+    #[arg(long)]
+    max_parents: Option<usize>,
+
+    /// Write a shareable end-of-run report: HTML, or Markdown for `.md` paths
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Record every block insertion to this JSON-lines log, for `replay`
+    #[arg(long)]
+    record: Option<PathBuf>,
+
+    /// Persist blocks to this file, resuming from whatever it already holds
+    #[arg(long)]
+    store: Option<PathBuf>,
+
+    /// Enforce the merge depth without kosherizing exemptions
+    #[arg(long, requires = "merge_depth")]
+    strict_merge_depth: bool,
+
+    /// Which tips StitchBot merges once it activates
+    #[arg(long, value_enum, default_value_t = StitchChoice::All)]
+    stitch_policy: StitchChoice,
+
+    /// Tips merged per stitch under the top-n policy
+    #[arg(long, default_value_t = 4)]
+    stitch_top: usize,
+
+    /// Let StitchBot fire at most once per this many blocks
+    #[arg(long)]
+    stitch_min_gap: Option<u64>,
+
+    /// Pause after every block to show what it merged, why it got its color and how the virtual moved
+    #[arg(long)]
+    step: bool,
+
+    /// Red rate the end-of-run k recommendation aims for
+    #[arg(long, default_value_t = 0.01)]
+    red_target: f64,
+
+    // The output flags below shape everything printed through the log facade.
+    // Data meant for other tools (slice, export to stdout) and the REPL's
+    // replies are printed as they are.
+    /// Only print reports and warnings
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print per-block detail
+    #[arg(long, short, global = true)]
+    verbose: bool,
+
+    /// Leave emoji out of printed output
+    #[arg(long, global = true)]
+    no_emoji: bool,
+
+    /// Never color printed output, even on a terminal
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Also write log records as JSON lines to this file; `-` writes them to stdout instead of text
+    #[arg(long, global = true)]
+    log_json: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a human-readable description of a scenario file
+    Describe { path: PathBuf },
+    /// Run the toy's GHOSTDAG over a Kaspa node's header dump (JSON or CSV) and compare
+    ImportKaspa {
+        path: PathBuf,
+        #[arg(long, default_value_t = 18)]
+        k: usize,
+        /// Disagreements to list individually
+        #[arg(long, default_value_t = 10)]
+        show: usize,
+    },
+    /// Run a scenario file: miners, latencies, partitions and attacks as scheduled
+    RunScenario {
+        path: PathBuf,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Compare fixed and adaptive StitchBot policies on a fork-heavy workload
+    BenchStitch {
+        #[arg(long, default_value_t = 400)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Time incremental virtual updates against full recomputation
+    BenchVirtual {
+        #[arg(long, default_value_t = 100_000)]
+        blocks: u64,
+        #[arg(long, default_value_t = 10)]
+        checkpoints: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Sweep delivery jitter at a fixed mean latency, with duplicate deliveries
+    Jitter {
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        #[arg(long, default_value_t = 2000)]
+        latency_ms: u64,
+        #[arg(long, default_value_t = 500)]
+        interval_ms: u64,
+        #[arg(long, default_value_t = 0.1)]
+        duplicate_prob: f64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Attacker withholding blocks to keep two honest groups on different chains; sweeps its hashrate
+    Balance {
+        /// Attacker hashrate fractions, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0,0.1,0.2,0.3,0.4")]
+        shares: Vec<f64>,
+        /// Delay between the two honest groups
+        #[arg(long, default_value_t = 5000)]
+        latency_ms: u64,
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        #[arg(long, default_value_t = 600)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Pit a miner that only extends the selected tip against DAG-aware miners
+    Freeloader {
+        /// Freeloader's fraction of total hashrate
+        #[arg(long, default_value_t = 0.3)]
+        share: f64,
+        /// DAG-aware miners splitting the rest
+        #[arg(long, default_value_t = 3)]
+        honest: usize,
+        #[arg(long, default_value_t = 500)]
+        blocks: u64,
+        #[arg(long, default_value_t = 2000)]
+        latency_ms: u64,
+        #[arg(long, default_value_t = 500)]
+        interval_ms: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Per-miner mempools with fee-priority templates: raw vs unique tx throughput across latencies
+    Mempool {
+        /// Latencies between miners in ms, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0,500,2000,5000")]
+        latencies_ms: Vec<u64>,
+        #[arg(long, default_value_t = 4)]
+        miners: usize,
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        /// Tx slots per block
+        #[arg(long, default_value_t = 20)]
+        capacity: usize,
+        /// New txs per block interval
+        #[arg(long, default_value_t = 25)]
+        tx_rate: u64,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Sweep block size against block interval with latency + size/bandwidth propagation
+    BlockSize {
+        /// Block sizes in bytes, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "1000,100000,1000000")]
+        sizes: Vec<u64>,
+        /// Block intervals in ms, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "1000,250,100")]
+        intervals_ms: Vec<u64>,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        #[arg(long, default_value_t = 100)]
+        latency_ms: u64,
+        /// Link bandwidth in bytes per ms (≈ kB/s)
+        #[arg(long, default_value_t = 1000)]
+        bandwidth: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Join a fresh node mid-run and time its header-first sync from the nearest peer
+    Sync {
+        /// Topology file
+        path: PathBuf,
+        /// Node that joins late
+        #[arg(long, default_value_t = 0)]
+        node: usize,
+        #[arg(long, default_value_t = 150_000)]
+        join_at_ms: u64,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        #[arg(long, default_value_t = 10_000)]
+        block_size: u64,
+        #[arg(long, default_value_t = 80)]
+        header_size: u64,
+        /// Blocks per download request
+        #[arg(long, default_value_t = 16)]
+        batch: usize,
+        /// Sync connection bandwidth in bytes per ms; 0 means unlimited
+        #[arg(long, default_value_t = 1000)]
+        bandwidth: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Pause a multi-node run to inspect nodes' local views, then chart how far the views drift apart
+    Views {
+        /// Topology file
+        path: PathBuf,
+        /// Nodes whose views to show, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0")]
+        nodes: Vec<usize>,
+        /// Blocks mined before the views are taken
+        #[arg(long, default_value_t = 150)]
+        at_block: u64,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        #[arg(long, default_value_t = 10_000)]
+        block_size: u64,
+        #[arg(long, default_value_t = 3)]
+        max_parents: usize,
+        /// Rows in the divergence timeline
+        #[arg(long, default_value_t = 10)]
+        rows: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Compare node topologies (TOML files) on tip divergence and red rate
+    Topology {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        #[arg(long, default_value_t = 50)]
+        interval_ms: u64,
+        /// Block size in bytes, paid per hop on links with limited bandwidth
+        #[arg(long, default_value_t = 0)]
+        block_size: u64,
+        #[arg(long, default_value_t = 3)]
+        max_parents: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Run the multi-node model on a topology, checkpointing as it goes, or carry on from a checkpoint
+    Nodes {
+        /// Topology file; the checkpoint has it when resuming
+        #[arg(required_unless_present = "resume", conflicts_with = "resume")]
+        path: Option<PathBuf>,
+        /// Blocks to mine, on top of the checkpoint's when resuming
+        #[arg(long, default_value_t = 1000)]
+        blocks: u64,
+        #[arg(long, default_value_t = 50, conflicts_with = "resume")]
+        interval_ms: u64,
+        /// Block size in bytes, paid per hop on links with limited bandwidth
+        #[arg(long, default_value_t = 0, conflicts_with = "resume")]
+        block_size: u64,
+        /// Defaults to 3; replaces the checkpoint's when resuming
+        #[arg(long)]
+        max_parents: Option<usize>,
+        /// Save the whole run here every --checkpoint-every blocks
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        #[arg(long, default_value_t = 1000)]
+        checkpoint_every: u64,
+        /// Carry on from this checkpoint instead of starting fresh
+        #[arg(long)]
+        resume: Option<PathBuf>,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Run the network model with several miners and watch chain quality online
+    Detect {
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        /// Relative hashrate per miner, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0.6,0.2,0.2")]
+        hashrates: Vec<f64>,
+        /// Selected-chain blocks considered per check
+        #[arg(long, default_value_t = 50)]
+        window: usize,
+        /// Alert when one miner's share of the window exceeds this
+        #[arg(long, default_value_t = 0.5)]
+        threshold: f64,
+        /// Miner ids counted as adversarial in the quality report, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0")]
+        adversaries: Vec<u32>,
+        /// Coinbase reward per blue block
+        #[arg(long, default_value_t = 50)]
+        reward: u64,
+        /// Coinbase reward per red block
+        #[arg(long, default_value_t = 0)]
+        red_reward: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Let k adapt to observed concurrency (DAGKnight-style) across latency phases
+    Knight {
+        /// Network latency per phase, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "1000,5000,20000,2000")]
+        latencies_ms: Vec<u64>,
+        #[arg(long, default_value_t = 200)]
+        blocks_per_phase: u64,
+        /// Recent blocks the k estimate looks at
+        #[arg(long, default_value_t = 100)]
+        window: usize,
+        /// Share of those blocks that must fit under k
+        #[arg(long, default_value_t = 0.9)]
+        coverage: f64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Find the smallest k that keeps the red rate under a target at each latency
+    RecommendK {
+        /// Network latencies in ms, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "500,1000,2000,4000")]
+        latencies_ms: Vec<u64>,
+        #[arg(long, default_value_t = 1000)]
+        block_interval_ms: u64,
+        /// Blocks mined per latency
+        #[arg(long, default_value_t = 500)]
+        blocks: u64,
+        /// Highest acceptable share of red blocks
+        #[arg(long, default_value_t = 0.01)]
+        red_target: f64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Run the difficulty adjustment through hashrate changes
+    Daa {
+        /// Total hashrate per phase (work per ms), comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "1,4,0.5")]
+        hashrates: Vec<f64>,
+        #[arg(long, default_value_t = 200)]
+        blocks_per_phase: u64,
+        /// Chain blocks in the difficulty window
+        #[arg(long, default_value_t = 30, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        window: usize,
+        /// Target time per blue block
+        #[arg(long, default_value_t = 1000)]
+        target_ms: u64,
+        /// How old a block must be before miners build on it
+        #[arg(long, default_value_t = 1500)]
+        delay_ms: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Sweep k × latency × block rate × miner count, averaging over seeds
+    Experiment {
+        #[arg(long, value_delimiter = ',', default_value = "15")]
+        k: Vec<usize>,
+        #[arg(long, value_delimiter = ',', default_value = "500,2000")]
+        latencies_ms: Vec<u64>,
+        /// Block rates in blocks per second
+        #[arg(long, value_delimiter = ',', default_value = "1")]
+        bps: Vec<f64>,
+        #[arg(long, value_delimiter = ',', default_value = "1,4")]
+        miners: Vec<usize>,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        /// Hashrate fraction given to miner 0, treated as the adversary
+        #[arg(long, default_value_t = 0.0)]
+        adversary_share: f64,
+        /// Runs per grid cell, with consecutive seeds
+        #[arg(long, default_value_t = 3)]
+        seeds: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+        /// Also write the combined table as CSV
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Run the network model, then check the blue set against the k-cluster rule
+    Audit {
+        #[arg(long, default_value_t = 200)]
+        blocks: u64,
+        #[arg(long, default_value_t = K)]
+        k: usize,
+        #[arg(long, default_value_t = 3000)]
+        latency_ms: u64,
+        /// Violations to list individually
+        #[arg(long, default_value_t = 10)]
+        show: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Flag stale parents, withholding bursts and timestamp anomalies, and score miners by them
+    Forensics {
+        /// Inspect the DAG of a --record log instead of running the network model
+        log: Option<PathBuf>,
+        #[arg(long, default_value_t = 300)]
+        blocks: u64,
+        /// Relative hashrate per miner, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0.6,0.2,0.2")]
+        hashrates: Vec<f64>,
+        /// Insertions a block may lag behind and still count as unseen
+        #[arg(long, default_value_t = 10)]
+        grace: usize,
+        /// Blue score a block may trail the best visible block by
+        #[arg(long, default_value_t = 3)]
+        stale_lag: u64,
+        /// Insertions without a block from a miner before its next run counts as a burst
+        #[arg(long, default_value_t = 50)]
+        silence: usize,
+        /// Consecutive blocks from one miner that make a burst
+        #[arg(long, default_value_t = 4)]
+        burst: usize,
+        /// Timestamp units a parent may claim to be newer than its child
+        #[arg(long, default_value_t = 0)]
+        clock_tolerance: u64,
+        /// Findings to list individually
+        #[arg(long, default_value_t = 10)]
+        show: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Let an attacker warp its block timestamps and see what past-median-time windows do about it
+    TimeWarp {
+        /// Past-median-time windows to try, in chain blocks; 0 means raw timestamps
+        #[arg(long, value_delimiter = ',', default_value = "0,3,11,31")]
+        median_windows: Vec<usize>,
+        #[arg(long, default_value_t = 0.3)]
+        share: f64,
+        /// How far the attacker stamps from the real time; negative backdates
+        #[arg(long, default_value_t = 60000, allow_hyphen_values = true)]
+        warp_ms: i64,
+        /// Total hashrate (work per ms)
+        #[arg(long, default_value_t = 1.0)]
+        hashrate: f64,
+        #[arg(long, default_value_t = 1000)]
+        blocks: u64,
+        /// Chain blocks in the difficulty window
+        #[arg(long, default_value_t = 30, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        window: usize,
+        #[arg(long, default_value_t = 1000)]
+        target_ms: u64,
+        #[arg(long, default_value_t = 1500)]
+        delay_ms: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Roll out a soft fork with stragglers and non-signaling miners; several veto shares give a sweep
+    SoftFork {
+        /// Share of hashrate that has upgraded but refuses to signal, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0.05")]
+        veto: Vec<f64>,
+        /// Share of hashrate that never upgraded
+        #[arg(long, default_value_t = 0.05)]
+        stragglers: f64,
+        /// Blocks after activation until stragglers upgrade; they never do when unset
+        #[arg(long)]
+        upgrade_lag: Option<u64>,
+        /// Blue blocks per signaling period
+        #[arg(long, default_value_t = 100)]
+        window: u64,
+        /// Share of a period that must signal to lock the fork in
+        #[arg(long, default_value_t = 0.9)]
+        threshold: f64,
+        #[arg(long, default_value_t = 1000)]
+        blocks: u64,
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        /// How old a block must be before miners build on it
+        #[arg(long, default_value_t = 2000)]
+        delay_ms: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Order the same DAG under two consensus protocols and diff their verdicts
+    CompareOrder {
+        #[arg(long, value_enum, default_value_t = Protocol::Ghostdag)]
+        protocol: Protocol,
+        #[arg(long, value_enum, default_value_t = Protocol::Spectre)]
+        against: Protocol,
+        #[arg(long, default_value_t = 40)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Feed the same blocks, in the same order, to two consensus engines and report where they part ways
+    Dual {
+        /// Engine as name[:k], e.g. ghostdag:15
+        #[arg(long, default_value = "ghostdag:18")]
+        left: Engine,
+        #[arg(long, default_value = "ghostdag:3")]
+        right: Engine,
+        /// Take the blocks from a --record log instead of generating them
+        #[arg(long)]
+        log: Option<PathBuf>,
+        #[arg(long, default_value_t = 500)]
+        blocks: u64,
+        /// Mean time between generated blocks
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        /// How old a block must be before generated blocks build on it
+        #[arg(long, default_value_t = 4000)]
+        delay_ms: u64,
+        /// Block ids listed per category
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Throughput and attack threshold of the longest chain against GHOSTDAG as blocks speed up
+    ChainVsDag {
+        /// Mean times between blocks to try, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "8000,4000,2000,1000,500")]
+        intervals_ms: Vec<u64>,
+        /// How old a block must be before miners build on it
+        #[arg(long, default_value_t = 2000)]
+        delay_ms: u64,
+        #[arg(long, default_value_t = 400)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Sweep block rate against network delay at a fixed k: red rate and confirmation time per cell
+    Frontier {
+        /// Block rates in blocks per second, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0.25,0.5,1,2,4,8")]
+        bps: Vec<f64>,
+        /// How old a block must be before miners build on it, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "250,1000,4000")]
+        delays_ms: Vec<u64>,
+        #[arg(long, default_value_t = K)]
+        k: usize,
+        #[arg(long, default_value_t = 400)]
+        blocks: u64,
+        /// Blue-score depth at which a tx counts as confirmed
+        #[arg(long, default_value_t = 10)]
+        confirm_depth: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+        /// Also write the grid as CSV, one row per cell
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Simulate quietly, then print a JSON slice of the DAG for front-ends
+    Slice {
+        #[arg(long, default_value_t = 100)]
+        blocks: u64,
+        /// Block to anchor the slice on (defaults to the virtual's selected parent)
+        #[arg(long)]
+        anchor: Option<u64>,
+        /// Parent hops to include below the anchor
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Build and query a DAG by hand, one command per line (`help` lists them)
+    Repl,
+    /// Rebuild a DAG from a block log written with --record, one insertion at a time
+    Replay {
+        path: PathBuf,
+        /// Pause between insertions
+        #[arg(long, default_value_t = 0)]
+        delay_ms: u64,
+        /// Step through the log in the terminal UI instead (needs the tui feature)
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Insert blocks streamed on stdin as --record log lines, from a reader thread, in any order
+    Ingest {
+        /// Blocks in flight before the reader waits for the DAG
+        #[arg(long, default_value_t = 64)]
+        capacity: usize,
+        /// Out-of-order blocks held for their parents before the oldest is dropped
+        #[arg(long, default_value_t = 1000)]
+        orphan_limit: usize,
+    },
+    /// Run the same seeded workload on independent DAGs that differ only in their genesis
+    Genesis {
+        /// Genesis specs as id:timestamp:difficulty, comma-separated
+        #[arg(long, value_delimiter = ',', default_value = "0:0:1,1000:0:1,0:1700000000:4")]
+        specs: Vec<Genesis>,
+        #[arg(long, default_value_t = 200)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Compare the DAGs rebuilt from two --record logs: unique blocks, colors, chain divergence
+    Diff {
+        left: PathBuf,
+        right: PathBuf,
+        /// Block ids listed per category
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Check the DAG's structure: parents, cycles, tips, chain blue scores and the children index
+    Check {
+        /// Check the DAG of a --record log instead of a fresh simulation
+        log: Option<PathBuf>,
+        #[arg(long, default_value_t = 1000)]
+        blocks: u64,
+        /// Violations to list individually
+        #[arg(long, default_value_t = 10)]
+        show: usize,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Simulate quietly, then compare the DAG's memory with the compact arena layout
+    Memory {
+        #[arg(long, default_value_t = 10000)]
+        blocks: u64,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Simulate quietly, then write the whole DAG as DOT, GraphML, or Cytoscape.js JSON
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Dot)]
+        format: ExportFormat,
+        #[arg(long, default_value_t = 100)]
+        blocks: u64,
+        /// Write here instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+    },
+    /// Run the simulation while serving the DAG over JSON-RPC for external visualizers
+    #[cfg(feature = "rpc")]
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:7070")]
+        addr: String,
+        #[arg(long, default_value_t = 500)]
+        blocks: u64,
+        /// Milliseconds between blocks
+        #[arg(long, default_value_t = 200)]
+        tick_ms: u64,
+    },
+    /// Watch the simulation live in a terminal UI
+    #[cfg(feature = "tui")]
+    Tui {
+        #[arg(long, default_value_t = 500)]
+        blocks: u64,
+        /// Milliseconds between blocks while running
+        #[arg(long, default_value_t = 200)]
+        tick_ms: u64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StitchChoice {
+    All,
+    TopN,
+    Antichain,
+}
+
+impl StitchChoice {
+    fn policy(self, top: usize, min_gap: Option<u64>) -> Box<dyn StitchPolicy> {
+        let policy: Box<dyn StitchPolicy> = match self {
+            StitchChoice::All => Box::new(MergeAll),
+            StitchChoice::TopN => Box::new(TopByBlueScore { n: top }),
+            StitchChoice::Antichain => Box::new(Antichain),
+        };
+        match min_gap {
+            Some(gap) => Box::new(RateLimited::new(policy, gap)),
+            None => policy,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Dot,
+    Graphml,
+    Cytoscape,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Protocol {
+    Ghostdag,
+    Spectre,
+    LongestChain,
+}
+
+impl Protocol {
+    fn rule(self) -> Box<dyn ConsensusProtocol> {
+        match self {
+            Protocol::Ghostdag => Box::new(Ghostdag),
+            Protocol::Spectre => Box::new(Spectre),
+            Protocol::LongestChain => Box::new(LongestChain),
+        }
+    }
+}
+
+// Why a command failed: errors exit 1, while a check that ran and found
+// violations has already reported them and exits 2
+enum Failure {
+    Error(String),
+    Violations,
+}
+
+impl From<String> for Failure {
+    fn from(e: String) -> Self {
+        Failure::Error(e)
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match init_log(&cli).map_err(Failure::from).and_then(|()| run(cli)) {
+        Ok(()) => {}
+        Err(Failure::Error(e)) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+        Err(Failure::Violations) => process::exit(2),
+    }
+}
+
+// Run the subcommand, or the default simulation without one
+fn run(cli: Cli) -> Result<(), Failure> {
+    match cli.command {
+        None => {
+            let rules = Rules {
+                merge_depth: cli.merge_depth.map(|depth| MergeDepth { depth, kosherize: !cli.strict_merge_depth }),
+                mergeset_limit: cli.mergeset_limit,
+                max_parents: cli.max_parents,
+                stitch_policy: cli.stitch_policy.policy(cli.stitch_top, cli.stitch_min_gap),
+            };
+            let outputs = Outputs {
+                stats_csv: cli.stats_csv.as_deref(),
+                metrics_out: cli.metrics_out.as_deref(),
+                record: cli.record.as_deref(),
+                store: cli.store.as_deref(),
+                report: cli.report.as_deref(),
+            };
+            run_simulation(outputs, rules, cli.confirm_depth, cli.red_target, cli.step)?
+        }
+        Some(Command::Describe { path }) => log::report("scenario", &Scenario::load(&path)?.describe()),
+        Some(Command::ImportKaspa { path, k, show }) => log::report("kaspa", &kaspa::compare(&kaspa::load(&path)?, k, show)?),
+        Some(Command::RunScenario { path, seed }) => log::report("scenario", &Scenario::load(&path)?.run(seed)?),
+        Some(Command::BenchStitch { blocks, seed }) => log::report("stitch", &stitch::benchmark(blocks, seed)),
+        Some(Command::BenchVirtual { blocks, checkpoints, seed }) => {
+            log::report("bench", &bench::benchmark_virtual(blocks, checkpoints, seed))
+        }
+        Some(Command::Jitter { blocks, latency_ms, interval_ms, duplicate_prob, seed }) => {
+            let base = NetworkConfig {
+                block_interval_ms: interval_ms,
+                latency_ms,
+                duplicate_prob,
+                ..NetworkConfig::default()
+            };
+            let jitters = [0, latency_ms / 4, latency_ms / 2, latency_ms, latency_ms * 2];
+            log::report("jitter", &network::jitter_experiment(&base, &jitters, blocks, seed));
+        }
+        Some(Command::Balance { shares, latency_ms, interval_ms, blocks, seed }) => {
+            log::report("balance", &balance::experiment(&shares, latency_ms, interval_ms, blocks, seed))
+        }
+        Some(Command::Freeloader { share, honest, blocks, latency_ms, interval_ms, seed }) => {
+            let base = NetworkConfig { block_interval_ms: interval_ms, latency_ms, ..NetworkConfig::default() };
+            log::report("freeloader", &freeloader::experiment(&base, share, honest, blocks, seed));
+        }
+        Some(Command::Mempool { latencies_ms, miners, interval_ms, capacity, tx_rate, blocks, seed }) => {
+            let base = MempoolConfig {
+                miners: miners.max(1),
+                block_interval_ms: interval_ms,
+                latency_ms: 0,
+                tx_per_block: tx_rate,
+                capacity,
+                max_parents: 3,
+            };
+            log::report("mempool", &mempool::experiment(&base, &latencies_ms, blocks, seed));
+        }
+        Some(Command::BlockSize { sizes, intervals_ms, blocks, latency_ms, bandwidth, seed }) => {
+            let base = NetworkConfig { latency_ms, bandwidth, ..NetworkConfig::default() };
+            log::report("block-size", &network::block_size_experiment(&base, &sizes, &intervals_ms, blocks, seed));
+        }
+        Some(Command::Topology { paths, blocks, interval_ms, block_size, max_parents, seed }) => {
+            let config = NodeSimConfig { block_interval_ms: interval_ms, block_size, max_parents, ..NodeSimConfig::default() };
+            let topologies = paths.iter().map(|p| Topology::load(p)).collect::<Result<Vec<_>, _>>()?;
+            log::report("topology", &nodes::topology_experiment(&topologies, &config, blocks, seed)?);
+        }
+        Some(Command::Sync { path, node, join_at_ms, blocks, interval_ms, block_size, header_size, batch, bandwidth, seed }) => {
+            let config = NodeSimConfig { block_interval_ms: interval_ms, block_size, ..NodeSimConfig::default() };
+            let plan = SyncPlan { node, join_at_ms, header_size, batch_size: batch, bandwidth };
+            log::report("sync", &nodes::sync_experiment(&Topology::load(&path)?, &config, plan, blocks, seed)?);
+        }
+        Some(Command::Views { path, nodes, at_block, blocks, interval_ms, block_size, max_parents, rows, seed }) => {
+            let config = NodeSimConfig { block_interval_ms: interval_ms, block_size, max_parents, ..NodeSimConfig::default() };
+            let topology = Topology::load(&path)?;
+            log::report("views", &nodes::view_experiment(&topology, &config, &nodes, at_block, blocks, rows, seed)?);
+        }
+        Some(Command::Nodes { path, blocks, interval_ms, block_size, max_parents, checkpoint, checkpoint_every, resume, seed }) => {
+            let mut sim = match (&path, &resume) {
+                (_, Some(from)) => {
+                    let checkpoint = Checkpoint::load(from)?;
+                    log::info("checkpoint", "💾", &format!("Resuming {} blocks in from {}", checkpoint.mined(), from.display()));
+                    NodeSim::resume(checkpoint)?
+                }
+                (Some(path), None) => {
+                    let config = NodeSimConfig { block_interval_ms: interval_ms, block_size, ..NodeSimConfig::default() };
+                    NodeSim::new(&Topology::load(path)?, config, seed)?
+                }
+                (None, None) => unreachable!("clap requires a topology or a checkpoint"),
+            };
+            sim.config.max_parents = max_parents.unwrap_or(sim.config.max_parents);
+            match &checkpoint {
+                Some(path) => {
+                    let saved = sim.run_with_checkpoints(blocks, checkpoint_every, path)?;
+                    log::info("checkpoint", "💾", &format!("Saved {} checkpoints to {}", saved, path.display()));
+                }
+                None => sim.run(blocks),
+            }
+            log::report("nodes", &sim.summary());
+        }
+        Some(Command::Detect { blocks, hashrates, window, threshold, adversaries, reward, red_reward, seed }) => {
+            let coinbase = Coinbase { reward, red_reward };
+            run_detection(blocks, hashrates, window, threshold, &adversaries, coinbase, seed)
+        }
+        Some(Command::Knight { latencies_ms, blocks_per_phase, window, coverage, seed }) => {
+            log::report("knight", &knight::experiment(&latencies_ms, blocks_per_phase, window, coverage, seed))
+        }
+        Some(Command::RecommendK { latencies_ms, block_interval_ms, blocks, red_target, seed }) => {
+            log::report("knight", &knight::recommend(&latencies_ms, block_interval_ms, blocks, red_target, seed))
+        }
+        Some(Command::SoftFork { veto, stragglers, upgrade_lag, window, threshold, blocks, interval_ms, delay_ms, seed }) => {
+            let fork = SoftFork { window, threshold, ..SoftFork::new(BLOCK_VERSION + 1) };
+            fork.check()?;
+            let config = RolloutConfig { fork, stragglers, vetoers: veto[0], upgrade_lag, interval_ms, delay_ms, blocks };
+            if veto.len() == 1 {
+                log::report("softfork", &softfork::report(&config, seed))
+            } else {
+                log::report("softfork", &softfork::experiment(&config, &veto, seed))
+            }
+        }
+        Some(Command::Daa { hashrates, blocks_per_phase, window, target_ms, delay_ms, seed }) => {
+            let phases: Vec<HashratePhase> = hashrates
+                .into_iter()
+                .map(|hashrate| HashratePhase { hashrate, blocks: blocks_per_phase })
+                .collect();
+            log::report("daa", &daa::experiment(&phases, Daa::new(window, target_ms), delay_ms, seed))
+        }
+        Some(Command::Experiment { k, latencies_ms, bps, miners, adversary_share, blocks, seeds, seed, out }) => {
+            let grid = Grid { ks: k, latencies_ms, bps, miners, adversary_share, blocks, seeds, base_seed: seed };
+            let results = experiment::run(&grid);
+            log::report("experiment", &experiment::to_table(&results));
+            if let Some(path) = out {
+                fs::write(&path, experiment::to_csv(&results)).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+        }
+        Some(Command::TimeWarp { median_windows, share, warp_ms, hashrate, blocks, window, target_ms, delay_ms, seed }) => {
+            let config = WarpConfig { hashrate, share, warp_ms, delay_ms, blocks };
+            log::report("timewarp", &timewarp::experiment(&Daa::new(window, target_ms), &median_windows, &config, seed))
+        }
+        Some(Command::Audit { blocks, k, latency_ms, show, seed }) => {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag.k_mode = KMode::Fixed(k);
+            let config = NetworkConfig { latency_ms, ..NetworkConfig::default() };
+            Network::new(config, seed).run(&mut dag, blocks);
+
+            let violations = dag.verify_blue_set();
+            let blues = dag.blocks().filter(|b| b.color() == Color::Blue).count();
+            log::report("audit", &audit::report(&violations, blues, show));
+            if !violations.is_empty() {
+                return Err(Failure::Violations);
+            }
+        }
+        Some(Command::Forensics { log, blocks, hashrates, grace, stale_lag, silence, burst, clock_tolerance, show, seed }) => {
+            let dag = match log {
+                Some(path) => rebuild(&path)?,
+                None => {
+                    let mut dag = ToyDag::new();
+                    dag.verbose = false;
+                    Network::new(NetworkConfig { hashrates, ..NetworkConfig::default() }, seed).run(&mut dag, blocks);
+                    dag
+                }
+            };
+            let findings = Forensics { grace, stale_lag, silence, burst, clock_tolerance }.inspect(&dag);
+            log::report("forensics", &forensics::report(&dag, &findings, show))
+        }
+        Some(Command::CompareOrder { protocol, against, blocks, seed }) => {
+            log::report("consensus", &consensus::compare(protocol.rule().as_ref(), against.rule().as_ref(), blocks, seed))
+        }
+        Some(Command::Dual { left, right, log, blocks, interval_ms, delay_ms, limit, seed }) => {
+            let entries = match log {
+                Some(path) => replay::read_log(&path)?,
+                None => dual::topology(blocks, interval_ms, delay_ms, seed),
+            };
+            log::report("dual", &dual::run(left, right, &entries)?.report(limit))
+        }
+        Some(Command::ChainVsDag { intervals_ms, delay_ms, blocks, seed }) => {
+            log::report("consensus", &consensus::chain_vs_dag(&intervals_ms, delay_ms, blocks, seed))
+        }
+        Some(Command::Frontier { bps, delays_ms, k, blocks, confirm_depth, seed, out }) => {
+            let points = frontier::run(&bps, &delays_ms, k, blocks, confirm_depth, seed);
+            log::report("frontier", &frontier::report(&points, k, blocks, confirm_depth, seed));
+            if let Some(path) = out {
+                fs::write(&path, frontier::to_csv(&points)).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+        }
+        Some(Command::Slice { blocks, anchor, depth, seed }) => {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            let mut rng = StdRng::seed_from_u64(seed);
+            for i in 1..=blocks {
+                simulation_step(&mut dag, &mut rng, i);
+            }
+            let anchor = anchor.unwrap_or(dag.selected_parent());
+            let slice = dag.dag_slice(anchor, depth).ok_or(format!("unknown anchor block {}", anchor))?;
+            // Data for front-ends, printed as is whatever the output flags say
+            println!("{}", serde_json::to_string_pretty(&slice).unwrap());
+        }
+        Some(Command::Repl) => {
+            let stdin = std::io::stdin();
+            repl::Repl::new().run(stdin.lock(), std::io::stdout()).map_err(|e| e.to_string())?;
+        }
+        Some(Command::Replay { path, delay_ms, tui }) => run_replay(&path, delay_ms, tui)?,
+        Some(Command::Ingest { capacity, orphan_limit }) => run_ingest(capacity, orphan_limit)?,
+        Some(Command::Genesis { specs, blocks, seed }) => log::report("genesis", &genesis::compare(&specs, blocks, seed)?),
+        Some(Command::Diff { left, right, limit }) => log::report("diff", &rebuild(&left)?.diff(&rebuild(&right)?).report(limit)),
+        Some(Command::Check { log, blocks, show, seed }) => {
+            let dag = match log {
+                Some(path) => rebuild(&path)?,
+                None => {
+                    let mut dag = ToyDag::new();
+                    dag.verbose = false;
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    for i in 1..=blocks {
+                        simulation_step(&mut dag, &mut rng, i);
+                    }
+                    dag
+                }
+            };
+            let violations = dag.check_integrity();
+            log::report("integrity", &integrity::report(&violations, dag.block_count(), show));
+            if !violations.is_empty() {
+                return Err(Failure::Violations);
+            }
+        }
+        Some(Command::Memory { blocks, seed }) => {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            let mut rng = StdRng::seed_from_u64(seed);
+            for i in 1..=blocks {
+                simulation_step(&mut dag, &mut rng, i);
+            }
+            log::report("memory", &dag.memory_report().map_err(|e| e.to_string())?);
+        }
+        Some(Command::Export { format, blocks, out, seed }) => {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            let mut rng = StdRng::seed_from_u64(seed);
+            for i in 1..=blocks {
+                simulation_step(&mut dag, &mut rng, i);
+            }
+            let text = match format {
+                ExportFormat::Dot => export::to_dot(&dag),
+                ExportFormat::Graphml => export::to_graphml(&dag),
+                ExportFormat::Cytoscape => export::to_cytoscape(&dag),
+            };
+            match out {
+                Some(path) => fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))?,
+                None => print!("{}", text), // Data, printed as is like the file would hold it
+            }
+        }
+        #[cfg(feature = "rpc")]
+        Some(Command::Serve { addr, blocks, tick_ms }) => {
+            run_server(&addr, blocks, tick_ms).map_err(|e| format!("{}: {}", addr, e))?
+        }
+        #[cfg(feature = "tui")]
+        Some(Command::Tui { blocks, tick_ms }) => tui::run(blocks, tick_ms).map_err(|e| e.to_string())?,
+    }
+    Ok(())
+}
+
+// Rebuild whatever the block store at `path` already holds, then keep writing
+// new blocks through to it
+fn resume(path: &Path) -> std::io::Result<ToyDag> {
+    let store = FileStore::open(path)?;
+    let mut dag = ToyDag::from_store(&store)?;
+    if !store.is_empty() {
+        log::info("store", "💾", &format!("Resumed {} blocks from {}", store.len(), path.display()));
+    }
+    dag.verbose = true;
+    dag.subscribe(Arc::new(Mutex::new(Persister::new(store))));
+    Ok(dag)
+}
+
+fn run_replay(path: &Path, delay_ms: u64, tui: bool) -> Result<(), String> {
+    let entries = replay::read_log(path)?;
+    if tui {
+        #[cfg(feature = "tui")]
+        return tui::replay(entries, delay_ms.max(1)).map_err(|e| e.to_string());
+        #[cfg(not(feature = "tui"))]
+        return Err("built without the tui feature".to_string());
+    }
+
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    log::info("replay", "⏪", &format!("Replaying {} insertions from {}", entries.len(), path.display()));
+    for entry in &entries {
+        if !replay::apply(&mut dag, entry)? {
+            log::info("replay", "", &format!("   block {:>5} declined", entry.id));
+            continue;
+        }
+        let block = &dag[entry.id];
+        log::info(
+            "replay",
+            "➕",
+            &format!(
+                "block {:>5} parents {:?} → {:?}, blue score {}, virtual selected parent {}",
+                entry.id,
+                entry.parents,
+                block.color(),
+                block.blue_score(),
+                dag.selected_parent()
+            ),
+        );
+        if delay_ms > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+        }
+    }
+    log::report("stats", &dag.stats.report(&dag));
+    Ok(())
+}
+
+// Feed stdin into a DAG through a block ingest channel, the way a network
+// reader would
+fn run_ingest(capacity: usize, orphan_limit: usize) -> Result<(), String> {
+    let (feed, mut queue) = ingest::channel(capacity, orphan_limit);
+    let reader = std::thread::spawn(move || -> Result<(), String> {
+        for (n, line) in std::io::stdin().lines().enumerate() {
+            let line = line.map_err(|e| format!("stdin: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: LogEntry = serde_json::from_str(&line).map_err(|e| format!("stdin:{}: {}", n + 1, e))?;
+            if feed.send(entry.block()).is_err() {
+                break; // The DAG side is gone
+            }
+        }
+        Ok(())
+    });
+
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    let summary = queue.run(&mut dag);
+    reader.join().expect("the reader thread doesn't panic")?;
+
+    log::report(
+        "ingest",
+        &format!(
+            "📥 Ingested {} of {} blocks: {} arrived early, {} duplicates, {} refused, {} dropped from a full orphan pool, {} still orphaned",
+            summary.inserted,
+            summary.received,
+            summary.parked,
+            summary.duplicates,
+            summary.refused,
+            summary.evicted,
+            queue.orphans.len()
+        ),
+    );
+    log::report("stats", &dag.stats.report(&dag));
+    Ok(())
+}
+
+// The DAG a --record log describes
+fn rebuild(path: &Path) -> Result<ToyDag, String> {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    for entry in &replay::read_log(path)? {
+        replay::apply(&mut dag, entry)?;
+    }
+    Ok(dag)
+}
+
+#[cfg(feature = "rpc")]
+fn run_server(addr: &str, blocks: u64, tick_ms: u64) -> std::io::Result<()> {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    let dag = Arc::new(Mutex::new(dag));
+    let bound = rpc::serve(dag.clone(), addr)?;
+    log::info("rpc", "📡", &format!("Serving JSON-RPC on {} ({} blocks, one every {} ms)", bound, blocks, tick_ms));
+
+    let mut rng = rand::thread_rng();
+    for i in 1..=blocks {
+        std::thread::sleep(std::time::Duration::from_millis(tick_ms));
+        simulation_step(&mut dag.lock().unwrap(), &mut rng, i);
+    }
+    log::info("rpc", "", "Simulation finished; still serving until interrupted");
+    loop {
+        std::thread::park();
+    }
+}
+
+fn run_detection(blocks: u64, hashrates: Vec<f64>, window: usize, threshold: f64, adversaries: &[u32], coinbase: Coinbase, seed: u64) {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+
+    let detector = Arc::new(Mutex::new(ChainQualityDetector::new(window, threshold)));
+    dag.subscribe(detector.clone());
+
+    let total: f64 = hashrates.iter().sum();
+    let adversary_hashrate: f64 = adversaries.iter().filter_map(|&m| hashrates.get(m as usize)).sum::<f64>() / total;
+    let config = NetworkConfig { hashrates, ..NetworkConfig::default() };
+    Network::new(config, seed).run(&mut dag, blocks);
+
+    let detector = detector.lock().unwrap();
+    let mut alerts = format!("{} chain-quality alert(s) over {} blocks\n", detector.alerts.len(), blocks);
+    let mut shares: Vec<(u32, f64)> = detector.shares().into_iter().collect();
+    shares.sort_by_key(|&(m, _)| m);
+    for (miner, share) in shares {
+        let _ = writeln!(alerts, "  miner {} holds {:.1}% of the last {} chain blocks", miner, share * 100.0, window);
+    }
+    log::report("alerts", &alerts);
+    log::report("quality", &quality::report(&quality::measure(&dag, adversaries), adversary_hashrate));
+    log::report("earnings", &dag.earnings(&coinbase).report(|m| m.map_or("unknown".to_string(), |m| format!("miner {}", m))));
+}
+
+// Set up the log facade from the global output flags before anything prints
+fn init_log(cli: &Cli) -> Result<(), String> {
+    let verbosity = match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (_, true) => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    };
+    let (text, json): (bool, Option<Box<dyn std::io::Write + Send>>) = match &cli.log_json {
+        None => (true, None),
+        Some(path) if path.as_os_str() == "-" => (false, Some(Box::new(std::io::stdout()))),
+        Some(path) => {
+            let file = fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            (true, Some(Box::new(LineWriter::new(file))))
+        }
+    };
+    let color = if cli.no_color { Some(false) } else { None };
+    log::init(Logger { verbosity, emoji: !cli.no_emoji, color, text, json });
+    Ok(())
+}
+
+// Consensus and StitchBot settings for the default simulation
+struct Rules {
+    merge_depth: Option<MergeDepth>,
+    mergeset_limit: Option<usize>,
+    max_parents: Option<usize>,
+    stitch_policy: Box<dyn StitchPolicy>,
+}
+
+// Files the default run reads and writes
+struct Outputs<'a> {
+    stats_csv: Option<&'a Path>,
+    metrics_out: Option<&'a Path>,
+    record: Option<&'a Path>,
+    store: Option<&'a Path>,
+    report: Option<&'a Path>,
+}
+
+// Prints each traced insertion and waits for a key: enter steps on, `c`
+// runs to the end without stopping, `q` quits
+struct TerminalStepper {
+    paused: bool,
+}
+
+impl Stepper for TerminalStepper {
+    fn on_step(&mut self, _dag: &ToyDag, step: &Step) {
+        log::report("step", &step.report());
+        if !self.paused {
+            return;
+        }
+        print!("[enter] next, c continue, q quit: "); // A prompt, not output: no newline, never logged
+        let _ = std::io::stdout().flush();
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+            self.paused = false; // Nothing left to read; run on
+        }
+        match line.trim() {
+            "c" => self.paused = false,
+            "q" => process::exit(0),
+            _ => {}
+        }
+    }
+}
+
+fn run_simulation(outputs: Outputs, rules: Rules, confirm_depth: u64, red_target: f64, step: bool) -> Result<(), String> {
+    let Outputs { stats_csv, metrics_out, record, store, report } = outputs;
+    let mut dag = match store {
+        Some(path) => resume(path).map_err(|e| format!("{}: {}", path.display(), e))?,
+        None => ToyDag::new(),
+    };
+    dag.merge_depth = rules.merge_depth;
+    dag.mergeset_limit = rules.mergeset_limit;
+    dag.max_parents = rules.max_parents;
+    dag.stitch_policy = rules.stitch_policy;
+    dag.track_anticones = true;
+    if step {
+        dag.stepper = Some(Arc::new(Mutex::new(TerminalStepper { paused: true })));
+    }
+    if let Some(path) = record {
+        let file = fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        dag.subscribe(Arc::new(Mutex::new(Recorder::new(LineWriter::new(file)))));
+    }
+    let confirmations = Arc::new(Mutex::new(ConfirmationTracker::new(confirm_depth)));
+    dag.subscribe(confirmations.clone());
+    let mut rng = rand::thread_rng();
+    let events = dag.event_channel();
+
+    log::info("run", "", &format!("Starting high-throughput simulation with k={} clustering and StitchBot...", K));
+
+    let start = dag.block_count() as u64 - 1; // Blocks already there when resuming
+    for i in start + 1..=start + 100 {
+        simulation_step(&mut dag, &mut rng, i);
+
+        if i % 20 == 0 {
+            toydag_viz::print_dag(&dag);
+        }
+    }
+
+    let anticones = dag.stats.anticone_report(&dag, red_target);
+    let finalized = events.try_iter().filter(|e| matches!(e, DagEvent::Finalized { .. })).count();
+    let finality = format!("🏁 {} chain blocks finalized (depth {})", finalized, FINALITY_DEPTH);
+    let confirmed = confirmations.lock().unwrap().report();
+    log::report("stats", &dag.stats.report(&dag));
+    log::report("anticone", &anticones);
+    log::report("finality", &finality);
+    log::report("confirmations", &confirmed);
+    if let Some(path) = report {
+        let mut run = RunReport::new("Toy GHOSTDAG run", &dag);
+        run.param("blocks", dag.block_count() - 1);
+        run.param("k", K);
+        run.param("confirm_depth", confirm_depth);
+        run.param("merge_depth", dag.merge_depth.as_ref().map_or("off".to_string(), |m| m.depth.to_string()));
+        run.param("mergeset_limit", dag.mergeset_limit.map_or("off".to_string(), |l| l.to_string()));
+        run.param("max_parents", dag.max_parents.map_or("off".to_string(), |p| p.to_string()));
+        run.param("stitch_policy", dag.stitch_policy.name());
+        run.param("red_target", red_target);
+        run.section("Anticone sizes", &anticones);
+        run.section("Finality", &format!("{}\n", finality));
+        run.section("Confirmations", &confirmed);
+        run.write(path)?;
+    }
+    if let Some(path) = stats_csv {
+        dag.stats.write_csv(&dag, path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    if let Some(path) = metrics_out {
+        metrics::write(&dag.stats.timeline, path)?;
+    }
+
+    // Issue a finality receipt for an early tx and check it against a snapshot
+    let snapshot = dag.clone();
+    if let Some(receipt) = Receipt::issue(&dag, 1, RECEIPT_KEY) {
+        log::info("receipt", "🧾", &receipt.to_string());
+        match receipt.verify(&snapshot, RECEIPT_KEY) {
+            Ok(()) => log::info("receipt", "✅", "Receipt verified against snapshot"),
+            Err(e) => log::warn("receipt", "❌", &format!("Receipt rejected: {}", e)),
+        }
+    }
+    Ok(())
+}
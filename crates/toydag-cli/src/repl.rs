@@ -0,0 +1,278 @@
+// Interactive shell for building a DAG by hand and poking at it. Each line is
+// one command; `help` lists them. Blocks made here can be saved as a --record
+// log, so a case built by hand can be replayed, diffed or kept as a fixture.
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use toydag_core::ToyDag;
+use toydag_core::step::{SharedStepper, Step, Stepper};
+use toydag_sim::replay::{self, LogEntry};
+use toydag_viz::export;
+
+const HELP: &str = "\
+add <parent>...     new block on the given parents (next free id)
+color <id>          color, blue score, blue work and selected parent of a block
+past <id> [depth]   blocks in the past of <id>, at most [depth] layers down
+future <id> [depth] blocks in the future of <id>, at most [depth] layers up
+anticone <id> [h]   blocks neither before nor after <id>, down to [h] layers below it
+horizon <id>        layers between <id> and the finality point
+tips                current tips, selected tip first
+chain               selected chain from genesis
+order               GHOSTDAG total order
+step on|off         show mergeset, coloring and virtual change for every `add`
+stitch              let StitchBot merge tips if it wants to
+stats               run summary
+dot <path>          write the DAG as Graphviz DOT
+save <path>         write the blocks as a replay log
+load <path>         insert the blocks of a replay log
+reset               start over from genesis
+help                this list
+quit                leave";
+
+pub struct Repl {
+    dag: ToyDag,
+    steps: Option<Arc<Mutex<Steps>>>, // Traces of the `add`s since stepping went on
+}
+
+// Collects step reports for `add` to print
+#[derive(Default)]
+struct Steps(String);
+
+impl Stepper for Steps {
+    fn on_step(&mut self, _dag: &ToyDag, step: &Step) {
+        self.0.push_str(&step.report());
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl { dag: fresh(), steps: None }
+    }
+
+    // Read commands until EOF or `quit`. Errors are printed and the shell carries on.
+    pub fn run(&mut self, input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "🧮 toy DAG shell: genesis is block {}. Type `help` for commands.", self.dag.genesis())?;
+        write!(out, "> ")?;
+        out.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.first() {
+                None => {}
+                Some(&"quit") | Some(&"exit") => return Ok(()),
+                Some(_) => match self.execute(&words) {
+                    Ok(reply) => write!(out, "{}", reply)?,
+                    Err(e) => writeln!(out, "error: {}", e)?,
+                },
+            }
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        writeln!(out)
+    }
+
+    fn execute(&mut self, words: &[&str]) -> Result<String, String> {
+        let mut out = String::new();
+        let args = &words[1..];
+        match words[0] {
+            "add" => {
+                let parents = ids(args)?;
+                let id = self.dag.create_block(parents).map_err(|e| e.to_string())?;
+                if let Some(steps) = &self.steps {
+                    out = std::mem::take(&mut steps.lock().unwrap().0);
+                }
+                let block = &self.dag[id];
+                let _ = writeln!(out, "➕ block {} {:?}, blue score {}", id, block.color(), block.blue_score());
+            }
+            "color" => {
+                let id = one_id(args)?;
+                let block = self.dag.get_block(id).map_err(|e| e.to_string())?;
+                let _ = writeln!(
+                    out,
+                    "block {}: {:?}, blue score {}, blue work {}, selected parent {}",
+                    id,
+                    block.color(),
+                    block.blue_score(),
+                    block.blue_work(),
+                    block.selected_parent().map_or("-".to_string(), |sp| sp.to_string())
+                );
+            }
+            "past" | "future" | "anticone" => {
+                let (id, depth) = match *ids(args)?.as_slice() {
+                    [id] => (id, usize::MAX),
+                    [id, depth] => (id, depth as usize),
+                    _ => return Err("expected a block id and an optional depth".to_string()),
+                };
+                let cone = match words[0] {
+                    "past" => self.dag.past_within(id, depth),
+                    "future" => self.dag.future_within(id, depth),
+                    _ => self.dag.anticone_within(id, depth),
+                };
+                let mut set: Vec<u64> = cone.map_err(|e| e.to_string())?.collect();
+                set.sort_unstable();
+                let _ = writeln!(out, "{} of {} ({}): {:?}", words[0], id, set.len(), set);
+            }
+            "horizon" => {
+                let id = one_id(args)?;
+                let horizon = self.dag.finality_horizon(id).map_err(|e| e.to_string())?;
+                let _ = writeln!(out, "block {}: {} layers above finality point {}", id, horizon, self.dag.finality_point());
+            }
+            "tips" => {
+                let _ = writeln!(out, "{:?}", self.dag.virtual_parents());
+            }
+            "chain" => {
+                let _ = writeln!(out, "{:?}", self.dag.selected_chain());
+            }
+            "order" => {
+                let _ = writeln!(out, "{:?}", self.dag.ordered_blocks());
+            }
+            "step" => {
+                let on = match args {
+                    ["on"] => true,
+                    ["off"] => false,
+                    _ => return Err("expected `step on` or `step off`".to_string()),
+                };
+                self.steps = on.then(|| Arc::new(Mutex::new(Steps::default())));
+                self.dag.stepper = self.steps.clone().map(|steps| steps as SharedStepper);
+                let _ = writeln!(out, "stepping {}", if on { "on" } else { "off" });
+            }
+            "stitch" => {
+                let before = self.dag.next_id();
+                self.dag.stitch_if_needed();
+                let made = self.dag.next_id() - before;
+                let _ = writeln!(out, "🧵 {} merge block(s)", made);
+            }
+            "stats" => out = self.dag.stats.report(&self.dag),
+            "dot" => {
+                let path = one_path(args)?;
+                fs::write(path, export::to_dot(&self.dag)).map_err(|e| format!("{}: {}", path, e))?;
+                let _ = writeln!(out, "wrote {}", path);
+            }
+            "save" => {
+                let path = one_path(args)?;
+                fs::write(path, self.log()).map_err(|e| format!("{}: {}", path, e))?;
+                let _ = writeln!(out, "wrote {} blocks to {}", self.dag.block_count() - 1, path);
+            }
+            "load" => {
+                let entries = replay::read_log(Path::new(one_path(args)?))?;
+                let mut inserted = 0;
+                for entry in &entries {
+                    inserted += replay::apply(&mut self.dag, entry)? as usize;
+                }
+                let _ = writeln!(out, "inserted {} of {} blocks", inserted, entries.len());
+            }
+            "reset" => {
+                self.dag = fresh();
+                self.dag.stepper = self.steps.clone().map(|steps| steps as SharedStepper);
+                let _ = writeln!(out, "back to genesis");
+            }
+            "help" => {
+                let _ = writeln!(out, "{}", HELP);
+            }
+            other => return Err(format!("unknown command '{}', try `help`", other)),
+        }
+        Ok(out)
+    }
+
+    // Every block but genesis, parents first, one JSON entry per line
+    fn log(&self) -> String {
+        let mut out = String::new();
+        for id in self.dag.ordered_blocks().into_iter().filter(|&id| id != self.dag.genesis()) {
+            let block = &self.dag[id];
+            let entry = LogEntry {
+                id,
+                parents: block.parents().to_vec(),
+                txs: block.txs().to_vec(),
+                miner: block.miner(),
+                timestamp: block.timestamp(),
+                version: block.version(),
+            };
+            let _ = writeln!(out, "{}", serde_json::to_string(&entry).expect("log entries always serialize"));
+        }
+        out
+    }
+}
+
+fn fresh() -> ToyDag {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag
+}
+
+fn ids(args: &[&str]) -> Result<Vec<u64>, String> {
+    args.iter().map(|a| a.parse().map_err(|_| format!("'{}' is not a block id", a))).collect()
+}
+
+fn one_id(args: &[&str]) -> Result<u64, String> {
+    match ids(args)?.as_slice() {
+        &[id] => Ok(id),
+        _ => Err("expected one block id".to_string()),
+    }
+}
+
+fn one_path<'a>(args: &[&'a str]) -> Result<&'a str, String> {
+    match args {
+        &[path] => Ok(path),
+        _ => Err("expected one path".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    // Feed `script` to a fresh shell and return everything it printed
+    fn session(script: &str) -> String {
+        let mut out = Vec::new();
+        Repl::new().run(Cursor::new(script), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn scripted_session_builds_and_queries_a_dag() {
+        let out = session("add 0\nadd 0\nadd 1 2\ncolor 3\npast 3\nanticone 1\nchain\ntips\nquit\nadd 3\n");
+        assert!(out.starts_with("🧮 toy DAG shell: genesis is block 0."));
+        assert!(out.contains("➕ block 1 Blue, blue score 1\n"));
+        assert!(out.contains("➕ block 3 Blue, blue score 3\n"));
+        assert!(out.contains("block 3: Blue, blue score 3, blue work 3, selected parent 1\n"));
+        assert!(out.contains("past of 3 (3): [0, 1, 2]\n"));
+        assert!(out.contains("anticone of 1 (1): [2]\n"));
+        assert!(out.contains("> [3]\n")); // Tips
+        assert!(!out.contains("block 4")); // Nothing after `quit`
+        assert!(out.ends_with("> "));
+    }
+
+    #[test]
+    fn bad_commands_print_an_error_and_the_shell_carries_on() {
+        let out = session("frobnicate\nadd x\nadd 9\ncolor\npast 1 2 3\nstep maybe\ncolor 0\n");
+        let errors: Vec<&str> = out.lines().filter_map(|l| l.strip_prefix("> error: ").or(l.strip_prefix("error: "))).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "unknown command 'frobnicate', try `help`",
+                "'x' is not a block id",
+                "block 1 references unknown parent 9",
+                "expected one block id",
+                "expected a block id and an optional depth",
+                "expected `step on` or `step off`",
+            ]
+        );
+        assert!(out.contains("block 0: Blue, blue score 0, blue work 0, selected parent -\n"));
+        assert!(out.ends_with("> \n")); // EOF without `quit`
+    }
+
+    #[test]
+    fn stepping_traces_each_add_until_turned_off() {
+        let out = session("step on\nadd 0\nstep off\nadd 1\nreset\nchain\n");
+        assert!(out.contains("stepping on\n"));
+        assert_eq!(out.matches("➕ block").count(), 2);
+        let traced = &out[out.find("stepping on").unwrap()..out.find("➕ block 1").unwrap()];
+        assert!(traced.lines().count() > 2);
+        assert!(out.contains("back to genesis\n> [0]\n"));
+    }
+}
@@ -0,0 +1,72 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::{STALE_WINDOW, ToyDag};
+
+const SIZES: [u64; 3] = [1_000, 10_000, 100_000];
+const SEED: u64 = 7;
+
+// Fork-heavy DAG of `blocks` blocks, stitched every few inserts like the simulator
+fn build_dag(blocks: u64) -> ToyDag {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for i in 1..=blocks {
+        add_block(&mut dag, &mut rng);
+        if i.is_multiple_of(5) {
+            dag.stitch_if_needed();
+        }
+    }
+    dag
+}
+
+fn add_block(dag: &mut ToyDag, rng: &mut StdRng) -> u64 {
+    let next = dag.block_count() as u64;
+    let recent: Vec<u64> = (next.saturating_sub(STALE_WINDOW)..next).collect();
+    let num_parents = rng.gen_range(1..=3);
+    let parents: Vec<u64> = recent.choose_multiple(rng, num_parents).copied().collect();
+    dag.create_block(parents).unwrap()
+}
+
+fn core_operations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dag");
+    group.sample_size(10);
+
+    for &size in &SIZES {
+        let dag = build_dag(size);
+        let tip = dag.selected_parent();
+        let mid = size / 2;
+
+        group.bench_with_input(BenchmarkId::new("insert", size), &dag, |b, dag| {
+            b.iter_batched_ref(
+                || (dag.clone(), StdRng::seed_from_u64(SEED)),
+                |(dag, rng)| add_block(dag, rng),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("anticone", size), &dag, |b, dag| {
+            b.iter(|| dag.anticone_size(black_box(mid), black_box(tip)))
+        });
+        group.bench_with_input(BenchmarkId::new("is_ancestor", size), &dag, |b, dag| {
+            b.iter(|| dag.is_ancestor(black_box(mid), black_box(tip)))
+        });
+        group.bench_with_input(BenchmarkId::new("past_set", size), &dag, |b, dag| {
+            b.iter(|| dag.past_set(black_box(tip)).unwrap().len())
+        });
+        group.bench_with_input(BenchmarkId::new("ordering", size), &dag, |b, dag| {
+            b.iter(|| dag.ordered_blocks().len())
+        });
+        group.bench_with_input(BenchmarkId::new("chain_depth", size), &dag, |b, dag| {
+            b.iter(|| dag.chain_ancestor_at_depth(black_box(tip), black_box(mid)))
+        });
+        group.bench_with_input(BenchmarkId::new("virtual", size), &dag, |b, dag| {
+            b.iter(|| dag.heaviest_blue_tip())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, core_operations);
+criterion_main!(benches);
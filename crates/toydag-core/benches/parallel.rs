@@ -0,0 +1,74 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::ThreadPool;
+
+use toydag_core::{BLOCK_VERSION, NewBlock, ToyDag};
+
+const SIZES: [u64; 2] = [1_000, 10_000];
+const WIDTH: u64 = 16; // Blocks per round, all building on the round before
+const SEED: u64 = 7;
+
+// Round-based topology: every block of a round picks parents from the previous
+// round only, so each round is one batch layer the parallel path can spread out
+fn rounds(blocks: u64) -> Vec<NewBlock> {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut previous = vec![0];
+    let mut out = Vec::new();
+    let mut id = 1;
+    while id <= blocks {
+        let mut round = Vec::new();
+        for _ in 0..WIDTH.min(blocks - id + 1) {
+            let num_parents = rng.gen_range(1..=previous.len().min(4));
+            let parents = previous.choose_multiple(&mut rng, num_parents).copied().collect();
+            out.push(NewBlock { id, parents, txs: vec![], miner: None, timestamp: id, version: BLOCK_VERSION });
+            round.push(id);
+            id += 1;
+        }
+        previous = round;
+    }
+    out
+}
+
+fn pool(threads: usize) -> ThreadPool {
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap()
+}
+
+// The same work on a one-thread pool (the serial path) and on the full pool
+fn serial_vs_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel");
+    group.sample_size(10);
+    let pools = [("serial", pool(1)), ("parallel", pool(0))];
+
+    for &size in &SIZES {
+        let batch = rounds(size);
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.insert_batch(batch.clone());
+
+        for (name, pool) in &pools {
+            group.bench_with_input(BenchmarkId::new(format!("insert_batch/{}", name), size), &batch, |b, batch| {
+                b.iter_batched(
+                    || {
+                        let mut fresh = ToyDag::new();
+                        fresh.verbose = false;
+                        (fresh, batch.clone())
+                    },
+                    |(mut fresh, batch)| pool.install(|| fresh.insert_batch(batch)),
+                    BatchSize::LargeInput,
+                )
+            });
+            group.bench_with_input(BenchmarkId::new(format!("ordering/{}", name), size), &dag, |b, dag| {
+                b.iter(|| pool.install(|| dag.ordered_blocks().len()))
+            });
+            group.bench_with_input(BenchmarkId::new(format!("virtual_full/{}", name), size), &dag, |b, dag| {
+                b.iter(|| pool.install(|| dag.heaviest_blue_tip_full()))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, serial_vs_parallel);
+criterion_main!(benches);
@@ -0,0 +1,114 @@
+use std::fmt::Write as _;
+
+use crate::{Color, ToyDag};
+
+// A blue block that sees more than k other blue blocks in its anticone.
+// Colors are the virtual's GHOSTDAG, which keeps the blue set a k-cluster, so
+// under the k the DAG was colored with there should be none. Checking
+// against a smaller k, or after an adaptive k has moved, shows where the
+// bound is tight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlueSetViolation {
+    pub block: u64,
+    pub k: usize,
+    pub blue_anticone: Vec<u64>, // Each one pairs with `block` as an offending pair
+}
+
+impl ToyDag {
+    // Check the blue set against the current k
+    pub fn verify_blue_set(&self) -> Vec<BlueSetViolation> {
+        self.verify_blue_set_for(self.k_mode.k())
+    }
+
+    // Quadratic in the number of blue blocks: each one gets its own past and
+    // future walked
+    pub fn verify_blue_set_for(&self, k: usize) -> Vec<BlueSetViolation> {
+        let mut blues: Vec<u64> = self.blocks.values().filter(|b| b.color == Color::Blue).map(|b| b.id).collect();
+        blues.sort_unstable();
+
+        let mut violations = Vec::new();
+        for &b in &blues {
+            let past = self.past_cone(b);
+            let future = self.future_cone(b);
+            let blue_anticone: Vec<u64> = blues
+                .iter()
+                .copied()
+                .filter(|id| !past.contains(id) && !future.contains(id))
+                .collect();
+            if blue_anticone.len() > k {
+                violations.push(BlueSetViolation { block: b, k, blue_anticone });
+            }
+        }
+        violations
+    }
+}
+
+// Summary plus the first `limit` violations with their offending pairs
+pub fn report(violations: &[BlueSetViolation], blues: usize, limit: usize) -> String {
+    let mut out = String::new();
+    if violations.is_empty() {
+        let _ = writeln!(out, "✅ Blue set is a k-cluster: all {} blue blocks see at most k blues in their anticone", blues);
+        return out;
+    }
+
+    let pairs: usize = violations.iter().map(|v| v.blue_anticone.len()).sum::<usize>() / 2;
+    let _ = writeln!(
+        out,
+        "❌ {} of {} blue blocks break the k = {} bound ({} offending pairs)",
+        violations.len(),
+        blues,
+        violations[0].k,
+        pairs
+    );
+    for v in violations.iter().take(limit) {
+        let _ = writeln!(out, "  block {:>5}: {} blues in anticone {:?}", v.block, v.blue_anticone.len(), v.blue_anticone);
+    }
+    if violations.len() > limit {
+        let _ = writeln!(out, "  … {} more", violations.len() - limit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Three side blocks off genesis all color blue, so with k = 1 each sees
+    // two blues it should not
+    #[test]
+    fn parallel_blues_over_k_are_reported() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for _ in 0..3 {
+            dag.create_block(vec![0]).unwrap();
+        }
+
+        assert!(dag.verify_blue_set_for(2).is_empty());
+        let violations = dag.verify_blue_set_for(1);
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0], BlueSetViolation { block: 1, k: 1, blue_anticone: vec![2, 3] });
+    }
+
+    // Four siblings at k = 1: 1 and 2 are blue, 3 and 4 red. A block merging
+    // all but 2 colors 3 blue in its own view and becomes the virtual's
+    // selected parent; 2 then has it, 1 and 3 beside it and turns red, so the
+    // blue set stays a k-cluster.
+    #[test]
+    fn virtual_turns_a_stale_tip_red_to_keep_the_k_cluster() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = crate::knight::KMode::Fixed(1);
+        for _ in 0..4 {
+            dag.create_block(vec![0]).unwrap();
+        }
+        let colors: Vec<Color> = (1..=4).map(|id| dag[id].color()).collect();
+        assert_eq!(colors, vec![Color::Blue, Color::Blue, Color::Red, Color::Red]);
+        assert!(dag.verify_blue_set().is_empty());
+        assert_eq!(dag.verify_blue_set_for(0).len(), 2);
+
+        let c = dag.create_block(vec![1, 3, 4]).unwrap();
+        let colors: Vec<Color> = [1, 2, 3, 4, c].into_iter().map(|id| dag[id].color()).collect();
+        assert_eq!(colors, vec![Color::Blue, Color::Red, Color::Blue, Color::Red, Color::Blue]);
+        assert!(dag.verify_blue_set().is_empty());
+    }
+}
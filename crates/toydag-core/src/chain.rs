@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use crate::{Block, ToyDag};
+use crate::compact::{map_bytes, vec_bytes};
+use crate::score::depth_between;
+
+// Skip pointers along selected chains, so finding a chain ancestor by blue
+// score takes O(log n) steps instead of one per chain block. A block at chain
+// height h (selected-parent steps above genesis) points to its chain
+// ancestors at h - 1, h - 2, h - 4, ... for as many powers of two as h has
+// trailing zero bits, two pointers a block on average. Searching climbs to
+// blocks with ever more pointers, then halves the jump back down, the way a
+// deterministic skip list does.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChainIndex {
+    entries: HashMap<u64, ChainEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct ChainEntry {
+    height: u64,
+    skips: Vec<u64>, // skips[i] is the chain ancestor 2^i blocks down
+}
+
+impl ChainIndex {
+    pub(crate) fn memory(&self) -> usize {
+        map_bytes(&self.entries) + self.entries.values().map(|e| vec_bytes(&e.skips)).sum::<usize>()
+    }
+
+    // Index a block on top of its selected parent's entry
+    pub(crate) fn insert(&mut self, id: u64, selected_parent: Option<u64>) {
+        let Some(parent) = selected_parent else {
+            self.entries.insert(id, ChainEntry { height: 0, skips: Vec::new() });
+            return;
+        };
+        let height = self.entries[&parent].height + 1;
+        let mut skips = vec![parent];
+        // The block 2^i down has at least i trailing zeros, so it holds the pointer 2^i further
+        for i in 1..=height.trailing_zeros() as usize {
+            skips.push(self.entries[&skips[i - 1]].skips[i - 1]);
+        }
+        self.entries.insert(id, ChainEntry { height, skips });
+    }
+}
+
+impl ToyDag {
+    // Selected-parent steps from genesis to `block`
+    pub fn chain_height(&self, block: u64) -> Option<u64> {
+        self.chain_index.entries.get(&block).map(|e| e.height)
+    }
+
+    // Highest block on the selected chain of `from` (itself included) that
+    // `reached` holds for, which must keep holding further down. Climbs while
+    // even the longest pointer falls short; once one overshoots, the answer
+    // is within that jump, and each pointer taken after halves it.
+    fn chain_search(&self, from: u64, reached: impl Fn(&Block) -> bool) -> Option<u64> {
+        if reached(&self.blocks[&from]) {
+            return Some(from);
+        }
+        let mut current = from;
+        let mut tries = usize::MAX; // Pointers below the one known to overshoot; all while climbing
+        loop {
+            let skips = &self.chain_index.entries[&current].skips;
+            if skips.is_empty() {
+                return None; // Genesis, and not even that reaches
+            }
+            let usable = skips.len().min(tries);
+            match (0..usable).rev().find(|&i| !reached(&self.blocks[&skips[i]])) {
+                Some(i) => {
+                    if tries != usize::MAX || i + 1 < skips.len() {
+                        tries = i;
+                    }
+                    current = skips[i];
+                }
+                None => return Some(skips[0]),
+            }
+        }
+    }
+
+    // Whether `block` is on the selected chain from genesis to the virtual's
+    // selected parent: the chain block at the block's own chain height is
+    // the block itself
+    pub fn is_chain_block(&self, block: u64) -> bool {
+        let Some(height) = self.chain_height(block) else {
+            return false;
+        };
+        self.chain_search(self.selected_parent, |b| self.chain_index.entries[&b.id].height <= height) == Some(block)
+    }
+
+    // Highest selected-chain block at least `depth` blue score below the
+    // virtual's selected parent; None while the chain is still shallower
+    pub fn chain_block_at_depth(&self, depth: u64) -> Option<u64> {
+        self.chain_ancestor_at_depth(self.selected_parent, depth)
+    }
+
+    // Same, along the selected chain of any block; None for an unknown one too
+    pub fn chain_ancestor_at_depth(&self, from: u64, depth: u64) -> Option<u64> {
+        let top = self.blocks.get(&from)?.blue_score;
+        self.chain_search(from, |b| depth_between(top, b.blue_score) >= depth)
+    }
+
+    // Highest block on the selected chain of `tip` with blue score at most
+    // `score`. The finality point is re-anchored here after a violation, so
+    // finality carries on along the new chain.
+    pub(crate) fn chain_block_at_or_below(&self, tip: u64, score: u64) -> u64 {
+        self.chain_search(tip, |b| b.blue_score <= score).unwrap_or(self.genesis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::testing::{arb_parents, build};
+
+    // Chain 0 → 1 → 2 → 3 with a side block 4 off block 1
+    fn forked() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for id in 1..=3 {
+            dag.create_block(vec![id - 1]).unwrap();
+        }
+        dag.create_block(vec![1]).unwrap();
+        dag
+    }
+
+    #[test]
+    fn chain_membership_follows_selected_chain() {
+        let dag = forked();
+        let chain = dag.selected_chain();
+        assert_eq!(chain, vec![0, 1, 2, 3]);
+        for b in dag.blocks() {
+            assert_eq!(dag.is_chain_block(b.id()), chain.contains(&b.id()), "block {}", b.id());
+        }
+        assert!(!dag.is_chain_block(99));
+    }
+
+    #[test]
+    fn chain_block_at_depth_walks_down_by_blue_score() {
+        let dag = forked();
+        assert_eq!(dag.chain_block_at_depth(0), Some(3));
+        assert_eq!(dag.chain_block_at_depth(2), Some(1));
+        assert_eq!(dag.chain_block_at_depth(3), Some(0));
+        assert_eq!(dag.chain_block_at_depth(4), None);
+    }
+
+    // One selected parent at a time, as the lookups used to go
+    fn walk(dag: &ToyDag, from: u64, depth: u64) -> Option<u64> {
+        let top = dag[from].blue_score();
+        let mut current = Some(from);
+        while let Some(id) = current {
+            if top - dag[id].blue_score() >= depth {
+                return Some(id);
+            }
+            current = dag[id].selected_parent();
+        }
+        None
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn skip_pointers_land_where_walking_does(parents in arb_parents(60, 3)) {
+            let dag = build(&parents);
+            for block in dag.blocks() {
+                let top = block.blue_score();
+                for depth in 0..=top + 1 {
+                    prop_assert_eq!(dag.chain_ancestor_at_depth(block.id(), depth), walk(&dag, block.id(), depth));
+                }
+                let steps = std::iter::successors(block.selected_parent(), |&p| dag[p].selected_parent()).count();
+                prop_assert_eq!(dag.chain_height(block.id()), Some(steps as u64));
+            }
+        }
+
+        #[test]
+        fn chain_membership_matches_the_selected_chain(parents in arb_parents(60, 3)) {
+            let dag = build(&parents);
+            let chain = dag.selected_chain();
+            for block in dag.blocks() {
+                prop_assert_eq!(dag.is_chain_block(block.id()), chain.contains(&block.id()), "block {}", block.id());
+            }
+        }
+    }
+}
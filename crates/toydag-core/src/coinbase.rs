@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::Deserialize;
+
+use crate::{Color, ToyDag};
+
+// Reward minted by each block for its miner. Red blocks get `red_reward`
+// instead, usually less or nothing; the difference is never minted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Coinbase {
+    pub reward: u64,
+    #[serde(default)]
+    pub red_reward: u64,
+}
+
+impl Default for Coinbase {
+    fn default() -> Self {
+        Coinbase { reward: 50, red_reward: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MinerEarnings {
+    pub blue: u64,
+    pub red: u64,
+    pub earned: u64,
+}
+
+// Who got paid, keyed by miner; blocks without a known miner sit under None
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Earnings {
+    pub per_miner: BTreeMap<Option<u32>, MinerEarnings>,
+    pub supply: u64,    // Everything minted
+    pub forfeited: u64, // What red blocks would have earned on top, had they been blue
+}
+
+impl Earnings {
+    // `name` labels each miner, e.g. with scenario names
+    pub fn report(&self, name: impl Fn(Option<u32>) -> String) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "💰 Coinbase supply {} ({} forfeited by red blocks)", self.supply, self.forfeited);
+        let _ = writeln!(out, "   {:<14} {:>7} {:>5} {:>10} {:>7}", "miner", "blue", "red", "earned", "share");
+        for (&miner, e) in &self.per_miner {
+            let share = if self.supply == 0 { 0.0 } else { e.earned as f64 / self.supply as f64 };
+            let _ = writeln!(out, "   {:<14} {:>7} {:>5} {:>10} {:>6.1}%", name(miner), e.blue, e.red, e.earned, share * 100.0);
+        }
+        out
+    }
+}
+
+impl ToyDag {
+    // Pay every block but genesis by its current color
+    pub fn earnings(&self, coinbase: &Coinbase) -> Earnings {
+        let mut earnings = Earnings::default();
+        for block in self.blocks.values().filter(|b| b.id != self.genesis) {
+            let entry = earnings.per_miner.entry(block.miner).or_default();
+            let paid = match block.color {
+                Color::Blue => {
+                    entry.blue += 1;
+                    coinbase.reward
+                }
+                Color::Red => {
+                    entry.red += 1;
+                    earnings.forfeited += coinbase.reward.saturating_sub(coinbase.red_reward);
+                    coinbase.red_reward
+                }
+            };
+            entry.earned += paid;
+            earnings.supply += paid;
+        }
+        earnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // At k = 0 miner 8's block arrives beside miner 7's blue one and is red
+    #[test]
+    fn red_blocks_earn_the_reduced_reward() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = crate::knight::KMode::Fixed(0);
+        dag.insert_block(1, vec![0], vec![], Some(7));
+        dag.insert_block(2, vec![0], vec![], Some(8));
+        dag.insert_block(3, vec![1, 2], vec![], Some(7));
+        assert_eq!(dag.blocks[&2].color, Color::Red);
+
+        let earnings = dag.earnings(&Coinbase { reward: 50, red_reward: 10 });
+        assert_eq!(earnings.per_miner[&Some(7)], MinerEarnings { blue: 2, red: 0, earned: 100 });
+        assert_eq!(earnings.per_miner[&Some(8)], MinerEarnings { blue: 0, red: 1, earned: 10 });
+        assert_eq!(earnings.supply, 110);
+        assert_eq!(earnings.forfeited, 40);
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::mem::size_of;
+
+use crate::ghostdag::Ghostdag;
+use crate::score::BlueWork;
+use crate::store::BlockStore;
+use crate::{Block, Color, ToyDag};
+
+const NONE: u32 = u32::MAX;
+
+// Arena layout for big DAGs. Ids are interned to u32 slots in insertion order,
+// every field lives in its own column, and parent and tx lists are packed
+// into shared arrays with one offset per block. Parents always take a lower
+// slot than their children, so cones are a single sweep over a bitset.
+#[derive(Debug, Clone, Default)]
+pub struct CompactStore {
+    slots: HashMap<u64, u32>,
+    ids: Vec<u64>,
+    parent_ends: Vec<u32>, // Slot i's parents are parents[parent_ends[i - 1]..parent_ends[i]]
+    parents: Vec<u32>,
+    tx_ends: Vec<u32>,
+    txs: Vec<u64>,
+    red: BitSet,
+    blue_scores: Vec<u64>,
+    works: Vec<BlueWork>,
+    blue_works: Vec<BlueWork>,
+    past_sizes: Vec<u64>,
+    topo_depths: Vec<u32>,
+    selected_parents: Vec<u32>, // NONE for genesis
+    miners: Vec<u32>,           // NONE when unknown
+    timestamps: Vec<u64>,
+    versions: Vec<u32>,
+}
+
+impl CompactStore {
+    fn slot(&self, id: u64) -> io::Result<u32> {
+        self.slots.get(&id).copied().ok_or_else(|| invalid(&format!("block {} is not stored", id)))
+    }
+
+    fn range(ends: &[u32], slot: usize) -> std::ops::Range<usize> {
+        let start = if slot == 0 { 0 } else { ends[slot - 1] as usize };
+        start..ends[slot] as usize
+    }
+
+    // Ids of every stored block in the past of `id`, itself included
+    pub fn past_bits(&self, id: u64) -> io::Result<BitSet> {
+        let slot = self.slot(id)? as usize;
+        let mut past = BitSet::with_len(slot + 1);
+        past.insert(slot);
+        for s in (0..=slot).rev() {
+            if past.contains(s) {
+                for &p in &self.parents[Self::range(&self.parent_ends, s)] {
+                    past.insert(p as usize);
+                }
+            }
+        }
+        Ok(past)
+    }
+
+    pub fn past_size(&self, id: u64) -> io::Result<usize> {
+        Ok(self.past_bits(id)?.len() - 1)
+    }
+
+    pub fn is_ancestor(&self, ancestor: u64, block: u64) -> io::Result<bool> {
+        let a = self.slot(ancestor)? as usize;
+        Ok(self.past_bits(block)?.contains(a))
+    }
+
+    // Bytes held, counting allocated capacity
+    pub fn memory(&self) -> usize {
+        size_of::<Self>()
+            + map_bytes(&self.slots)
+            + vec_bytes(&self.ids)
+            + vec_bytes(&self.parent_ends)
+            + vec_bytes(&self.parents)
+            + vec_bytes(&self.tx_ends)
+            + vec_bytes(&self.txs)
+            + vec_bytes(&self.red.words)
+            + vec_bytes(&self.blue_scores)
+            + vec_bytes(&self.works)
+            + vec_bytes(&self.blue_works)
+            + vec_bytes(&self.past_sizes)
+            + vec_bytes(&self.topo_depths)
+            + vec_bytes(&self.selected_parents)
+            + vec_bytes(&self.miners)
+            + vec_bytes(&self.timestamps)
+            + vec_bytes(&self.versions)
+    }
+}
+
+impl BlockStore for CompactStore {
+    // Parents must already be stored, as they are for blocks put in insertion order
+    fn put(&mut self, block: &Block) -> io::Result<()> {
+        if self.slots.contains_key(&block.id) {
+            return Ok(());
+        }
+        let slot = u32::try_from(self.ids.len()).ok().filter(|&s| s != NONE).ok_or_else(|| invalid("arena is full"))?;
+        let parents = block.parents.iter().map(|&p| self.slot(p)).collect::<io::Result<Vec<u32>>>()?;
+        let selected_parent = block.selected_parent.map_or(Ok(NONE), |sp| self.slot(sp))?;
+        let parent_end = u32::try_from(self.parents.len() + parents.len()).map_err(|_| invalid("parent array is full"))?;
+        let tx_end = u32::try_from(self.txs.len() + block.txs.len()).map_err(|_| invalid("tx array is full"))?;
+        let topo_depth =
+            u32::try_from(block.topo_depth).map_err(|_| invalid(&format!("block {} is too deep for the arena", block.id)))?;
+
+        self.slots.insert(block.id, slot);
+        self.ids.push(block.id);
+        self.parents.extend(parents);
+        self.parent_ends.push(parent_end);
+        self.txs.extend(&block.txs);
+        self.tx_ends.push(tx_end);
+        if block.color == Color::Red {
+            self.red.insert(slot as usize);
+        }
+        self.blue_scores.push(block.blue_score);
+        self.works.push(block.work);
+        self.blue_works.push(block.blue_work);
+        self.past_sizes.push(block.past_size);
+        self.topo_depths.push(topo_depth);
+        self.selected_parents.push(selected_parent);
+        self.miners.push(block.miner.unwrap_or(NONE));
+        self.timestamps.push(block.timestamp);
+        self.versions.push(block.version);
+        Ok(())
+    }
+
+    fn get(&self, id: u64) -> io::Result<Option<Block>> {
+        let Some(&slot) = self.slots.get(&id) else {
+            return Ok(None);
+        };
+        let s = slot as usize;
+        let some = |v: u32| Some(v).filter(|&v| v != NONE);
+        Ok(Some(Block {
+            id,
+            parents: self.parents[Self::range(&self.parent_ends, s)].iter().map(|&p| self.ids[p as usize]).collect(),
+            color: if self.red.contains(s) { Color::Red } else { Color::Blue },
+            blue_score: self.blue_scores[s],
+            work: self.works[s],
+            blue_work: self.blue_works[s],
+            past_size: self.past_sizes[s],
+            topo_depth: self.topo_depths[s] as usize,
+            selected_parent: some(self.selected_parents[s]).map(|p| self.ids[p as usize]),
+            txs: self.txs[Self::range(&self.tx_ends, s)].to_vec(),
+            miner: some(self.miners[s]),
+            timestamp: self.timestamps[s],
+            version: self.versions[s],
+        }))
+    }
+
+    fn ids(&self) -> Vec<u64> {
+        self.ids.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+// Fixed-width set of slots, one bit each
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn with_len(bits: usize) -> Self {
+        BitSet { words: vec![0; bits.div_ceil(64)] }
+    }
+
+    pub fn insert(&mut self, bit: usize) {
+        if bit / 64 >= self.words.len() {
+            self.words.resize(bit / 64 + 1, 0);
+        }
+        self.words[bit / 64] |= 1 << (bit % 64);
+    }
+
+    pub fn contains(&self, bit: usize) -> bool {
+        self.words.get(bit / 64).is_some_and(|w| w & (1 << (bit % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+}
+
+pub(crate) fn vec_bytes<T>(v: &Vec<T>) -> usize {
+    v.capacity() * size_of::<T>()
+}
+
+// Buckets plus one control byte each, as in the std SwissTable
+pub(crate) fn map_bytes<K, V>(m: &HashMap<K, V>) -> usize {
+    m.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+impl ToyDag {
+    // Rough bytes held by everything the DAG keeps per block: the block map
+    // and child lists, tips, each block's GHOSTDAG view, soft-fork tallies,
+    // the chain index, red fates and the per-block stats
+    pub fn memory(&self) -> usize {
+        let blocks: usize = self.blocks.values().map(|b| vec_bytes(&b.parents) + vec_bytes(&b.txs)).sum();
+        let children: usize = self.children.values().map(vec_bytes).sum();
+        let views: usize = self.ghostdag.values().chain([&self.virtual_view]).map(ghostdag_bytes).sum();
+        let stats = &self.stats;
+        map_bytes(&self.blocks)
+            + blocks
+            + map_bytes(&self.children)
+            + children
+            + self.tips.capacity() * (size_of::<u64>() + 1)
+            + map_bytes(&self.tip_since)
+            + map_bytes(&self.ghostdag)
+            + views
+            + map_bytes(&self.deployments)
+            + self.chain_index.memory()
+            + self.reds.memory()
+            + map_bytes(&stats.anticones)
+            + vec_bytes(&stats.tip_counts)
+            + vec_bytes(&stats.anticone_sizes)
+            + vec_bytes(&stats.reorg_depths)
+            + vec_bytes(&stats.merge_latencies)
+            + vec_bytes(&stats.rescue_delays)
+            + vec_bytes(&stats.timeline)
+    }
+
+    // Compare the DAG's working set with the same blocks in a CompactStore
+    pub fn memory_report(&self) -> io::Result<String> {
+        let mut compact = CompactStore::default();
+        for id in self.ordered_blocks() {
+            compact.put(&self.blocks[&id])?;
+        }
+        let (dag, arena) = (self.memory(), compact.memory());
+        let n = self.blocks.len().max(1);
+        let mut out = String::new();
+        let _ = writeln!(out, "🧠 Memory for {} blocks ({} parent links)", self.blocks.len(), compact.parents.len());
+        let _ = writeln!(out, "   {:<14} {:>12} {:>10}", "layout", "bytes", "per block");
+        let _ = writeln!(out, "   {:<14} {:>12} {:>10}", "DAG (HashMap)", dag, dag / n);
+        let _ = writeln!(out, "   {:<14} {:>12} {:>10}", "compact arena", arena, arena / n);
+        let _ = writeln!(out, "   compact uses {:.0}% of the DAG's working set", 100.0 * arena as f64 / dag.max(1) as f64);
+        Ok(out)
+    }
+}
+
+fn ghostdag_bytes(view: &Ghostdag) -> usize {
+    vec_bytes(&view.mergeset_blues)
+        + vec_bytes(&view.mergeset_reds)
+        + map_bytes(&view.blues_anticone_sizes)
+        + map_bytes(&view.blue_anticones)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        dag.insert_block(3, vec![1, 2], vec![7, 8], Some(2));
+        dag.create_block(vec![2]).unwrap();
+        dag
+    }
+
+    #[test]
+    fn compact_store_round_trips_blocks() {
+        let dag = sample();
+        let mut store = CompactStore::default();
+        for id in dag.ordered_blocks() {
+            store.put(&dag[id]).unwrap();
+        }
+        for b in dag.blocks() {
+            let back = store.get(b.id()).unwrap().unwrap();
+            assert_eq!(back.parents(), b.parents());
+            assert_eq!(back.txs(), b.txs());
+            assert_eq!((back.blue_work(), back.miner(), back.selected_parent()), (b.blue_work(), b.miner(), b.selected_parent()));
+        }
+        assert_eq!(ToyDag::from_store(&store).unwrap().ordered_blocks(), dag.ordered_blocks());
+    }
+
+    #[test]
+    fn bitset_cones_match_the_dag() {
+        let dag = sample();
+        let mut store = CompactStore::default();
+        for id in dag.ordered_blocks() {
+            store.put(&dag[id]).unwrap();
+        }
+        for b in dag.blocks() {
+            assert_eq!(store.past_size(b.id()).unwrap() as u64, b.past_size());
+        }
+        assert!(store.is_ancestor(2, 3).unwrap());
+        assert!(!store.is_ancestor(1, 4).unwrap());
+        assert!(store.put(&Block { id: 9, parents: vec![8], ..dag[4].clone() }).is_err());
+        assert!(store.put(&Block { id: 9, topo_depth: usize::MAX, ..dag[4].clone() }).is_err());
+        assert_eq!(store.len(), dag.block_count());
+    }
+
+    #[test]
+    fn dag_memory_counts_more_than_the_block_map() {
+        let mut dag = sample();
+        let before = dag.memory();
+        let blocks = map_bytes(&dag.blocks) + map_bytes(&dag.children);
+        assert!(before > blocks + map_bytes(&dag.ghostdag) + dag.chain_index.memory());
+
+        dag.track_anticones = true;
+        for _ in 0..20 {
+            dag.create_block(vec![dag.selected_parent()]).unwrap();
+        }
+        assert!(dag.memory() > before + map_bytes(&dag.stats.anticones));
+    }
+}
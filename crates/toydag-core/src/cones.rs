@@ -0,0 +1,210 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::ToyDag;
+use crate::error::DagError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Direction {
+    Past,
+    Future,
+    Anticone(HashSet<u64>), // The block's future, passed through without being yielded
+}
+
+// Lazy breadth-first walk over one cone of a block. Nothing is visited until
+// asked for, so `take`, `find` and friends stop the walk early. Blocks whose
+// topological depth falls outside `floor..=ceiling` are neither yielded nor
+// walked through, which keeps the bounded queries' cost to the window.
+#[derive(Clone)]
+pub struct Cone<'a> {
+    dag: &'a ToyDag,
+    direction: Direction,
+    queue: VecDeque<u64>,
+    seen: HashSet<u64>,
+    floor: usize,
+    ceiling: usize,
+}
+
+impl<'a> Cone<'a> {
+    // Starts from `start`; `skip` is never visited
+    fn new(dag: &'a ToyDag, direction: Direction, start: &[u64], skip: Option<u64>) -> Self {
+        let mut cone =
+            Cone { dag, direction, queue: VecDeque::new(), seen: skip.into_iter().collect(), floor: 0, ceiling: usize::MAX };
+        cone.enqueue(start);
+        cone
+    }
+
+    fn within(mut self, floor: usize, ceiling: usize) -> Self {
+        self.floor = floor;
+        self.ceiling = ceiling;
+        self
+    }
+
+    fn enqueue(&mut self, next: &[u64]) {
+        for &id in next {
+            if self.seen.insert(id) {
+                self.queue.push_back(id);
+            }
+        }
+    }
+}
+
+impl Iterator for Cone<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let dag = self.dag;
+        while let Some(id) = self.queue.pop_front() {
+            if !(self.floor..=self.ceiling).contains(&dag.blocks[&id].topo_depth) {
+                continue;
+            }
+            match &self.direction {
+                Direction::Past => {
+                    self.enqueue(&dag.blocks[&id].parents);
+                    return Some(id);
+                }
+                Direction::Future => {
+                    self.enqueue(dag.children.get(&id).map_or(&[], Vec::as_slice));
+                    return Some(id);
+                }
+                // Walking down from the tips: the block's past is marked seen
+                // up front, so it is a dead end
+                Direction::Anticone(future) => {
+                    let in_future = future.contains(&id);
+                    self.enqueue(&dag.blocks[&id].parents);
+                    if !in_future {
+                        return Some(id);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl ToyDag {
+    // Ancestors of `block`, nearest first; the block itself is not included
+    pub fn iter_past(&self, block: u64) -> Result<Cone<'_>, DagError> {
+        Ok(Cone::new(self, Direction::Past, &self.get_block(block)?.parents, Some(block)))
+    }
+
+    // Descendants of `block`, nearest first; the block itself is not included
+    pub fn iter_future(&self, block: u64) -> Result<Cone<'_>, DagError> {
+        self.get_block(block)?;
+        Ok(Cone::new(self, Direction::Future, self.children.get(&block).map_or(&[], Vec::as_slice), Some(block)))
+    }
+
+    // Blocks neither in the past nor the future of `block`, found by walking
+    // down from the tips
+    pub fn iter_anticone(&self, block: u64) -> Result<Cone<'_>, DagError> {
+        self.anticone_from(block, 0)
+    }
+
+    // Ancestors at most `max_depth` topological layers below `block`
+    pub fn past_within(&self, block: u64, max_depth: usize) -> Result<Cone<'_>, DagError> {
+        let depth = self.get_block(block)?.topo_depth;
+        Ok(self.iter_past(block)?.within(depth.saturating_sub(max_depth), usize::MAX))
+    }
+
+    // Descendants at most `max_depth` topological layers above `block`
+    pub fn future_within(&self, block: u64, max_depth: usize) -> Result<Cone<'_>, DagError> {
+        let depth = self.get_block(block)?.topo_depth;
+        Ok(self.iter_future(block)?.within(0, depth.saturating_add(max_depth)))
+    }
+
+    // The anticone down to `horizon` layers below `block`. Concurrent blocks
+    // deeper than that are left out, and so is the walk through them, which
+    // on a large DAG is nearly all of it.
+    pub fn anticone_within(&self, block: u64, horizon: usize) -> Result<Cone<'_>, DagError> {
+        let depth = self.get_block(block)?.topo_depth;
+        self.anticone_from(block, depth.saturating_sub(horizon))
+    }
+
+    // The block's past and future are each walked once, the past only down
+    // to `floor`, rather than asked about for every block the walk visits
+    fn anticone_from(&self, block: u64, floor: usize) -> Result<Cone<'_>, DagError> {
+        let past: Vec<u64> = self.iter_past(block)?.within(floor, usize::MAX).collect();
+        let future = self.iter_future(block)?.collect();
+        let mut tips: Vec<u64> = self.tips().collect();
+        tips.sort_unstable();
+        let mut cone = Cone::new(self, Direction::Anticone(future), &[], Some(block)).within(floor, usize::MAX);
+        cone.seen.extend(past);
+        cone.enqueue(&tips);
+        Ok(cone)
+    }
+
+    // Layers between `block` and the finality point below it, the natural
+    // horizon for the bounded queries: everything deeper is settled
+    pub fn finality_horizon(&self, block: u64) -> Result<usize, DagError> {
+        let depth = self.get_block(block)?.topo_depth;
+        Ok(depth.saturating_sub(self.blocks[&self.finality_point()].topo_depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two branches off genesis (1 → 3 and 2), joined again by block 4
+    fn diamond() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![1]).unwrap();
+        dag.create_block(vec![3, 2]).unwrap();
+        dag.create_block(vec![2]).unwrap();
+        dag
+    }
+
+    #[test]
+    fn cones_match_the_materialized_sets() {
+        let dag = diamond();
+        for id in 0..=5 {
+            let past: HashSet<u64> = dag.iter_past(id).unwrap().collect();
+            let future: HashSet<u64> = dag.iter_future(id).unwrap().collect();
+            let anticone: HashSet<u64> = dag.iter_anticone(id).unwrap().collect();
+
+            let mut expected_past = dag.past_set(id).unwrap();
+            expected_past.remove(&id);
+            let mut expected_future = dag.future_set(id).unwrap();
+            expected_future.remove(&id);
+            let expected_anticone: HashSet<u64> = dag
+                .blocks()
+                .map(|b| b.id())
+                .filter(|b| *b != id && !expected_past.contains(b) && !expected_future.contains(b))
+                .collect();
+
+            assert_eq!(past, expected_past, "past of {}", id);
+            assert_eq!(future, expected_future, "future of {}", id);
+            assert_eq!(anticone, expected_anticone, "anticone of {}", id);
+        }
+    }
+
+    #[test]
+    fn walks_are_breadth_first_and_lazy() {
+        let dag = diamond();
+        assert_eq!(dag.iter_past(4).unwrap().take(2).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(dag.iter_future(2).unwrap().collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(dag.iter_anticone(1).unwrap().collect::<Vec<_>>(), vec![5, 2]);
+    }
+
+    #[test]
+    fn bounded_walks_stop_at_their_horizon() {
+        let dag = diamond();
+        assert_eq!(dag.past_within(4, 1).unwrap().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(dag.past_within(4, 2).unwrap().collect::<HashSet<_>>(), HashSet::from([3, 2, 1]));
+        assert_eq!(dag.future_within(0, 1).unwrap().collect::<HashSet<_>>(), HashSet::from([1, 2]));
+        assert_eq!(dag.anticone_within(3, 0).unwrap().collect::<Vec<_>>(), vec![5]);
+        assert_eq!(dag.anticone_within(3, 1).unwrap().collect::<HashSet<_>>(), HashSet::from([5, 2]));
+        assert_eq!(dag.past_within(4, usize::MAX).unwrap().count(), dag.iter_past(4).unwrap().count());
+    }
+
+    #[test]
+    fn unknown_blocks_are_an_error() {
+        let dag = diamond();
+        assert!(matches!(dag.iter_past(9), Err(DagError::UnknownBlock(9))));
+        assert!(matches!(dag.future_within(9, 1), Err(DagError::UnknownBlock(9))));
+        assert!(matches!(dag.anticone_within(9, 1), Err(DagError::UnknownBlock(9))));
+        assert_eq!(dag.finality_horizon(9), Err(DagError::UnknownBlock(9)));
+    }
+}
@@ -0,0 +1,239 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
+
+use crate::ToyDag;
+use crate::score::BlueWork;
+
+// A rule for ordering the blocks of a DAG. GHOSTDAG commits to one total order;
+// SPECTRE only settles pairs, each by a vote of every block in the DAG; the
+// longest chain keeps one chain and orphans the rest.
+pub trait ConsensusProtocol {
+    fn name(&self) -> &'static str;
+
+    // Verdict for every pair of blocks the protocol orders
+    fn pairwise(&self, dag: &ToyDag) -> PairwiseOrder;
+}
+
+// Pairwise verdicts, keyed by (lower id, higher id)
+#[derive(Debug, Clone, Default)]
+pub struct PairwiseOrder {
+    verdicts: HashMap<(u64, u64), Ordering>,
+}
+
+impl PairwiseOrder {
+    // Less when `a` comes first; None for unknown blocks
+    pub fn compare(&self, a: u64, b: u64) -> Option<Ordering> {
+        if a == b {
+            return Some(Ordering::Equal);
+        }
+        let key = (a.min(b), a.max(b));
+        let verdict = *self.verdicts.get(&key)?;
+        Some(if a < b { verdict } else { verdict.reverse() })
+    }
+
+    // Every decided pair as (lower id, higher id, verdict), sorted
+    pub fn pairs(&self) -> Vec<(u64, u64, Ordering)> {
+        let mut pairs: Vec<_> = self.verdicts.iter().map(|(&(a, b), &v)| (a, b, v)).collect();
+        pairs.sort_unstable_by_key(|&(a, b, _)| (a, b));
+        pairs
+    }
+
+    pub fn len(&self) -> usize {
+        self.verdicts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verdicts.is_empty()
+    }
+}
+
+// Pairs read off the position of each block in `ordered_blocks`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ghostdag;
+
+impl ConsensusProtocol for Ghostdag {
+    fn name(&self) -> &'static str {
+        "ghostdag"
+    }
+
+    fn pairwise(&self, dag: &ToyDag) -> PairwiseOrder {
+        let position: HashMap<u64, usize> = dag.ordered_blocks().into_iter().enumerate().map(|(i, id)| (id, i)).collect();
+        let mut ids: Vec<u64> = position.keys().copied().collect();
+        ids.sort_unstable();
+
+        let mut order = PairwiseOrder::default();
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                order.verdicts.insert((a, b), position[&a].cmp(&position[&b]));
+            }
+        }
+        order
+    }
+}
+
+// Nakamoto's rule run over a DAG: each block extends only its parent with the
+// most chain work, and any other parents it names are orphans as far as it is
+// concerned. The heaviest tip's chain is the ledger and everything off it is
+// orphaned, so only pairs of chain blocks get a verdict.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LongestChain;
+
+impl LongestChain {
+    // The heaviest chain from genesis; equal work goes to the lower id
+    pub fn chain(&self, dag: &ToyDag) -> Vec<u64> {
+        let mut topo: Vec<u64> = dag.blocks().map(|b| b.id).collect();
+        topo.sort_unstable_by_key(|&id| (dag.blocks[&id].topo_depth, id));
+
+        // Chain work and chain parent of every block, parents first
+        let mut chains: HashMap<u64, (BlueWork, Option<u64>)> = HashMap::with_capacity(topo.len());
+        for &id in &topo {
+            let block = &dag.blocks[&id];
+            let parent = block.parents().iter().copied().max_by_key(|p| (chains[p].0, Reverse(*p)));
+            let below = parent.map_or(0, |p| chains[&p].0);
+            chains.insert(id, (below.saturating_add(block.work()), parent));
+        }
+
+        let mut tip = chains.iter().max_by_key(|&(&id, &(work, _))| (work, Reverse(id))).map(|(&id, _)| id);
+        let mut chain = Vec::new();
+        while let Some(id) = tip {
+            chain.push(id);
+            tip = chains[&id].1;
+        }
+        chain.reverse();
+        chain
+    }
+
+    // Blocks off the heaviest chain, sorted
+    pub fn orphans(&self, dag: &ToyDag) -> Vec<u64> {
+        let chain: HashSet<u64> = self.chain(dag).into_iter().collect();
+        let mut orphans: Vec<u64> = dag.blocks().map(|b| b.id).filter(|id| !chain.contains(id)).collect();
+        orphans.sort_unstable();
+        orphans
+    }
+}
+
+impl ConsensusProtocol for LongestChain {
+    fn name(&self) -> &'static str {
+        "longest-chain"
+    }
+
+    fn pairwise(&self, dag: &ToyDag) -> PairwiseOrder {
+        let chain = self.chain(dag);
+        let mut order = PairwiseOrder::default();
+        for (i, &a) in chain.iter().enumerate() {
+            for &b in &chain[i + 1..] {
+                order.verdicts.insert((a.min(b), a.max(b)), if a < b { Ordering::Less } else { Ordering::Greater });
+            }
+        }
+        order
+    }
+}
+
+// Simplified SPECTRE vote on each pair (x, y):
+//   - a block that sees only x (or is x) votes x first, and vice versa;
+//   - a block that sees both votes with the majority of its own past;
+//   - a block that sees neither votes with the majority of its future.
+// The virtual takes the majority of all votes; an exact tie goes to the lower id.
+// Unlike the recursive original, past majorities are tallied over the votes
+// already cast in this DAG rather than re-run on each block's sub-DAG.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spectre;
+
+impl ConsensusProtocol for Spectre {
+    fn name(&self) -> &'static str {
+        "spectre"
+    }
+
+    fn pairwise(&self, dag: &ToyDag) -> PairwiseOrder {
+        let mut topo: Vec<u64> = dag.blocks().map(|b| b.id).collect();
+        topo.sort_unstable_by_key(|&id| (dag.blocks[&id].topo_depth, id));
+        let pasts: HashMap<u64, HashSet<u64>> = topo
+            .iter()
+            .map(|&id| {
+                let mut past = dag.past_cone(id);
+                past.remove(&id);
+                (id, past)
+            })
+            .collect();
+
+        let mut ids = topo.clone();
+        ids.sort_unstable();
+
+        let mut order = PairwiseOrder::default();
+        for (i, &x) in ids.iter().enumerate() {
+            for &y in &ids[i + 1..] {
+                order.verdicts.insert((x, y), vote(&topo, &pasts, x, y));
+            }
+        }
+        order
+    }
+}
+
+// Tally of the vote on (x, y); negative favors x first
+fn vote(topo: &[u64], pasts: &HashMap<u64, HashSet<u64>>, x: u64, y: u64) -> Ordering {
+    let sees = |z: u64, b: u64| z == b || pasts[&z].contains(&b);
+    let mut votes: HashMap<u64, i64> = HashMap::with_capacity(topo.len());
+
+    // Voters that see x or y, oldest first so their pasts have already voted
+    for &z in topo {
+        let v = match (sees(z, x), sees(z, y)) {
+            (true, false) => -1,
+            (false, true) => 1,
+            (true, true) => pasts[&z].iter().map(|p| votes.get(p).copied().unwrap_or(0)).sum::<i64>().signum(),
+            (false, false) => continue,
+        };
+        votes.insert(z, v);
+    }
+
+    // Voters that see neither follow their future, newest first
+    for &z in topo.iter().rev() {
+        if votes.contains_key(&z) {
+            continue;
+        }
+        let future: i64 = topo
+            .iter()
+            .filter(|&&f| pasts[&f].contains(&z))
+            .map(|f| votes.get(f).copied().unwrap_or(0))
+            .sum();
+        votes.insert(z, future.signum());
+    }
+
+    // Ties go to x, which has the lower id
+    if votes.values().sum::<i64>() <= 0 { Ordering::Less } else { Ordering::Greater }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::testing::{arb_parents, build};
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // Whatever they make of anticone pairs, both rules put ancestors first
+        #[test]
+        fn both_protocols_respect_topology(parents in arb_parents(25, 3)) {
+            let dag = build(&parents);
+            for protocol in [&Ghostdag as &dyn ConsensusProtocol, &Spectre, &LongestChain] {
+                let order = protocol.pairwise(&dag);
+                for (x, y, verdict) in order.pairs() {
+                    if dag.in_past(x, y) {
+                        prop_assert_eq!(verdict, Ordering::Less, "{}: {} before {}", protocol.name(), x, y);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn longest_chain_follows_the_heavier_branch_and_orphans_the_rest() {
+        let dag = build(&[vec![0], vec![0], vec![2], vec![1, 3]]);
+        assert_eq!(LongestChain.chain(&dag), vec![0, 2, 3, 4]);
+        assert_eq!(LongestChain.orphans(&dag), vec![1]);
+        let order = LongestChain.pairwise(&dag);
+        assert_eq!(order.compare(4, 2), Some(Ordering::Greater));
+        assert_eq!(order.compare(1, 4), None);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::ToyDag;
+use crate::score::BlueWork;
+
+// Difficulty adjustment over the selected chain. The window is the last `window`
+// chain blocks below a new block; the blue-score gap across it counts every blue
+// block mined in that time, merged side blocks included, so the DAG's whole
+// block rate is steered toward one blue block per `target_interval`.
+//
+// Timestamps are whatever the miner wrote. With `median_window` set, the
+// window is measured between past median times instead, the median timestamp
+// of a block and the chain blocks below it, so a minority of skewed stamps
+// can't stretch or squeeze it; and a block whose timestamp isn't past its
+// selected parent's median time is refused outright.
+#[derive(Debug, Clone)]
+pub struct Daa {
+    pub window: usize,
+    pub target_interval: u64, // In the same units as block timestamps
+    pub max_adjust: f64,      // Largest factor difficulty may move per block
+    pub median_window: usize, // Chain blocks in the past median time; 0 uses raw timestamps
+}
+
+impl Daa {
+    pub fn new(window: usize, target_interval: u64) -> Self {
+        Daa { window, target_interval, max_adjust: 4.0, median_window: 0 }
+    }
+
+    // Median timestamp of `block` and the chain blocks below it, `median_window`
+    // blocks in all (fewer near genesis); the raw timestamp when the rule is off
+    pub fn past_median_time(&self, dag: &ToyDag, block: u64) -> u64 {
+        if self.median_window == 0 {
+            return dag[block].timestamp;
+        }
+        let mut stamps = Vec::with_capacity(self.median_window);
+        let mut current = Some(block);
+        while let Some(id) = current
+            && stamps.len() < self.median_window
+        {
+            let b = &dag[id];
+            stamps.push(b.timestamp);
+            current = b.selected_parent;
+        }
+        stamps.sort_unstable();
+        stamps[stamps.len() / 2]
+    }
+
+    // Difficulty for a block whose selected parent is `selected_parent`. Until
+    // the window spans some blue blocks there is no rate to measure, and the
+    // DAG's flat `block_work` stands in. A zero window still holds the
+    // selected parent, so it measures nothing rather than panicking.
+    pub fn next_work(&self, dag: &ToyDag, selected_parent: u64) -> BlueWork {
+        let mut window = Vec::with_capacity(self.window.max(1));
+        let mut current = Some(selected_parent);
+        while let Some(id) = current
+            && window.len() < self.window.max(1)
+        {
+            let block = &dag[id];
+            window.push(block);
+            current = block.selected_parent;
+        }
+
+        let newest = window[0];
+        let oldest = window[window.len() - 1];
+        let blues = newest.blue_score.saturating_sub(oldest.blue_score);
+        if blues == 0 {
+            return dag.block_work;
+        }
+
+        let elapsed = self.past_median_time(dag, newest.id).saturating_sub(self.past_median_time(dag, oldest.id)).max(1) as f64;
+        let observed_interval = elapsed / blues as f64;
+        let mean_work = window.iter().map(|b| b.work as f64).sum::<f64>() / window.len() as f64;
+        let factor = (self.target_interval as f64 / observed_interval).clamp(1.0 / self.max_adjust, self.max_adjust);
+        (mean_work * factor).round().max(1.0) as BlueWork
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DagError;
+
+    #[test]
+    fn past_median_time_refuses_stale_stamps_and_shrugs_off_outliers() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.daa = Some(Daa { median_window: 3, ..Daa::new(10, 10) });
+        let a = dag.create_block_at(vec![0], 10).unwrap();
+        let b = dag.create_block_at(vec![a], 20).unwrap();
+        let c = dag.create_block_at(vec![b], 1_000_000).unwrap(); // Warped far ahead
+
+        let daa = dag.daa.clone().unwrap();
+        assert_eq!(daa.past_median_time(&dag, c), 20);
+        assert_eq!(
+            dag.create_block_at(vec![c], 20),
+            Err(DagError::StaleTimestamp { block: 4, timestamp: 20, median: 20 })
+        );
+        assert!(dag.create_block_at(vec![c], 30).is_ok());
+        assert_eq!(dag.stats.stale_timestamps, 1);
+    }
+
+    // A chain off genesis stamped at `stamps`. The DAA is off while it's
+    // built, so every block carries the flat 100 work.
+    fn chain(stamps: &[u64]) -> (ToyDag, u64) {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.block_work = 100;
+        let mut tip = dag.genesis();
+        for &t in stamps {
+            tip = dag.create_block_at(vec![tip], t).unwrap();
+        }
+        (dag, tip)
+    }
+
+    #[test]
+    fn next_work_measures_only_its_window_and_clamps_the_step() {
+        // 1000 ms apart, then 5
+        let (dag, tip) = chain(&[1000, 2000, 2005, 2010]);
+        assert_eq!(Daa::new(1, 10).next_work(&dag, tip), 100); // One block spans no blues yet
+        assert_eq!(Daa::new(0, 10).next_work(&dag, tip), 100); // Nor does an empty window
+        assert_eq!(Daa::new(3, 10).next_work(&dag, tip), 200); // 5 ms per blue, twice the target
+        assert_eq!(Daa::new(4, 10).next_work(&dag, tip), 25); // The 1000 ms gap counts: clamped at 1/4
+        assert_eq!(Daa { max_adjust: 100.0, ..Daa::new(4, 10) }.next_work(&dag, tip), 3);
+
+        let (dag, tip) = chain(&[1, 1, 1]);
+        assert_eq!(Daa::new(3, 10).next_work(&dag, tip), 400); // No time passed: clamped at 4
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt::Write as _;
+
+use crate::ToyDag;
+
+// How two DAG states differ, e.g. two nodes' views or before and after a heal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DagDiff {
+    pub only_left: Vec<u64>,
+    pub only_right: Vec<u64>,
+    pub color_disagreements: Vec<u64>, // Shared blocks colored differently
+    pub divergence: u64,               // Last block both selected chains share
+    pub left_chain: Vec<u64>,          // Left's selected chain past the divergence point
+    pub right_chain: Vec<u64>,         // Right's, likewise
+}
+
+impl DagDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty()
+            && self.only_right.is_empty()
+            && self.color_disagreements.is_empty()
+            && self.left_chain.is_empty()
+            && self.right_chain.is_empty()
+    }
+
+    // Summary plus the first `limit` ids of each list
+    pub fn report(&self, limit: usize) -> String {
+        let mut out = String::new();
+        if self.is_empty() {
+            let _ = writeln!(out, "✅ Identical: same blocks, same colors, same selected chain");
+            return out;
+        }
+        let _ = writeln!(out, "🔀 DAG diff");
+        for (label, ids) in [
+            ("Only on the left", &self.only_left),
+            ("Only on the right", &self.only_right),
+            ("Color disagreements", &self.color_disagreements),
+        ] {
+            let _ = writeln!(out, "  {:<20}: {}{}", label, ids.len(), preview(ids, limit));
+        }
+        let _ = writeln!(out, "  {:<20}: block {}", "Chains diverge after", self.divergence);
+        let _ = writeln!(out, "  {:<20}: {}{}", "Left chain beyond", self.left_chain.len(), preview(&self.left_chain, limit));
+        let _ = writeln!(out, "  {:<20}: {}{}", "Right chain beyond", self.right_chain.len(), preview(&self.right_chain, limit));
+        out
+    }
+}
+
+fn preview(ids: &[u64], limit: usize) -> String {
+    if ids.is_empty() {
+        return String::new();
+    }
+    let shown: Vec<String> = ids.iter().take(limit).map(u64::to_string).collect();
+    let more = if ids.len() > limit { ", …" } else { "" };
+    format!(" [{}{}]", shown.join(", "), more)
+}
+
+impl ToyDag {
+    // Compare this DAG (left) with `other` (right). Block ids are assumed to
+    // name the same blocks on both sides, as they do across simulated nodes.
+    pub fn diff(&self, other: &ToyDag) -> DagDiff {
+        let mut only_left: Vec<u64> = self.blocks.keys().copied().filter(|id| !other.blocks.contains_key(id)).collect();
+        let mut only_right: Vec<u64> = other.blocks.keys().copied().filter(|id| !self.blocks.contains_key(id)).collect();
+        let mut color_disagreements: Vec<u64> = self
+            .blocks
+            .values()
+            .filter(|b| other.blocks.get(&b.id).is_some_and(|o| o.color != b.color))
+            .map(|b| b.id)
+            .collect();
+        only_left.sort_unstable();
+        only_right.sort_unstable();
+        color_disagreements.sort_unstable();
+
+        // Both chains start at genesis, so they agree on some prefix
+        let left = self.selected_chain();
+        let right = other.selected_chain();
+        let shared = left.iter().zip(&right).take_while(|(l, r)| l == r).count();
+
+        DagDiff {
+            only_left,
+            only_right,
+            color_disagreements,
+            divergence: left[shared - 1],
+            left_chain: left[shared..].to_vec(),
+            right_chain: right[shared..].to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diverging_views_are_reported() {
+        let mut left = ToyDag::new();
+        left.verbose = false;
+        left.create_block(vec![0]).unwrap();
+        let mut right = left.clone();
+
+        left.insert_block(2, vec![1], vec![], None);
+        left.insert_block(3, vec![2], vec![], None);
+        right.insert_block(4, vec![1], vec![], None);
+
+        let diff = left.diff(&right);
+        assert_eq!(diff.only_left, vec![2, 3]);
+        assert_eq!(diff.only_right, vec![4]);
+        assert_eq!(diff.divergence, 1);
+        assert_eq!(diff.left_chain, vec![2, 3]);
+        assert_eq!(diff.right_chain, vec![4]);
+        assert!(left.diff(&left).is_empty());
+    }
+}
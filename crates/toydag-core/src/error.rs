@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt;
+
+// Why the DAG refused a block or a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DagError {
+    UnknownBlock(u64),
+    UnknownParent { block: u64, parent: u64 },
+    NoParents(u64),
+    TooManyParents { block: u64, count: usize, limit: usize },
+    DuplicateBlock(u64),
+    DuplicateParent { block: u64, parent: u64 },
+    IdSpaceExhausted(u64), // No id left above this one for the next block
+    ZeroDifficulty(u64),   // A genesis with no work
+    Cycle(Vec<u64>), // Batch blocks that never became ready: on a parent cycle or built on one
+    MergeDepthViolation(u64),
+    MergesetTooLarge { block: u64, size: usize, limit: usize },
+    StaleTimestamp { block: u64, timestamp: u64, median: u64 },
+    OutdatedVersion { block: u64, version: u32, required: u32 },
+}
+
+impl DagError {
+    // Rejected by a consensus rule, as opposed to malformed input
+    pub fn is_rule_violation(&self) -> bool {
+        matches!(
+            self,
+            DagError::MergeDepthViolation(_)
+                | DagError::MergesetTooLarge { .. }
+                | DagError::TooManyParents { .. }
+                | DagError::StaleTimestamp { .. }
+                | DagError::OutdatedVersion { .. }
+        )
+    }
+}
+
+impl fmt::Display for DagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DagError::UnknownBlock(id) => write!(f, "unknown block {}", id),
+            DagError::UnknownParent { block, parent } => write!(f, "block {} references unknown parent {}", block, parent),
+            DagError::NoParents(id) => write!(f, "block {} has no parents", id),
+            DagError::TooManyParents { block, count, limit } => {
+                write!(f, "block {} has {} parents, over the limit of {}", block, count, limit)
+            }
+            DagError::DuplicateBlock(id) => write!(f, "block {} is already in the DAG", id),
+            DagError::DuplicateParent { block, parent } => write!(f, "block {} names parent {} more than once", block, parent),
+            DagError::IdSpaceExhausted(id) => write!(f, "block id {} leaves no id for the next block", id),
+            DagError::ZeroDifficulty(id) => write!(f, "genesis {} has zero difficulty", id),
+            DagError::Cycle(ids) => write!(f, "blocks {:?} are on or behind a parent cycle", ids),
+            DagError::MergeDepthViolation(id) => write!(f, "block {} merges below its merge-depth root", id),
+            DagError::MergesetTooLarge { block, size, limit } => {
+                write!(f, "block {} merges {} blocks, over the limit of {}", block, size, limit)
+            }
+            DagError::StaleTimestamp { block, timestamp, median } => {
+                write!(f, "block {} is stamped {}, not past its past median time {}", block, timestamp, median)
+            }
+            DagError::OutdatedVersion { block, version, required } => {
+                write!(f, "block {} has version {}, but an active soft fork requires {}", block, version, required)
+            }
+        }
+    }
+}
+
+impl Error for DagError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BLOCK_VERSION, NewBlock, ToyDag};
+
+    fn block(id: u64, parents: Vec<u64>) -> NewBlock {
+        NewBlock { id, parents, txs: vec![], miner: None, timestamp: 0, version: BLOCK_VERSION }
+    }
+
+    #[test]
+    fn bad_blocks_are_refused_with_a_reason() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        assert_eq!(dag.add_block(block(1, vec![])), Err(DagError::NoParents(1)));
+        assert_eq!(dag.add_block(block(1, vec![7])), Err(DagError::UnknownParent { block: 1, parent: 7 }));
+        assert_eq!(dag.add_block(block(1, vec![0, 0])), Err(DagError::DuplicateParent { block: 1, parent: 0 }));
+        assert_eq!(dag.add_block(block(1, vec![0])), Ok(()));
+        assert_eq!(dag.add_block(block(1, vec![0])), Err(DagError::DuplicateBlock(1)));
+        assert_eq!(dag.past_set(9), Err(DagError::UnknownBlock(9)));
+        assert_eq!(dag.is_ancestor(0, 9), Err(DagError::UnknownBlock(9)));
+        assert_eq!(dag.prune_parents(&[1, 9]), Err(DagError::UnknownBlock(9)));
+        assert_eq!(dag.select_parents(&[9], 2), Err(DagError::UnknownBlock(9)));
+        assert!(dag.block(9).is_none());
+        assert_eq!((dag.chain_height(9), dag.chain_ancestor_at_depth(9, 1)), (None, None));
+        assert_eq!(dag.block_count(), 2);
+        assert_eq!(dag.add_block(block(u64::MAX, vec![1])), Err(DagError::IdSpaceExhausted(u64::MAX)));
+        assert_eq!(dag.block_count(), 2);
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::{Color, ToyDag};
+
+// Things that happen to a DAG, in the order they happen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DagEvent {
+    BlockAdded { id: u64 },
+    ColorFlipped { id: u64, from: Color, to: Color }, // The virtual moved and repainted an existing block
+    SelectedParentChanged { from: u64, to: u64 },
+    StitchActivated { merge_block: u64, tips: usize },
+    Finalized { id: u64 }, // Chain block reached FINALITY_DEPTH below the virtual
+    FinalityViolation(FinalityViolation),
+}
+
+// A selected-chain switch that dropped an already finalized block off the
+// chain: the safety property finality is meant to give has been broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinalityViolation {
+    pub finality_point: u64, // Finalized chain block that is no longer on the chain
+    pub from: u64,           // Selected parent before the switch
+    pub to: u64,             // and after
+    pub reorg_depth: usize,  // Chain blocks dropped, as in Stats::reorg_depths
+}
+
+// Subscribers see every event along with the DAG state right after it
+pub trait Observer {
+    fn on_event(&mut self, dag: &ToyDag, event: &DagEvent);
+}
+
+pub type SharedObserver = Arc<Mutex<dyn Observer + Send>>;
+
+// Forwards events into a channel, for consumers that would rather poll
+struct ChannelObserver {
+    sender: Sender<DagEvent>,
+}
+
+impl Observer for ChannelObserver {
+    fn on_event(&mut self, _dag: &ToyDag, event: &DagEvent) {
+        // A dropped receiver just means nobody is listening anymore
+        let _ = self.sender.send(event.clone());
+    }
+}
+
+impl ToyDag {
+    pub fn subscribe(&mut self, observer: SharedObserver) {
+        self.observers.push(observer);
+    }
+
+    // Event stream as a channel; events queue up until the receiver drains them
+    pub fn event_channel(&mut self) -> Receiver<DagEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribe(Arc::new(Mutex::new(ChannelObserver { sender })));
+        receiver
+    }
+
+    pub(crate) fn emit(&self, event: DagEvent) {
+        for observer in &self.observers {
+            observer.lock().unwrap().on_event(self, &event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FINALITY_DEPTH;
+
+    #[test]
+    fn reorg_past_the_finality_point_is_reported() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        // k wide enough for the private chain to stay blue beside the honest one
+        dag.k_mode = crate::knight::KMode::Fixed(FINALITY_DEPTH as usize + 10);
+        let events = dag.event_channel();
+        let mut honest = dag.genesis();
+        for _ in 0..FINALITY_DEPTH + 5 {
+            honest = dag.create_block(vec![honest]).unwrap();
+        }
+        let finalized = dag.finality_point();
+        assert_ne!(finalized, dag.genesis());
+
+        // A longer private chain from genesis, published block by block
+        let mut private = dag.genesis();
+        for _ in 0..FINALITY_DEPTH + 10 {
+            private = dag.create_block(vec![private]).unwrap();
+        }
+        assert_eq!(dag.selected_parent(), private);
+
+        let violations: Vec<FinalityViolation> = events
+            .try_iter()
+            .filter_map(|e| match e {
+                DagEvent::FinalityViolation(v) => Some(v),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].finality_point, violations[0].from), (finalized, honest));
+        assert_eq!(violations[0].reorg_depth as u64, FINALITY_DEPTH + 5);
+        assert_eq!(dag.stats.finality_violations, 1);
+        assert!(dag.is_ancestor(dag.finality_point(), private).unwrap()); // Re-anchored on the new chain
+    }
+
+    // The audit's four siblings at k = 1: a block merging 1, 3 and 4 moves
+    // the virtual onto a view where 3 is blue and 2 is red
+    #[test]
+    fn repainted_blocks_are_reported() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = crate::knight::KMode::Fixed(1);
+        let events = dag.event_channel();
+        for _ in 0..4 {
+            dag.create_block(vec![0]).unwrap();
+        }
+        dag.create_block(vec![1, 3, 4]).unwrap();
+
+        let flips: Vec<(u64, Color, Color)> = events
+            .try_iter()
+            .filter_map(|e| match e {
+                DagEvent::ColorFlipped { id, from, to } => Some((id, from, to)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(flips, vec![(3, Color::Red, Color::Blue), (2, Color::Blue, Color::Red)]);
+        assert_eq!((dag.red_fate(3), dag.red_fate(2)), (None, Some(crate::rescue::RedFate::Pending)));
+    }
+}
@@ -0,0 +1,272 @@
+// Hand-written DAGs with the GHOSTDAG results they must produce, so a
+// refactor of coloring or ordering can't quietly change consensus. A fixture
+// is plain text, one statement per line, `#` starting a comment:
+//
+//   k 0
+//   1 <- 0          | blue 1
+//   2 <- 0          | red 1 # block 1 is blue in its anticone
+//   3 <- 1 2        | blue 2
+//   chain 0 1 3
+//   order 0 1 2 3
+//
+// Blocks go in insertion order as `id <- parents`, optionally followed by the
+// expected color and blue score. `chain` is the selected chain from genesis
+// and `order` the total order; either may be left out. Genesis is block 0.
+// Expectations are worked out by hand, not copied from the engine. A blue
+// score counts the blues in a block's past as its own GHOSTDAG colored them.
+// Colors are the final virtual's: each selected-chain block colors its
+// mergeset in topological order, a block red when more than k blues of the
+// chain block's blue set sit in its anticone or one of those already has k.
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use crate::knight::KMode;
+use crate::{Color, ToyDag};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureBlock {
+    pub id: u64,
+    pub parents: Vec<u64>,
+    pub expect: Option<(Color, u64)>, // Color and blue score
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    pub k: usize,
+    pub blocks: Vec<FixtureBlock>,
+    pub chain: Option<Vec<u64>>,
+    pub order: Option<Vec<u64>>,
+}
+
+impl FromStr for Fixture {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut fixture = Fixture { k: crate::K, blocks: Vec::new(), chain: None, order: None };
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at = |e: String| format!("line {}: {}", n + 1, e);
+            let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match keyword {
+                "k" => fixture.k = rest.trim().parse().map_err(|_| at(format!("bad k '{}'", rest.trim())))?,
+                "chain" => fixture.chain = Some(ids(rest).map_err(at)?),
+                "order" => fixture.order = Some(ids(rest).map_err(at)?),
+                _ => fixture.blocks.push(block(line).map_err(at)?),
+            }
+        }
+        Ok(fixture)
+    }
+}
+
+fn ids(text: &str) -> Result<Vec<u64>, String> {
+    text.split_whitespace().map(|id| id.parse().map_err(|_| format!("bad block id '{}'", id))).collect()
+}
+
+fn block(line: &str) -> Result<FixtureBlock, String> {
+    let (shape, expect) = line.split_once('|').unwrap_or((line, ""));
+    let (id, parents) = shape.split_once("<-").ok_or_else(|| format!("expected 'id <- parents', got '{}'", line))?;
+    let id = id.trim().parse().map_err(|_| format!("bad block id '{}'", id.trim()))?;
+    let parents = ids(parents)?;
+    let expect = match expect.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => None,
+        [color, score] => {
+            let color = match color {
+                "blue" => Color::Blue,
+                "red" => Color::Red,
+                _ => return Err(format!("bad color '{}'", color)),
+            };
+            Some((color, score.parse().map_err(|_| format!("bad blue score '{}'", score))?))
+        }
+        _ => return Err(format!("expected '| color blue_score' after block {}", id)),
+    };
+    Ok(FixtureBlock { id, parents, expect })
+}
+
+impl Fixture {
+    // The fixture's DAG under its k, StitchBot kept out of it
+    pub fn build(&self) -> Result<ToyDag, String> {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = KMode::Fixed(self.k);
+        for block in &self.blocks {
+            dag.insert_block(block.id, block.parents.clone(), vec![], None)
+                .then_some(())
+                .ok_or_else(|| format!("block {} was refused", block.id))?;
+        }
+        Ok(dag)
+    }
+
+    // Build the DAG and list every way it differs from what the fixture expects
+    pub fn check(&self) -> Result<(), Vec<String>> {
+        let dag = self.build().map_err(|e| vec![e])?;
+        let mut mismatches = Vec::new();
+        for block in &self.blocks {
+            let Some((color, score)) = block.expect else {
+                continue;
+            };
+            let got = &dag[block.id];
+            if got.color() != color || got.blue_score() != score {
+                mismatches.push(format!(
+                    "block {}: expected {} {}, got {} {}",
+                    block.id,
+                    name(color),
+                    score,
+                    name(got.color()),
+                    got.blue_score()
+                ));
+            }
+        }
+        for (what, expected, got) in [("chain", &self.chain, dag.selected_chain()), ("order", &self.order, dag.ordered_blocks())] {
+            if let Some(expected) = expected
+                && *expected != got
+            {
+                mismatches.push(format!("{}: expected {:?}, got {:?}", what, expected, got));
+            }
+        }
+        if mismatches.is_empty() { Ok(()) } else { Err(mismatches) }
+    }
+
+    // The same fixture with every expectation filled in from what the DAG
+    // does now: how a new fixture is first written, or an intended
+    // consensus change is accepted
+    pub fn snapshot(&self) -> Result<Fixture, String> {
+        let dag = self.build()?;
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|b| FixtureBlock { expect: Some((dag[b.id].color(), dag[b.id].blue_score())), ..b.clone() })
+            .collect();
+        Ok(Fixture { k: self.k, blocks, chain: Some(dag.selected_chain()), order: Some(dag.ordered_blocks()) })
+    }
+
+    // The fixture `text` with every expectation replaced by what the DAG
+    // does now. Comments and blank lines stay where they were; a missing
+    // `chain` or `order` is added at the end.
+    pub fn bless(text: &str) -> Result<String, String> {
+        let snapshot = text.parse::<Fixture>()?.snapshot()?;
+        let mut expected = snapshot.blocks.iter().filter_map(|b| b.expect);
+        let (mut chain, mut order) = (snapshot.chain, snapshot.order);
+        let code = |line: &str| line.split('#').next().unwrap().trim().to_string();
+        let width = text.lines().map(code).filter(|c| c.contains("<-")).map(|c| shape(&c).len()).max().unwrap_or(0);
+
+        let mut out = String::new();
+        for line in text.lines() {
+            let code = code(line);
+            let keyword = code.split_whitespace().next().unwrap_or("");
+            let blessed = match keyword {
+                "" | "k" => None,
+                "chain" => chain.take().map(|c| format!("chain {}", join(&c))),
+                "order" => order.take().map(|o| format!("order {}", join(&o))),
+                _ => expected.next().map(|(color, score)| format!("{:<width$} | {} {}", shape(&code), name(color), score)),
+            };
+            match (blessed, line.split_once('#')) {
+                (None, _) => _ = writeln!(out, "{}", line),
+                (Some(blessed), Some((_, comment))) => _ = writeln!(out, "{} #{}", blessed, comment),
+                (Some(blessed), None) => _ = writeln!(out, "{}", blessed),
+            }
+        }
+        if let Some(chain) = chain {
+            let _ = writeln!(out, "chain {}", join(&chain));
+        }
+        if let Some(order) = order {
+            let _ = writeln!(out, "order {}", join(&order));
+        }
+        Ok(out)
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "k {}", self.k);
+        let shapes: Vec<String> = self.blocks.iter().map(|b| format!("{} <- {}", b.id, join(&b.parents))).collect();
+        let width = shapes.iter().map(String::len).max().unwrap_or(0);
+        for (block, shape) in self.blocks.iter().zip(shapes) {
+            match block.expect {
+                Some((color, score)) => _ = writeln!(out, "{:<width$} | {} {}", shape, name(color), score),
+                None => _ = writeln!(out, "{}", shape),
+            }
+        }
+        if let Some(chain) = &self.chain {
+            let _ = writeln!(out, "chain {}", join(chain));
+        }
+        if let Some(order) = &self.order {
+            let _ = writeln!(out, "order {}", join(order));
+        }
+        out
+    }
+}
+
+// A block line's `id <- parents`, without its expectation
+fn shape(code: &str) -> &str {
+    code.split_once('|').map_or(code, |(shape, _)| shape).trim()
+}
+
+fn name(color: Color) -> &'static str {
+    match color {
+        Color::Blue => "blue",
+        Color::Red => "red",
+    }
+}
+
+fn join(ids: &[u64]) -> String {
+    ids.iter().map(u64::to_string).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::Fixture;
+
+    // Every fixture under `fixtures/` must still hold. With TOYDAG_BLESS=1 the
+    // files are rewritten with the current results instead, comments kept.
+    #[test]
+    fn fixtures_hold() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let bless = std::env::var_os("TOYDAG_BLESS").is_some();
+        let mut paths: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        paths.retain(|p| p.extension().is_some_and(|e| e == "dag"));
+        paths.sort();
+        assert!(!paths.is_empty(), "no fixtures in {}", dir.display());
+
+        let mut failures = Vec::new();
+        for path in paths {
+            let text = fs::read_to_string(&path).unwrap();
+            let fixture: Fixture = text.parse().unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            if bless {
+                fs::write(&path, Fixture::bless(&text).unwrap()).unwrap();
+            } else if let Err(mismatches) = fixture.check() {
+                failures.push(format!("{}:\n  {}", path.display(), mismatches.join("\n  ")));
+            }
+        }
+        assert!(failures.is_empty(), "fixtures no longer hold:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn fixtures_round_trip_and_report_mismatches() {
+        let fixture: Fixture = "k 0\n1 <- 0\n2 <- 0 | blue 9 # wrong\norder 2 1 0\n".parse().unwrap();
+        let mismatches = fixture.check().unwrap_err();
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].starts_with("block 2: expected blue 9"));
+        assert!(mismatches[1].starts_with("order:"));
+
+        let snapshot = fixture.snapshot().unwrap();
+        assert_eq!(snapshot.check(), Ok(()));
+        assert_eq!(snapshot.render().parse::<Fixture>(), Ok(snapshot));
+        assert!("1 <- 0 | green 1".parse::<Fixture>().unwrap_err().starts_with("line 1: bad color"));
+    }
+
+    #[test]
+    fn blessing_keeps_comments_and_layout() {
+        let text = "# Two siblings at k = 0\nk 0\n\n1 <- 0\n2 <- 0 | blue 9 # wrong\n10 <- 1 2\norder 2 1 0\n";
+        let blessed = Fixture::bless(text).unwrap();
+        assert_eq!(
+            blessed,
+            "# Two siblings at k = 0\nk 0\n\n1 <- 0    | blue 1\n2 <- 0    | red 1 # wrong\n10 <- 1 2 | blue 2\norder 0 1 2 10\nchain 0 1 10\n"
+        );
+        assert_eq!(blessed.parse::<Fixture>().unwrap().check(), Ok(()));
+    }
+}
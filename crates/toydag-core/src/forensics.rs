@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+
+use crate::{Color, ToyDag};
+
+// Heuristics for spotting misbehaving miners in a finished DAG. None of them
+// proves anything on its own: latency makes honest blocks look stale too, and
+// a big miner wins runs of blocks by luck. They are meant to rank miners by
+// how often their blocks look odd, not to convict one block.
+//
+// "Visible" and "silence" are measured in insertions, the only clock a DAG
+// keeps for itself; timestamps are whatever the miners wrote.
+#[derive(Debug, Clone)]
+pub struct Forensics {
+    pub grace: usize,         // Insertions a block may lag behind and still count as unseen
+    pub stale_lag: u64,       // Blue score a block may trail the best visible block by
+    pub silence: usize,       // Insertions without a block from a miner before a run counts as a burst
+    pub burst: usize,         // Consecutive insertions from one miner that make a run a burst
+    pub clock_tolerance: u64, // Timestamp units a parent may claim to be newer than its child
+}
+
+impl Default for Forensics {
+    fn default() -> Self {
+        Forensics { grace: 10, stale_lag: 3, silence: 50, burst: 4, clock_tolerance: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suspicion {
+    // Built below blocks that had been around for a while: a miner ignoring
+    // fresh tips, or one that mined in private and released late
+    StaleParents { lag: u64 },
+    // One of `run` blocks in a row from one miner after `silence` insertions
+    // without any, the shape of a withheld chain being published
+    Burst { run: usize, silence: usize },
+    // Stamped `by` units before its newest parent
+    BackDated { by: u64 },
+    // Stamped `by` units after its latest child. A lone child stamped before
+    // its only parent flags both; with more children the honest side clears.
+    FutureDated { by: u64 },
+}
+
+impl Suspicion {
+    pub fn name(self) -> &'static str {
+        match self {
+            Suspicion::StaleParents { .. } => "stale parents",
+            Suspicion::Burst { .. } => "burst",
+            Suspicion::BackDated { .. } => "back-dated",
+            Suspicion::FutureDated { .. } => "future-dated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub block: u64,
+    pub miner: Option<u32>,
+    pub suspicion: Suspicion,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MinerSuspicion {
+    pub blocks: usize,
+    pub stale: usize,
+    pub bursts: usize, // Blocks in bursts, not bursts
+    pub timestamps: usize,
+    pub score: f64, // Share of the miner's blocks flagged at least once
+}
+
+impl Forensics {
+    // Every finding, in insertion order within each heuristic
+    pub fn inspect(&self, dag: &ToyDag) -> Vec<Finding> {
+        let mut findings = self.stale_parents(dag);
+        findings.extend(self.bursts(dag));
+        findings.extend(self.timestamps(dag));
+        findings
+    }
+
+    // The blue score a block would have had on the best block inserted at
+    // least `grace` insertions before it, against the one it has
+    fn stale_parents(&self, dag: &ToyDag) -> Vec<Finding> {
+        let reach = |id: u64| {
+            let b = &dag.blocks[&id];
+            b.blue_score + (b.color == Color::Blue) as u64
+        };
+        let order: Vec<u64> = dag.stats.timeline.iter().map(|row| row.block).collect();
+        let mut visible = reach(dag.genesis);
+        let mut findings = Vec::new();
+        for (i, &id) in order.iter().enumerate() {
+            if i >= self.grace {
+                visible = visible.max(reach(order[i - self.grace]));
+            }
+            let block = &dag.blocks[&id];
+            let lag = visible.saturating_sub(block.blue_score);
+            if lag > self.stale_lag {
+                findings.push(Finding { block: id, miner: block.miner, suspicion: Suspicion::StaleParents { lag } });
+            }
+        }
+        findings
+    }
+
+    fn bursts(&self, dag: &ToyDag) -> Vec<Finding> {
+        let order: Vec<(u64, Option<u32>)> =
+            dag.stats.timeline.iter().map(|row| (row.block, dag.blocks[&row.block].miner)).collect();
+        let mut last_seen: HashMap<u32, usize> = HashMap::new();
+        let mut findings = Vec::new();
+        let mut start = 0;
+        while start < order.len() {
+            let miner = order[start].1;
+            let end = start + order[start..].iter().take_while(|(_, m)| *m == miner).count();
+            if let Some(m) = miner {
+                // A miner never seen before has been silent since genesis
+                let silence = start - last_seen.get(&m).map_or(0, |&at| at + 1);
+                let run = end - start;
+                if run >= self.burst && silence >= self.silence {
+                    let suspicion = Suspicion::Burst { run, silence };
+                    findings.extend(order[start..end].iter().map(|&(block, miner)| Finding { block, miner, suspicion }));
+                }
+                last_seen.insert(m, end - 1);
+            }
+            start = end;
+        }
+        findings
+    }
+
+    fn timestamps(&self, dag: &ToyDag) -> Vec<Finding> {
+        let mut ids: Vec<u64> = dag.blocks.keys().copied().filter(|&id| id != dag.genesis).collect();
+        ids.sort_unstable();
+        let mut findings = Vec::new();
+        for id in ids {
+            let block = &dag.blocks[&id];
+            let newest_parent = block.parents.iter().map(|p| dag.blocks[p].timestamp).max().unwrap_or(0);
+            if newest_parent > block.timestamp + self.clock_tolerance {
+                let by = newest_parent - block.timestamp;
+                findings.push(Finding { block: id, miner: block.miner, suspicion: Suspicion::BackDated { by } });
+            }
+            let latest_child = dag.children.get(&id).into_iter().flatten().map(|c| dag.blocks[c].timestamp).max();
+            if let Some(child) = latest_child
+                && block.timestamp > child + self.clock_tolerance
+            {
+                let by = block.timestamp - child;
+                findings.push(Finding { block: id, miner: block.miner, suspicion: Suspicion::FutureDated { by } });
+            }
+        }
+        findings
+    }
+}
+
+// Findings tallied per miner; blocks without a known miner sit under None
+pub fn suspicion(dag: &ToyDag, findings: &[Finding]) -> BTreeMap<Option<u32>, MinerSuspicion> {
+    let mut per_miner: BTreeMap<Option<u32>, MinerSuspicion> = BTreeMap::new();
+    for block in dag.blocks.values().filter(|b| b.id != dag.genesis) {
+        per_miner.entry(block.miner).or_default().blocks += 1;
+    }
+    let mut flagged: HashSet<u64> = HashSet::new();
+    for f in findings {
+        let entry = per_miner.entry(f.miner).or_default();
+        match f.suspicion {
+            Suspicion::StaleParents { .. } => entry.stale += 1,
+            Suspicion::Burst { .. } => entry.bursts += 1,
+            Suspicion::BackDated { .. } | Suspicion::FutureDated { .. } => entry.timestamps += 1,
+        }
+        if flagged.insert(f.block) {
+            entry.score += 1.0;
+        }
+    }
+    for entry in per_miner.values_mut() {
+        entry.score /= entry.blocks.max(1) as f64;
+    }
+    per_miner
+}
+
+// Miners from most to least suspicious, then the first `limit` findings
+pub fn report(dag: &ToyDag, findings: &[Finding], limit: usize) -> String {
+    let mut miners: Vec<(Option<u32>, MinerSuspicion)> = suspicion(dag, findings).into_iter().collect();
+    miners.sort_by(|a, b| b.1.score.total_cmp(&a.1.score).then(a.0.cmp(&b.0)));
+
+    let mut out = String::new();
+    let _ = writeln!(out, "🔎 Forensics: {} findings over {} blocks", findings.len(), dag.block_count() - 1);
+    let _ = writeln!(out, "   {:<10} {:>7} {:>6} {:>6} {:>10} {:>10}", "miner", "blocks", "stale", "burst", "timestamp", "suspicion");
+    for (miner, s) in &miners {
+        let _ = writeln!(
+            out,
+            "   {:<10} {:>7} {:>6} {:>6} {:>10} {:>9.1}%",
+            miner.map_or("unknown".to_string(), |m| format!("miner {}", m)),
+            s.blocks,
+            s.stale,
+            s.bursts,
+            s.timestamps,
+            s.score * 100.0
+        );
+    }
+    for f in findings.iter().take(limit) {
+        let detail = match f.suspicion {
+            Suspicion::StaleParents { lag } => format!("{} blue score behind the best visible block", lag),
+            Suspicion::Burst { run, silence } => format!("{} in a row after {} insertions without one", run, silence),
+            Suspicion::BackDated { by } => format!("{} before its newest parent", by),
+            Suspicion::FutureDated { by } => format!("{} after its latest child", by),
+        };
+        let miner = f.miner.map_or("unknown".to_string(), |m| m.to_string());
+        let _ = writeln!(out, "  block {:>5} (miner {}): {}, {}", f.block, miner, f.suspicion.name(), detail);
+    }
+    if findings.len() > limit {
+        let _ = writeln!(out, "  … {} more", findings.len() - limit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Miner 0 mines a chain of ten; miner 1 then publishes three blocks it
+    // mined in private on genesis, the last stamped before its parent
+    #[test]
+    fn withheld_blocks_are_flagged_and_pinned_on_their_miner() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let mut tip = dag.genesis();
+        for i in 1..=10 {
+            dag.insert_block_at(i, vec![tip], vec![], Some(0), i * 10);
+            tip = i;
+        }
+        dag.insert_block_at(11, vec![0], vec![], Some(1), 5);
+        dag.insert_block_at(12, vec![11], vec![], Some(1), 6);
+        dag.insert_block_at(13, vec![12], vec![], Some(1), 1);
+
+        let forensics = Forensics { grace: 2, stale_lag: 3, silence: 10, burst: 3, clock_tolerance: 0 };
+        let findings = forensics.inspect(&dag);
+        let kinds = |block: u64| findings.iter().filter(|f| f.block == block).map(|f| f.suspicion.name()).collect::<Vec<_>>();
+        assert_eq!(kinds(11), vec!["stale parents", "burst"]);
+        assert_eq!(kinds(12), vec!["stale parents", "burst", "future-dated"]);
+        assert_eq!(kinds(13), vec!["stale parents", "burst", "back-dated"]);
+        assert!(findings.iter().all(|f| f.miner == Some(1)));
+
+        let per_miner = suspicion(&dag, &findings);
+        assert_eq!(per_miner[&Some(0)].score, 0.0);
+        assert_eq!(per_miner[&Some(1)], MinerSuspicion { blocks: 3, stale: 3, bursts: 3, timestamps: 2, score: 1.0 });
+    }
+}
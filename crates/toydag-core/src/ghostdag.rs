@@ -0,0 +1,236 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::events::DagEvent;
+use crate::{Color, ToyDag};
+
+// GHOSTDAG's coloring of one block's mergeset, from that block's own view.
+// It depends on nothing but the block's past, so every node that has the
+// block agrees on it whatever order the blocks came in. A block's blue set is
+// its selected parent's, plus the selected parent, plus `mergeset_blues`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Ghostdag {
+    pub(crate) k: usize, // k the mergeset was colored under
+    pub(crate) mergeset_blues: Vec<u64>, // Selected parent first, then merged blues in topological order
+    pub(crate) mergeset_reds: Vec<u64>,
+    pub(crate) blues_anticone_sizes: HashMap<u64, usize>, // Blues whose blue anticone this view set or grew
+    pub(crate) blue_anticones: HashMap<u64, usize>,       // Blues each merged block saw in its anticone
+}
+
+impl Ghostdag {
+    pub(crate) fn new(selected_parent: u64, k: usize) -> Self {
+        Ghostdag {
+            k,
+            mergeset_blues: vec![selected_parent],
+            blues_anticone_sizes: HashMap::from([(selected_parent, 0)]),
+            ..Ghostdag::default()
+        }
+    }
+
+    pub(crate) fn selected_parent(&self) -> u64 {
+        self.mergeset_blues[0]
+    }
+
+    // The mergeset without the selected parent
+    pub(crate) fn mergeset(&self) -> impl Iterator<Item = u64> + '_ {
+        self.mergeset_blues[1..].iter().chain(&self.mergeset_reds).copied()
+    }
+}
+
+impl ToyDag {
+    // Color a mergeset in topological order. A candidate is blue when at most
+    // k blues sit in its anticone and none of those already has k, so the
+    // blue set stays a k-cluster.
+    pub(crate) fn ghostdag(&self, selected_parent: u64, mergeset: &[u64], k: usize) -> Ghostdag {
+        let mut view = Ghostdag::new(selected_parent, k);
+        let mut candidates = mergeset.to_vec();
+        candidates.sort_by_key(|&c| (self.blocks[&c].topo_depth, c));
+        for c in candidates {
+            self.color_candidate(&mut view, c);
+        }
+        view
+    }
+
+    fn color_candidate(&self, view: &mut Ghostdag, candidate: u64) {
+        let (selected_parent, k) = (view.selected_parent(), view.k);
+        let blues = self.anticone_blues(view, candidate);
+        view.blue_anticones.insert(candidate, blues.len());
+        let sizes: Vec<(u64, usize)> = if blues.len() <= k {
+            blues.iter().map(|&b| (b, self.blue_anticone_size(view, selected_parent, b))).collect()
+        } else {
+            Vec::new()
+        };
+        if blues.len() <= k && sizes.iter().all(|&(_, size)| size < k) {
+            view.mergeset_blues.push(candidate);
+            view.blues_anticone_sizes.insert(candidate, blues.len());
+            for (b, size) in sizes {
+                view.blues_anticone_sizes.insert(b, size + 1);
+            }
+        } else {
+            view.mergeset_reds.push(candidate);
+        }
+    }
+
+    // Blues of the view's blue set in `candidate`'s anticone. Walks the
+    // selected chain down until a chain block is in the candidate's past;
+    // everything blue below that is too.
+    fn anticone_blues(&self, view: &Ghostdag, candidate: u64) -> Vec<u64> {
+        let mut past = Past::new(self, candidate);
+        let mut found: Vec<u64> = view.mergeset_blues.iter().copied().filter(|&b| !past.contains(b)).collect();
+        let mut chain = Some(view.selected_parent());
+        while let Some(block) = chain.filter(|&c| !past.contains(c)) {
+            found.extend(self.ghostdag[&block].mergeset_blues.iter().copied().filter(|&b| !past.contains(b)));
+            chain = self.blocks[&block].selected_parent;
+        }
+        found
+    }
+
+    // How many blues a blue block has in its anticone, as of the view: the
+    // latest count down the selected chain. Only genesis is in no view's
+    // counts, and it is in every block's past.
+    fn blue_anticone_size(&self, view: &Ghostdag, selected_parent: u64, block: u64) -> usize {
+        if let Some(&size) = view.blues_anticone_sizes.get(&block) {
+            return size;
+        }
+        let mut chain = Some(selected_parent);
+        while let Some(c) = chain {
+            if let Some(&size) = self.ghostdag[&c].blues_anticone_sizes.get(&block) {
+                return size;
+            }
+            chain = self.blocks[&c].selected_parent;
+        }
+        0
+    }
+
+    // Colors are the virtual's: the selected chain's mergesets as each chain
+    // block colored them, and the rest as the virtual colors its own
+    // mergeset. `new` has just gone in; moving the virtual onto `best`
+    // repaints the chain blocks it did not have before and the whole virtual
+    // mergeset. When the virtual stays put and `new` comes last in
+    // topological order, the old coloring stands and only `new` is colored.
+    // Nor does anything else move if `new` already has more than k blues in
+    // its anticone from the blocks ordered before it: a red block changes no
+    // later block's count. Blue scores and blue work are each block's own
+    // view and never move.
+    pub(crate) fn recolor(&mut self, best: u64, new: u64, tick: u64) {
+        let k = self.k_mode.k();
+        if best == self.selected_parent && self.virtual_view.k == k {
+            let key = |id: u64| (self.blocks[&id].topo_depth, id);
+            let later: HashSet<u64> = self.virtual_view.mergeset().filter(|&m| key(m) > key(new)).collect();
+            if later.is_empty() {
+                let mut view = std::mem::take(&mut self.virtual_view);
+                self.color_candidate(&mut view, new);
+                let color = if view.mergeset_reds.last() == Some(&new) { Color::Red } else { Color::Blue };
+                self.virtual_view = view;
+                self.paint(&[new], color, new, tick);
+                return;
+            }
+            let before = self.anticone_blues(&self.virtual_view, new).into_iter().filter(|b| !later.contains(b)).count();
+            if before > k {
+                self.virtual_view.blue_anticones.insert(new, before);
+                self.virtual_view.mergeset_reds.push(new);
+                self.paint(&[new], Color::Red, new, tick);
+                return;
+            }
+        }
+
+        let step = |dag: &ToyDag, id: u64| dag.blocks[&id].selected_parent.unwrap_or(id);
+        let (mut old, mut gain) = (self.selected_parent, best);
+        let mut gained = Vec::new();
+        while old != gain {
+            if self.blocks[&old].topo_depth >= self.blocks[&gain].topo_depth {
+                old = step(self, old);
+            } else {
+                gained.push(gain);
+                gain = step(self, gain);
+            }
+        }
+        for chain_block in gained.into_iter().rev() {
+            let Ghostdag { mergeset_blues, mergeset_reds, .. } = self.ghostdag[&chain_block].clone();
+            self.paint(&mergeset_blues, Color::Blue, new, tick);
+            self.paint(&mergeset_reds, Color::Red, new, tick);
+        }
+
+        let tips: Vec<u64> = self.tips().collect();
+        let mergeset = self.mergeset_without_selected(best, &tips);
+        let view = self.ghostdag(best, &mergeset, k);
+        self.paint(&view.mergeset_blues, Color::Blue, new, tick);
+        self.paint(&view.mergeset_reds, Color::Red, new, tick);
+        self.virtual_view = view;
+    }
+
+    // The virtual's view of a block it has: blues in its anticone, counted as
+    // the virtual's GHOSTDAG went; for its selected parent, the whole
+    // mergeset's blues. None for blocks below the virtual's mergeset.
+    pub(crate) fn virtual_blue_anticone(&self, id: u64) -> Option<usize> {
+        if id == self.virtual_view.selected_parent() {
+            return Some(self.virtual_view.mergeset_blues.len() - 1);
+        }
+        self.virtual_view.blue_anticones.get(&id).copied()
+    }
+
+    // Set colors, reporting every block that changes; `new` is being
+    // colored for the first time, so it has nothing to flip from
+    fn paint(&mut self, ids: &[u64], color: Color, new: u64, tick: u64) {
+        for &id in ids {
+            let block = self.blocks.get_mut(&id).expect("colored blocks are in the DAG");
+            let from = std::mem::replace(&mut block.color, color);
+            if id == new {
+                if color == Color::Red {
+                    self.track_red(id, tick);
+                }
+            } else if from != color {
+                self.emit(DagEvent::ColorFlipped { id, from, to: color });
+                match color {
+                    Color::Red => self.track_red(id, tick),
+                    Color::Blue => {
+                        self.untrack_red(id);
+                        self.rescue_reds(id, tick);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// A block's past, walked down only as deep as the questions asked of it.
+// Every ancestor at a given depth is reached through blocks above it, so
+// expanding everything at or above that depth settles membership there.
+// Nothing at or above the block's own depth can be in its past.
+struct Past<'a> {
+    dag: &'a ToyDag,
+    depth: usize,
+    seen: HashSet<u64>,
+    frontier: BinaryHeap<(usize, u64)>,
+}
+
+impl<'a> Past<'a> {
+    fn new(dag: &'a ToyDag, block: u64) -> Self {
+        let depth = dag.blocks[&block].topo_depth;
+        let parents = &dag.blocks[&block].parents;
+        Past {
+            dag,
+            depth,
+            seen: parents.iter().copied().collect(),
+            frontier: parents.iter().map(|&p| (dag.blocks[&p].topo_depth, p)).collect(),
+        }
+    }
+
+    fn contains(&mut self, id: u64) -> bool {
+        if self.seen.contains(&id) {
+            return true;
+        }
+        let depth = self.dag.blocks[&id].topo_depth;
+        if depth >= self.depth {
+            return false;
+        }
+        while self.frontier.peek().is_some_and(|&(d, _)| d >= depth) {
+            let (_, block) = self.frontier.pop().unwrap();
+            for &p in &self.dag.blocks[&block].parents {
+                if self.seen.insert(p) {
+                    self.frontier.push((self.dag.blocks[&p].topo_depth, p));
+                }
+            }
+        }
+        self.seen.contains(&id)
+    }
+}
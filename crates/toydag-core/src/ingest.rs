@@ -0,0 +1,358 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+
+use crate::error::DagError;
+use crate::{NewBlock, ToyDag};
+
+impl ToyDag {
+    // Insert a whole topology handed over in any order (a snapshot, a fuzzer
+    // case, another node's blocks). The batch is checked as a unit first:
+    // every parent must be in the DAG or the batch, no id may appear twice,
+    // nor a parent twice in one block, and there must be no cycles; any of
+    // those refuses the whole batch and leaves the DAG untouched. Blocks the
+    // DAG already has are skipped. The rest go through `insert_batch`
+    // parents-first, lowest id first among blocks that are ready together,
+    // so the result doesn't depend on the order they came in. Returns how
+    // many were inserted; blocks a consensus rule refuses are left out like
+    // in `insert_batch`.
+    pub fn ingest_batch(&mut self, blocks: Vec<NewBlock>) -> Result<usize, DagError> {
+        let order = self.topo_sort(blocks)?;
+        Ok(self.insert_batch(order))
+    }
+
+    fn topo_sort(&self, blocks: Vec<NewBlock>) -> Result<Vec<NewBlock>, DagError> {
+        let mut pending: HashMap<u64, NewBlock> = HashMap::with_capacity(blocks.len());
+        for block in blocks {
+            if self.blocks.contains_key(&block.id) {
+                continue;
+            }
+            self.check_parent_list(&block)?;
+            if let Some(dup) = pending.insert(block.id, block) {
+                return Err(DagError::DuplicateBlock(dup.id));
+            }
+        }
+
+        // Kahn's algorithm over the parent links that stay inside the batch
+        let mut waiting: HashMap<u64, usize> = HashMap::with_capacity(pending.len());
+        let mut children: HashMap<u64, Vec<u64>> = HashMap::new();
+        for block in pending.values() {
+            let mut count = 0;
+            for &parent in &block.parents {
+                if pending.contains_key(&parent) {
+                    children.entry(parent).or_default().push(block.id);
+                    count += 1;
+                } else if !self.blocks.contains_key(&parent) {
+                    return Err(DagError::UnknownParent { block: block.id, parent });
+                }
+            }
+            waiting.insert(block.id, count);
+        }
+        let mut ready: BinaryHeap<Reverse<u64>> =
+            waiting.iter().filter(|&(_, &n)| n == 0).map(|(&id, _)| Reverse(id)).collect();
+        let mut order = Vec::with_capacity(pending.len());
+        while let Some(Reverse(id)) = ready.pop() {
+            for child in children.remove(&id).unwrap_or_default() {
+                let n = waiting.get_mut(&child).expect("children are batch blocks");
+                *n -= 1;
+                if *n == 0 {
+                    ready.push(Reverse(child));
+                }
+            }
+            order.push(pending.remove(&id).expect("each block is ready once"));
+        }
+        if !pending.is_empty() {
+            let mut stuck: Vec<u64> = pending.into_keys().collect();
+            stuck.sort_unstable();
+            return Err(DagError::Cycle(stuck));
+        }
+        Ok(order)
+    }
+}
+
+// Blocks that arrived before one of their parents, waiting for it. At the
+// limit the longest-waiting orphan is dropped to make room, so a source that
+// never sends the missing parent can't fill memory.
+#[derive(Debug, Clone)]
+pub struct OrphanPool {
+    orphans: HashMap<u64, (u64, NewBlock)>, // With their arrival number
+    waiting_on: HashMap<u64, Vec<u64>>,     // Missing parent -> orphans that need it
+    queue: VecDeque<(u64, u64)>,            // (arrival, id), oldest first; entries for orphans gone since are skipped
+    arrivals: u64,
+    pub limit: usize,
+}
+
+// What happened to the blocks handed to an `OrphanPool`, cumulative
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IngestSummary {
+    pub received: usize,
+    pub inserted: usize,   // Orphans included, once their parents came
+    pub duplicates: usize, // Already in the DAG or the pool
+    pub parked: usize,     // Arrived before a parent
+    pub evicted: usize,    // Dropped from a full pool
+    pub refused: usize,    // Turned down by the DAG's rules
+}
+
+impl OrphanPool {
+    pub fn new(limit: usize) -> Self {
+        OrphanPool { orphans: HashMap::new(), waiting_on: HashMap::new(), queue: VecDeque::new(), arrivals: 0, limit }
+    }
+
+    pub fn len(&self) -> usize {
+        self.orphans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.orphans.contains_key(&id)
+    }
+
+    // Insert a block whose parents are all in, then every orphan that
+    // completes; park it when a parent is still missing
+    pub fn submit(&mut self, dag: &mut ToyDag, block: NewBlock, summary: &mut IngestSummary) {
+        summary.received += 1;
+        if dag.blocks.contains_key(&block.id) || self.contains(block.id) {
+            summary.duplicates += 1;
+            return;
+        }
+        let missing: Vec<u64> = block.parents.iter().copied().filter(|p| !dag.blocks.contains_key(p)).collect();
+        if !missing.is_empty() {
+            self.park(block, missing, summary);
+            return;
+        }
+
+        let mut ready = vec![block];
+        while let Some(block) = ready.pop() {
+            let id = block.id;
+            if dag.add_block(block).is_err() {
+                summary.refused += 1;
+                continue; // Its orphans wait on, for a parent that won't come
+            }
+            summary.inserted += 1;
+            for child in self.waiting_on.remove(&id).unwrap_or_default() {
+                let complete = self.orphans.get(&child).is_some_and(|(_, b)| b.parents.iter().all(|p| dag.blocks.contains_key(p)));
+                if complete {
+                    ready.push(self.orphans.remove(&child).expect("checked above").1);
+                }
+            }
+        }
+        self.sweep_queue();
+    }
+
+    fn park(&mut self, block: NewBlock, missing: Vec<u64>, summary: &mut IngestSummary) {
+        if self.orphans.len() >= self.limit.max(1) {
+            self.evict_oldest();
+            summary.evicted += 1;
+        }
+        for parent in missing {
+            self.waiting_on.entry(parent).or_default().push(block.id);
+        }
+        self.arrivals += 1;
+        self.queue.push_back((self.arrivals, block.id));
+        self.orphans.insert(block.id, (self.arrivals, block));
+        summary.parked += 1;
+    }
+
+    // Inserted orphans leave their queue entries behind; sweep them out
+    // before they outnumber the live ones
+    fn sweep_queue(&mut self) {
+        if self.queue.len() > 2 * self.orphans.len() {
+            let orphans = &self.orphans;
+            self.queue.retain(|(arrival, id)| orphans.get(id).is_some_and(|(a, _)| a == arrival));
+        }
+    }
+
+    // Drop the longest-waiting orphan, and its place in `waiting_on`
+    fn evict_oldest(&mut self) {
+        while let Some((arrival, id)) = self.queue.pop_front() {
+            if self.orphans.get(&id).is_none_or(|(a, _)| *a != arrival) {
+                continue; // Inserted since, or evicted and parked again later
+            }
+            let (_, block) = self.orphans.remove(&id).expect("checked above");
+            for parent in block.parents {
+                if let Some(children) = self.waiting_on.get_mut(&parent) {
+                    children.retain(|&c| c != id);
+                    if children.is_empty() {
+                        self.waiting_on.remove(&parent);
+                    }
+                }
+            }
+            return;
+        }
+    }
+}
+
+// The sending end of a block feed, for another thread (a network reader, a
+// websocket client) to push blocks through. Clone it for more sources. The
+// queue behind it is bounded, so a source outrunning the DAG is held back.
+#[derive(Debug, Clone)]
+pub struct BlockIngest {
+    sender: SyncSender<NewBlock>,
+}
+
+impl BlockIngest {
+    // Waits while the queue is full; hands the block back once the receiving
+    // side is gone
+    pub fn send(&self, block: NewBlock) -> Result<(), NewBlock> {
+        self.sender.send(block).map_err(|e| e.0)
+    }
+
+    // Hands the block back instead of waiting when the queue is full
+    pub fn try_send(&self, block: NewBlock) -> Result<(), TrySendError<NewBlock>> {
+        self.sender.try_send(block)
+    }
+}
+
+// The receiving end, kept with the DAG. Blocks go in in arrival order, with
+// out-of-order ones held in the orphan pool until their parents show up.
+#[derive(Debug)]
+pub struct IngestQueue {
+    receiver: Receiver<NewBlock>,
+    pub orphans: OrphanPool,
+    pub summary: IngestSummary,
+}
+
+// A feed holding up to `capacity` blocks in flight and `orphan_limit` orphans
+pub fn channel(capacity: usize, orphan_limit: usize) -> (BlockIngest, IngestQueue) {
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let queue = IngestQueue { receiver, orphans: OrphanPool::new(orphan_limit), summary: IngestSummary::default() };
+    (BlockIngest { sender }, queue)
+}
+
+impl IngestQueue {
+    // Insert whatever has arrived, without waiting; how many blocks came in
+    pub fn drain(&mut self, dag: &mut ToyDag) -> usize {
+        let mut received = 0;
+        while let Ok(block) = self.receiver.try_recv() {
+            self.orphans.submit(dag, block, &mut self.summary);
+            received += 1;
+        }
+        received
+    }
+
+    // Insert blocks as they arrive until every `BlockIngest` is dropped
+    pub fn run(&mut self, dag: &mut ToyDag) -> IngestSummary {
+        while let Ok(block) = self.receiver.recv() {
+            self.orphans.submit(dag, block, &mut self.summary);
+        }
+        self.summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+
+    use super::{IngestSummary, OrphanPool, channel};
+    use crate::error::DagError;
+    use crate::testing::{arb_parents, build};
+    use crate::{BLOCK_VERSION, NewBlock, ToyDag};
+
+    fn block(id: u64, parents: Vec<u64>) -> NewBlock {
+        NewBlock { id, parents, txs: vec![], miner: None, timestamp: id, version: BLOCK_VERSION }
+    }
+
+    fn fresh() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // Shuffled or not, a batch ends up where one-at-a-time inserts would
+        #[test]
+        fn shuffled_batches_land_like_ordered_ones(parents in arb_parents(30, 3), seed in any::<u64>()) {
+            let source = build(&parents);
+            let mut blocks: Vec<NewBlock> =
+                parents.iter().enumerate().map(|(i, list)| block(i as u64 + 1, list.clone())).collect();
+            blocks.shuffle(&mut StdRng::seed_from_u64(seed));
+
+            let mut dag = fresh();
+            prop_assert_eq!(dag.ingest_batch(blocks.clone()), Ok(parents.len()));
+            prop_assert_eq!(dag.ordered_blocks(), source.ordered_blocks());
+            prop_assert_eq!(dag.selected_parent(), source.selected_parent());
+            prop_assert_eq!(dag.ingest_batch(blocks), Ok(0)); // Already known, skipped
+        }
+    }
+
+    #[test]
+    fn bad_batches_are_refused_whole() {
+        let mut dag = fresh();
+        assert_eq!(dag.ingest_batch(vec![block(2, vec![1]), block(1, vec![9])]), Err(DagError::UnknownParent { block: 1, parent: 9 }));
+        assert_eq!(dag.ingest_batch(vec![block(1, vec![0]), block(1, vec![0])]), Err(DagError::DuplicateBlock(1)));
+        assert_eq!(
+            dag.ingest_batch(vec![block(1, vec![0]), block(2, vec![1, 0, 1])]),
+            Err(DagError::DuplicateParent { block: 2, parent: 1 })
+        );
+        assert_eq!(
+            dag.ingest_batch(vec![block(1, vec![0]), block(2, vec![1, 3]), block(3, vec![2]), block(4, vec![3])]),
+            Err(DagError::Cycle(vec![2, 3, 4]))
+        );
+        assert_eq!(dag.block_count(), 1);
+    }
+
+    // A feeding thread sending children before parents through a tiny queue
+    #[test]
+    fn channel_feed_parks_orphans_until_their_parents_arrive() {
+        let (ingest, mut queue) = channel(2, 8);
+        let feeder = std::thread::spawn(move || {
+            for (id, parents) in [(2, vec![1]), (3, vec![1, 2]), (1, vec![0]), (4, vec![3]), (4, vec![3]), (6, vec![5])] {
+                ingest.send(block(id, parents)).unwrap();
+            }
+        });
+        let mut dag = fresh();
+        let summary = queue.run(&mut dag);
+        feeder.join().unwrap();
+
+        assert_eq!(dag.selected_chain(), vec![0, 1, 2, 3, 4]);
+        assert_eq!((summary.received, summary.inserted, summary.parked, summary.duplicates), (6, 4, 3, 1));
+        assert!(queue.orphans.contains(6));
+
+        let (ingest, mut queue) = channel(1, 1);
+        ingest.send(block(8, vec![7])).unwrap();
+        assert!(ingest.try_send(block(9, vec![7])).is_err()); // Full until drained
+        queue.drain(&mut dag);
+        ingest.send(block(9, vec![7])).unwrap();
+        queue.drain(&mut dag);
+        assert_eq!((queue.summary.evicted, queue.orphans.len()), (1, 1));
+    }
+
+    // Over the limit the oldest orphan goes, from both maps; its parent
+    // showing up later then brings in nothing
+    #[test]
+    fn a_full_pool_evicts_the_oldest_orphan_everywhere() {
+        let mut dag = fresh();
+        let mut pool = OrphanPool::new(3);
+        let mut summary = IngestSummary::default();
+        for id in 10..15 {
+            pool.submit(&mut dag, block(id, vec![id - 9, 9]), &mut summary);
+        }
+        assert_eq!((summary.parked, summary.evicted), (5, 2));
+        assert_eq!(pool.len(), 3);
+        assert!(!pool.contains(10) && !pool.contains(11) && pool.contains(12));
+        assert!(!pool.waiting_on.contains_key(&1) && !pool.waiting_on.contains_key(&2));
+        assert_eq!(pool.waiting_on[&9], vec![12, 13, 14]);
+
+        // Block 10 again is an orphan anew, not the one evicted
+        pool.submit(&mut dag, block(10, vec![1, 9]), &mut summary);
+        assert!(pool.contains(10) && !pool.contains(12));
+        assert_eq!(pool.waiting_on[&9], vec![13, 14, 10]);
+
+        pool.submit(&mut dag, block(9, vec![0]), &mut summary);
+        for parent in [1, 4, 5] {
+            pool.submit(&mut dag, block(parent, vec![0]), &mut summary);
+        }
+        assert!(pool.is_empty() && pool.waiting_on.is_empty());
+        assert!(pool.queue.is_empty());
+        assert!(dag.contains(10) && dag.contains(13) && dag.contains(14));
+        assert!(!dag.contains(11) && !dag.contains(12));
+    }
+}
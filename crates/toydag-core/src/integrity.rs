@@ -0,0 +1,219 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fmt::Write as _;
+
+use crate::ToyDag;
+
+// A structural invariant the DAG's own bookkeeping has broken. None of these
+// can happen through the public API; they point at a bug in insertion, a
+// store that was edited by hand, or a fixture built wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    MissingParent { block: u64, parent: u64 },
+    DuplicateParent { block: u64, parent: u64 }, // Named more than once in the block's parent list
+    Cycle { blocks: Vec<u64> }, // Every block on or behind a cycle, so no parents-first order reaches them
+    StaleTip(u64),              // In the tip set but has children
+    MissingTip(u64),            // Childless but not in the tip set
+    ChainScoreNotMonotone { block: u64, selected_parent: u64, score: u64, parent_score: u64 },
+    MissingChild { parent: u64, child: u64 }, // Child names the parent, the parent's index doesn't list it
+    PhantomChild { parent: u64, child: u64 }, // Index lists a child that doesn't name the parent
+    DuplicateChild { parent: u64, child: u64 }, // Index lists the same child more than once
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::MissingParent { block, parent } => write!(f, "block {} names parent {}, which isn't in the DAG", block, parent),
+            Violation::DuplicateParent { block, parent } => write!(f, "block {} names parent {} more than once", block, parent),
+            Violation::Cycle { blocks } => write!(f, "{} blocks sit on or behind a parent cycle: {:?}", blocks.len(), blocks),
+            Violation::StaleTip(id) => write!(f, "block {} is a tip but has children", id),
+            Violation::MissingTip(id) => write!(f, "block {} has no children but isn't a tip", id),
+            Violation::ChainScoreNotMonotone { block, selected_parent, score, parent_score } => write!(
+                f,
+                "chain block {} has blue score {}, not above its selected parent {}'s {}",
+                block, score, selected_parent, parent_score
+            ),
+            Violation::MissingChild { parent, child } => write!(f, "block {} names parent {}, whose children index leaves it out", child, parent),
+            Violation::PhantomChild { parent, child } => write!(f, "block {}'s children index lists {}, which doesn't name it as a parent", parent, child),
+            Violation::DuplicateChild { parent, child } => write!(f, "block {}'s children index lists {} more than once", parent, child),
+        }
+    }
+}
+
+impl ToyDag {
+    // Every broken invariant, one check after another, each in block id order
+    // (the chain check from the tip down). Linear in blocks plus edges.
+    pub fn check_integrity(&self) -> Vec<Violation> {
+        let mut ids: Vec<u64> = self.blocks.keys().copied().collect();
+        ids.sort_unstable();
+        let mut violations = Vec::new();
+
+        for &id in &ids {
+            let parents = &self.blocks[&id].parents;
+            for (i, &parent) in parents.iter().enumerate() {
+                if !self.blocks.contains_key(&parent) {
+                    violations.push(Violation::MissingParent { block: id, parent });
+                }
+                if parents[..i].contains(&parent) {
+                    violations.push(Violation::DuplicateParent { block: id, parent });
+                }
+            }
+        }
+
+        // Kahn's algorithm over known parents: whatever never comes free is
+        // stuck behind a cycle
+        let mut waiting: HashMap<u64, usize> = HashMap::new();
+        let mut children: HashMap<u64, Vec<u64>> = HashMap::new();
+        for &id in &ids {
+            let known: Vec<u64> = self.blocks[&id].parents.iter().copied().filter(|p| self.blocks.contains_key(p)).collect();
+            waiting.insert(id, known.len());
+            for p in known {
+                children.entry(p).or_default().push(id);
+            }
+        }
+        let mut ready: VecDeque<u64> = ids.iter().copied().filter(|id| waiting[id] == 0).collect();
+        while let Some(id) = ready.pop_front() {
+            for &child in children.get(&id).into_iter().flatten() {
+                let left = waiting.get_mut(&child).unwrap();
+                *left -= 1;
+                if *left == 0 {
+                    ready.push_back(child);
+                }
+            }
+        }
+        let stuck: Vec<u64> = ids.iter().copied().filter(|id| waiting[id] > 0).collect();
+        if !stuck.is_empty() {
+            violations.push(Violation::Cycle { blocks: stuck });
+        }
+
+        let mut tips: Vec<u64> = self.tips.iter().copied().collect();
+        tips.sort_unstable();
+        for tip in tips {
+            if children.contains_key(&tip) {
+                violations.push(Violation::StaleTip(tip));
+            }
+        }
+        for &id in &ids {
+            if !children.contains_key(&id) && !self.tips.contains(&id) {
+                violations.push(Violation::MissingTip(id));
+            }
+        }
+
+        // Down the selected chain from the virtual; a chain longer than the
+        // DAG is itself a cycle, already reported
+        let mut current = self.selected_parent;
+        for _ in 0..self.blocks.len() {
+            let Some(block) = self.blocks.get(&current) else { break };
+            let Some(sp) = block.selected_parent else { break };
+            let Some(parent) = self.blocks.get(&sp) else { break };
+            if block.blue_score <= parent.blue_score {
+                violations.push(Violation::ChainScoreNotMonotone {
+                    block: current,
+                    selected_parent: sp,
+                    score: block.blue_score,
+                    parent_score: parent.blue_score,
+                });
+            }
+            current = sp;
+        }
+
+        for &id in &ids {
+            let indexed = self.children.get(&id).map_or(&[][..], |c| c.as_slice());
+            for &child in children.get(&id).into_iter().flatten() {
+                if !indexed.contains(&child) {
+                    violations.push(Violation::MissingChild { parent: id, child });
+                }
+            }
+            for (i, &child) in indexed.iter().enumerate() {
+                if !self.blocks.get(&child).is_some_and(|c| c.parents.contains(&id)) {
+                    violations.push(Violation::PhantomChild { parent: id, child });
+                }
+                if indexed[..i].contains(&child) {
+                    violations.push(Violation::DuplicateChild { parent: id, child });
+                }
+            }
+        }
+        violations
+    }
+}
+
+// Summary plus the first `limit` violations
+pub fn report(violations: &[Violation], blocks: usize, limit: usize) -> String {
+    let mut out = String::new();
+    if violations.is_empty() {
+        let _ = writeln!(out, "✅ All {} blocks pass the structural checks", blocks);
+        return out;
+    }
+    let _ = writeln!(out, "❌ {} integrity violations over {} blocks", violations.len(), blocks);
+    for v in violations.iter().take(limit) {
+        let _ = writeln!(out, "  {}", v);
+    }
+    if violations.len() > limit {
+        let _ = writeln!(out, "  … {} more", violations.len() - limit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupted_bookkeeping_is_reported() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let a = dag.create_block(vec![0]).unwrap();
+        let b = dag.create_block(vec![a]).unwrap();
+        let c = dag.create_block(vec![0]).unwrap();
+        assert!(dag.check_integrity().is_empty());
+
+        dag.tips.insert(a);
+        dag.tips.remove(&c);
+        dag.children.get_mut(&a).unwrap().clear();
+        dag.children.get_mut(&0).unwrap().push(b);
+        dag.blocks.get_mut(&b).unwrap().blue_score = 0;
+        dag.blocks.get_mut(&c).unwrap().parents.push(99);
+
+        assert_eq!(
+            dag.check_integrity(),
+            vec![
+                Violation::MissingParent { block: c, parent: 99 },
+                Violation::StaleTip(a),
+                Violation::MissingTip(c),
+                Violation::ChainScoreNotMonotone { block: b, selected_parent: a, score: 0, parent_score: 1 },
+                Violation::PhantomChild { parent: 0, child: b },
+                Violation::MissingChild { parent: a, child: b },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_parent_cycle_strands_the_blocks_behind_it() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let a = dag.create_block(vec![0]).unwrap();
+        let b = dag.create_block(vec![a]).unwrap();
+        let c = dag.create_block(vec![b]).unwrap();
+        dag.blocks.get_mut(&a).unwrap().parents.push(b);
+        dag.children.get_mut(&b).unwrap().push(a);
+
+        assert_eq!(dag.check_integrity(), vec![Violation::Cycle { blocks: vec![a, b, c] }]);
+    }
+
+    // A repeated edge still lets Kahn's walk through, so it's only caught
+    // by the duplicate checks, not reported as a cycle
+    #[test]
+    fn repeated_parents_and_children_are_reported() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let a = dag.create_block(vec![0]).unwrap();
+        let b = dag.create_block(vec![a]).unwrap();
+        dag.blocks.get_mut(&b).unwrap().parents.push(a);
+        dag.children.get_mut(&0).unwrap().push(a);
+
+        assert_eq!(
+            dag.check_integrity(),
+            vec![Violation::DuplicateParent { block: b, parent: a }, Violation::DuplicateChild { parent: 0, child: a }]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+
+// How the coloring rule's k is chosen
+#[derive(Debug, Clone)]
+pub enum KMode {
+    Fixed(usize),
+    Adaptive(AdaptiveK),
+}
+
+impl KMode {
+    pub fn k(&self) -> usize {
+        match self {
+            KMode::Fixed(k) => *k,
+            KMode::Adaptive(adaptive) => adaptive.k(),
+        }
+    }
+
+    // Feed the mergeset size of a newly inserted block
+    pub fn observe(&mut self, mergeset: usize) {
+        if let KMode::Adaptive(adaptive) = self {
+            adaptive.observe(mergeset);
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KMode::Fixed(_) => "fixed",
+            KMode::Adaptive(_) => "adaptive",
+        }
+    }
+}
+
+// DAGKnight-style k: rather than assuming a delay bound up front, take the
+// smallest k that a `coverage` share of recent blocks fit under, judging each
+// block by how many concurrent blocks it merged. Delay spikes push k up; calm
+// stretches let it settle back down.
+#[derive(Debug, Clone)]
+pub struct AdaptiveK {
+    pub window: usize, // Recent blocks considered
+    pub coverage: f64, // Share of them that must fit under k
+    pub min_k: usize,
+    pub max_k: usize,
+    samples: VecDeque<usize>,
+}
+
+impl AdaptiveK {
+    pub fn new(window: usize, coverage: f64) -> Self {
+        AdaptiveK {
+            window,
+            coverage,
+            min_k: 1,
+            max_k: 1000,
+            samples: VecDeque::with_capacity(window),
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        if self.samples.is_empty() {
+            return self.min_k;
+        }
+        let mut sorted: Vec<usize> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let covered = ((self.coverage * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+        sorted[covered - 1].clamp(self.min_k, self.max_k)
+    }
+
+    pub fn observe(&mut self, mergeset: usize) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(mergeset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, ToyDag};
+
+    #[test]
+    fn k_follows_the_covered_share_of_the_window() {
+        let mut adaptive = AdaptiveK::new(4, 0.75);
+        assert_eq!(adaptive.k(), 1); // Nothing seen yet: min_k
+        for mergeset in [0, 2, 5, 3] {
+            adaptive.observe(mergeset);
+        }
+        assert_eq!(adaptive.k(), 3); // Three of the four fit under 3
+        adaptive.observe(9); // Pushes the 0 out
+        assert_eq!(adaptive.k(), 5);
+        adaptive.max_k = 4;
+        assert_eq!(adaptive.k(), 4);
+    }
+
+    // Four siblings merged by one block, then three siblings on top of it.
+    // The merge teaches adaptive k that three concurrent blocks are normal,
+    // so the third sibling on top stays blue; fixed k = 1 colors it red.
+    #[test]
+    fn adaptive_k_colors_what_fixed_k_would_not() {
+        let third_on_top = |k_mode: KMode| {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag.k_mode = k_mode;
+            let below: Vec<u64> = (0..4).map(|_| dag.create_block(vec![0]).unwrap()).collect();
+            let merge = dag.create_block(below).unwrap();
+            let above: Vec<u64> = (0..3).map(|_| dag.create_block(vec![merge]).unwrap()).collect();
+            (dag.k_mode.k(), dag[above[2]].color())
+        };
+        assert_eq!(third_on_top(KMode::Fixed(1)), (1, Color::Red));
+        assert_eq!(third_on_top(KMode::Adaptive(AdaptiveK::new(10, 1.0))), (3, Color::Blue));
+    }
+}
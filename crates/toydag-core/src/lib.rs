@@ -0,0 +1,1040 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::ops::Index;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+pub mod audit;
+mod chain;
+pub mod coinbase;
+pub mod compact;
+pub mod cones;
+pub mod consensus;
+pub mod daa;
+pub mod diff;
+pub mod error;
+pub mod events;
+pub mod fixture;
+pub mod forensics;
+mod ghostdag;
+pub mod ingest;
+pub mod integrity;
+pub mod knight;
+pub mod log;
+pub mod mempool;
+pub mod merge_depth;
+pub mod metrics;
+mod par;
+pub mod receipts;
+pub mod rescue;
+pub mod score;
+mod selection;
+pub mod slice;
+pub mod softfork;
+pub mod stats;
+pub mod step;
+pub mod stitch;
+pub mod store;
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+use chain::ChainIndex;
+use daa::Daa;
+use error::DagError;
+use events::{DagEvent, FinalityViolation, SharedObserver};
+use ghostdag::Ghostdag;
+use knight::KMode;
+use log::Level;
+use merge_depth::{MergeCheck, MergeDepth};
+use metrics::BlockMetrics;
+use rescue::RedTracker;
+use score::{BlueWork, count_score, depth_between, sum_work};
+use softfork::{Deployment, Phase, SoftFork};
+use stats::Stats;
+use step::SharedStepper;
+use stitch::{MergeAll, StitchMode, StitchPolicy};
+
+pub const K: usize = 15; // GHOSTDAG k-parameter (Kaspa uses ~15)
+pub const STITCH_THRESHOLD: usize = 10; // When StitchBot activates
+pub const FINALITY_DEPTH: u64 = 50; // Blue-score depth at which chain blocks are final
+pub const MERGESET_LIMIT: usize = 10 * K; // Notional cap on the virtual's mergeset
+pub const BLOCK_VERSION: u32 = 1; // Rule set a block follows unless its miner has upgraded
+pub const STALE_WINDOW: u64 = 6; // Recent blocks the fork-heavy workloads draw parents from, so tips fork
+
+#[derive(Debug, Clone)]
+pub struct Block {
+    id: u64,
+    parents: Vec<u64>,
+    color: Color, // Blue or Red relative to virtual
+    blue_score: u64, // Blue blocks in past (excluding self)
+    work: BlueWork, // Work this block contributes when blue
+    blue_work: BlueWork, // Total work of blue blocks in past (excluding self)
+    past_size: u64, // Blocks in past (excluding self)
+    topo_depth: usize, // Longest parent path down to genesis
+    selected_parent: Option<u64>, // Parent with highest blue score (None for genesis)
+    txs: Vec<u64>, // Toy transaction ids carried by this block
+    miner: Option<u32>, // Who mined it, when known
+    timestamp: u64, // Claimed mining time; insertion order unless the caller says otherwise
+    version: u32, // Rule set its miner follows; soft forks raise it
+}
+
+impl Block {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn parents(&self) -> &[u64] {
+        &self.parents
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn blue_score(&self) -> u64 {
+        self.blue_score
+    }
+
+    // Score the difficulty adjustment counts blocks by: blue blocks in past.
+    // In this toy that is exactly the blue score, carried forward from the
+    // selected parent as each block arrives.
+    pub fn daa_score(&self) -> u64 {
+        self.blue_score
+    }
+
+    pub fn blue_work(&self) -> BlueWork {
+        self.blue_work
+    }
+
+    pub fn past_size(&self) -> u64 {
+        self.past_size
+    }
+
+    pub fn topo_depth(&self) -> usize {
+        self.topo_depth
+    }
+
+    pub fn selected_parent(&self) -> Option<u64> {
+        self.selected_parent
+    }
+
+    pub fn txs(&self) -> &[u64] {
+        &self.txs
+    }
+
+    pub fn miner(&self) -> Option<u32> {
+        self.miner
+    }
+
+    // Difficulty the block was mined at, i.e. the work it adds when blue
+    pub fn work(&self) -> BlueWork {
+        self.work
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+// GHOSTDAG scores a block would get from a given parent set
+pub(crate) struct ParentScores {
+    selected_parent: u64,
+    mergeset: Vec<u64>, // Merged blocks besides the selected parent
+    ghostdag: Ghostdag, // How the block colors its mergeset
+    blue_score: u64,
+    blue_work: BlueWork,
+    past_size: u64,
+}
+
+// Everything about a new block that depends only on its parents, so blocks
+// whose parents are all in place can be prepared independently
+struct Prepared {
+    scores: ParentScores,
+    work: BlueWork,
+    topo_depth: usize,
+    merge_check: MergeCheck,
+}
+
+// A block to insert, as handed to `insert_batch`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewBlock {
+    pub id: u64,
+    pub parents: Vec<u64>,
+    pub txs: Vec<u64>,
+    pub miner: Option<u32>,
+    pub timestamp: u64,
+    pub version: u32,
+}
+
+// The block a DAG starts from. DAGs built from different genesis blocks
+// share nothing, so several can run side by side for A/B comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Genesis {
+    pub id: u64,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default = "unit_work")]
+    pub difficulty: BlueWork, // Work of the genesis block, and of every block after it until a DAA says otherwise
+}
+
+impl Default for Genesis {
+    fn default() -> Self {
+        Genesis { id: 0, timestamp: 0, difficulty: 1 }
+    }
+}
+
+fn unit_work() -> BlueWork {
+    1
+}
+
+// `id[:timestamp[:difficulty]]`, e.g. `1000:0:4`
+impl FromStr for Genesis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = s.split(':').map(str::trim).collect();
+        if fields.len() > 3 {
+            return Err(format!("genesis '{}' has more than id:timestamp:difficulty", s));
+        }
+        let bad = |name: &str| format!("bad genesis {} in '{}'", name, s);
+        let mut genesis = Genesis { id: fields[0].parse().map_err(|_| bad("id"))?, ..Genesis::default() };
+        if let Some(t) = fields.get(1) {
+            genesis.timestamp = t.parse().map_err(|_| bad("timestamp"))?;
+        }
+        if let Some(d) = fields.get(2) {
+            genesis.difficulty = d.parse().map_err(|_| bad("difficulty"))?;
+        }
+        genesis.check().map_err(|e| e.to_string())?;
+        Ok(genesis)
+    }
+}
+
+impl Genesis {
+    // Room for at least one child (a block at u64::MAX would leave no next id),
+    // and some work to start from
+    pub fn check(&self) -> Result<(), DagError> {
+        if self.id >= u64::MAX - 1 {
+            return Err(DagError::IdSpaceExhausted(self.id));
+        }
+        if self.difficulty == 0 {
+            return Err(DagError::ZeroDifficulty(self.id));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Blue,
+    Red,
+}
+
+#[derive(Clone)]
+pub struct ToyDag {
+    blocks: HashMap<u64, Block>,
+    children: HashMap<u64, Vec<u64>>,
+    tips: HashSet<u64>,
+    genesis: u64,
+    next_id: u64,
+    selected_parent: u64, // Current virtual selected tip
+    pub stats: Stats,
+    tip_since: HashMap<u64, u64>, // Insertion tick at which each tip appeared
+    pub stitch_mode: StitchMode,
+    pub stitch_policy: Box<dyn StitchPolicy>, // Which tips a stitch merges
+    pub k_mode: KMode, // Fixed K, or adapted to observed concurrency
+    pub verbose: bool, // StitchBot narrates what it does
+    pub narrator: fn(&log::Record), // Where that narration goes; the log facade unless replaced
+    observers: Vec<SharedObserver>,
+    pub stepper: Option<SharedStepper>, // When set, every `create_block` is traced and handed to it
+    finality_point: u64, // Highest finalized selected-chain block
+    reds: RedTracker,
+    chain_index: ChainIndex, // Skip pointers for chain lookups by blue score
+    pub block_work: BlueWork, // Work credited to each new block
+    pub daa: Option<Daa>, // When set, overrides `block_work` with an adjusted difficulty
+    pub merge_depth: Option<MergeDepth>, // When set, blocks merging too deep are rejected
+    pub mergeset_limit: Option<usize>, // When set, blocks merging more than this many (besides the selected parent) are rejected
+    pub max_parents: Option<usize>, // When set, blocks with more parents than this are rejected
+    pub track_anticones: bool, // Keep every block's running anticone size in `stats`
+    pub soft_fork: Option<SoftFork>, // Set before inserting blocks; they are tallied as they arrive
+    deployments: HashMap<u64, Deployment>, // Soft-fork tally as of each block
+    ghostdag: HashMap<u64, Ghostdag>, // Each block's coloring of its own mergeset
+    virtual_view: Ghostdag, // The virtual's coloring of its mergeset, which sets the colors above the chain
+}
+
+impl Default for ToyDag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// `dag[id]` for ids the caller got from the DAG itself; panics on unknown ids
+// like indexing a map. `block` and `get_block` are the checked lookups.
+impl Index<u64> for ToyDag {
+    type Output = Block;
+
+    fn index(&self, id: u64) -> &Block {
+        &self.blocks[&id]
+    }
+}
+
+impl ToyDag {
+    pub fn new() -> Self {
+        Self::with_genesis(Genesis::default()).expect("the default genesis is valid")
+    }
+
+    pub fn with_genesis(spec: Genesis) -> Result<Self, DagError> {
+        spec.check()?;
+        let genesis = Block {
+            id: spec.id,
+            parents: vec![],
+            color: Color::Blue,
+            blue_score: 0,
+            work: spec.difficulty,
+            blue_work: 0,
+            past_size: 0,
+            topo_depth: 0,
+            selected_parent: None,
+            txs: vec![],
+            miner: None,
+            timestamp: spec.timestamp,
+            version: BLOCK_VERSION,
+        };
+        let mut blocks = HashMap::new();
+        blocks.insert(spec.id, genesis);
+        let mut chain_index = ChainIndex::default();
+        chain_index.insert(spec.id, None);
+
+        Ok(ToyDag {
+            blocks,
+            children: HashMap::new(),
+            tips: HashSet::from([spec.id]),
+            genesis: spec.id,
+            next_id: spec.id + 1,
+            selected_parent: spec.id,
+            stats: Stats::default(),
+            tip_since: HashMap::from([(spec.id, 0)]),
+            stitch_mode: StitchMode::Fixed(STITCH_THRESHOLD),
+            stitch_policy: Box::new(MergeAll),
+            k_mode: KMode::Fixed(K),
+            verbose: true,
+            narrator: log::emit,
+            observers: Vec::new(),
+            stepper: None,
+            finality_point: spec.id,
+            reds: RedTracker::default(),
+            chain_index,
+            block_work: spec.difficulty,
+            daa: None,
+            merge_depth: None,
+            mergeset_limit: None,
+            max_parents: None,
+            track_anticones: false,
+            soft_fork: None,
+            deployments: HashMap::new(),
+            ghostdag: HashMap::from([(spec.id, Ghostdag::default())]),
+            virtual_view: Ghostdag::new(spec.id, K),
+        })
+    }
+
+    pub fn selected_parent(&self) -> u64 {
+        self.selected_parent
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn block(&self, id: u64) -> Option<&Block> {
+        self.blocks.get(&id)
+    }
+
+    pub fn get_block(&self, id: u64) -> Result<&Block, DagError> {
+        self.blocks.get(&id).ok_or(DagError::UnknownBlock(id))
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.blocks.contains_key(&id)
+    }
+
+    // All blocks, in no particular order
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.values()
+    }
+
+    pub fn tips(&self) -> impl Iterator<Item = u64> + '_ {
+        self.tips.iter().copied()
+    }
+
+    pub fn tip_count(&self) -> usize {
+        self.tips.len()
+    }
+
+    pub fn blue_tips(&self) -> impl Iterator<Item = u64> + '_ {
+        self.tips().filter(|t| self.blocks[t].color == Color::Blue)
+    }
+
+    // Parents of the virtual block: its selected parent first, then the other
+    // tips by id
+    pub fn virtual_parents(&self) -> Vec<u64> {
+        let mut others: Vec<u64> = self.tips().filter(|&t| t != self.selected_parent).collect();
+        others.sort_unstable();
+        let mut parents = vec![self.selected_parent];
+        parents.extend(others);
+        parents
+    }
+
+    // Highest finalized selected-chain block; everything in its past is final too
+    pub fn finality_point(&self) -> u64 {
+        self.finality_point
+    }
+
+    pub fn genesis(&self) -> u64 {
+        self.genesis
+    }
+
+    // Id the next locally created block will get
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    // Core GHOSTDAG: compute anticone size relative to selected parent
+    pub fn anticone_size(&self, block_id: u64, reference_id: u64) -> Result<usize, DagError> {
+        self.get_block(block_id)?;
+        self.get_block(reference_id)?;
+        Ok(self.anticone_count(block_id, reference_id))
+    }
+
+    // Meant for blocks already in the DAG; one about to be inserted has no
+    // future to tell its anticone from the reference's
+    fn anticone_count(&self, block_id: u64, reference_id: u64) -> usize {
+        // Simplified reachability: count blocks reachable from block but not from reference
+        let reachable_from_block = self.future_cone(block_id);
+        let reachable_from_ref = self.future_cone(reference_id);
+        reachable_from_block
+            .difference(&reachable_from_ref)
+            .count()
+            - 1 // subtract self
+    }
+
+    // Future cone: all blocks that have this as ancestor (including self)
+    pub fn future_set(&self, block_id: u64) -> Result<HashSet<u64>, DagError> {
+        self.get_block(block_id)?;
+        Ok(self.future_cone(block_id))
+    }
+
+    // Past cone: all ancestors (including self)
+    pub fn past_set(&self, block_id: u64) -> Result<HashSet<u64>, DagError> {
+        self.get_block(block_id)?;
+        Ok(self.past_cone(block_id))
+    }
+
+    // Unchecked versions for ids the DAG already vouches for
+    pub(crate) fn future_cone(&self, block_id: u64) -> HashSet<u64> {
+        let mut future = HashSet::new();
+        let mut queue = vec![block_id];
+        future.insert(block_id);
+
+        while let Some(current) = queue.pop() {
+            for &child_id in self.children.get(&current).into_iter().flatten() {
+                if future.insert(child_id) {
+                    queue.push(child_id);
+                }
+            }
+        }
+        future
+    }
+
+    pub(crate) fn past_cone(&self, block_id: u64) -> HashSet<u64> {
+        let mut past = HashSet::new();
+        let mut queue = vec![block_id];
+        past.insert(block_id);
+
+        while let Some(current) = queue.pop() {
+            for &parent in &self.blocks[&current].parents {
+                if past.insert(parent) {
+                    queue.push(parent);
+                }
+            }
+        }
+        past
+    }
+
+    // Whether `ancestor` is in the past of `block` (or is `block`)
+    pub fn is_ancestor(&self, ancestor: u64, block: u64) -> Result<bool, DagError> {
+        self.get_block(ancestor)?;
+        self.get_block(block)?;
+        Ok(self.in_past(ancestor, block))
+    }
+
+    // Unchecked `is_ancestor`. Only walks the layers between the two, since
+    // depth strictly drops along parent edges.
+    pub(crate) fn in_past(&self, ancestor: u64, block: u64) -> bool {
+        let floor = self.blocks[&ancestor].topo_depth;
+        let mut seen = HashSet::from([block]);
+        let mut queue = vec![block];
+
+        while let Some(current) = queue.pop() {
+            if current == ancestor {
+                return true;
+            }
+            for &parent in &self.blocks[&current].parents {
+                if self.blocks[&parent].topo_depth >= floor && seen.insert(parent) {
+                    queue.push(parent);
+                }
+            }
+        }
+        false
+    }
+
+    // past(block) minus past(selected_parent) minus the selected parent itself
+    fn mergeset_without_selected(&self, selected_parent: u64, parents: &[u64]) -> Vec<u64> {
+        self.outside_past(&[selected_parent], parents.iter().copied().filter(|&p| p != selected_parent))
+    }
+
+    // Blocks reachable from `from` (included) that are in the past of none of
+    // `roots` (roots included), found by walking back in depth order. Ancestors
+    // of the roots are discovered lazily, only as deep as the walk goes.
+    fn outside_past(&self, roots: &[u64], from: impl IntoIterator<Item = u64>) -> Vec<u64> {
+        let depth = |id: u64| self.blocks[&id].topo_depth;
+
+        let mut candidates: BinaryHeap<(usize, u64)> = from.into_iter().map(|id| (depth(id), id)).collect();
+        let mut seen = HashSet::new();
+        let mut roots_past: HashSet<u64> = roots.iter().copied().collect();
+        let mut roots_frontier: BinaryHeap<(usize, u64)> = roots.iter().map(|&r| (depth(r), r)).collect();
+        let mut outside = Vec::new();
+
+        while let Some((d, id)) = candidates.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            while roots_frontier.peek().is_some_and(|&(fd, _)| fd >= d) {
+                let (_, f) = roots_frontier.pop().unwrap();
+                for &p in &self.blocks[&f].parents {
+                    if roots_past.insert(p) {
+                        roots_frontier.push((depth(p), p));
+                    }
+                }
+            }
+            if roots_past.contains(&id) {
+                continue;
+            }
+            outside.push(id);
+            for &p in &self.blocks[&id].parents {
+                if !seen.contains(&p) {
+                    candidates.push((depth(p), p));
+                }
+            }
+        }
+        outside
+    }
+
+    // Scores build on the selected parent's: past = past(sp) + sp + mergeset,
+    // so only the mergeset has to be walked instead of the whole past
+    pub(crate) fn parent_scores(&self, parent_ids: &[u64]) -> ParentScores {
+        // Selected parent: highest blue score, lowest id on ties
+        let sp_id = parent_ids
+            .iter()
+            .copied()
+            .max_by_key(|&p| (self.blocks[&p].blue_score, std::cmp::Reverse(p)))
+            .expect("non-genesis block has a parent");
+        let sp = &self.blocks[&sp_id];
+        let mergeset = self.mergeset_without_selected(sp_id, parent_ids);
+        let ghostdag = self.ghostdag(sp_id, &mergeset, self.k_mode.k());
+        let merged_blues = ghostdag.mergeset_blues.iter().map(|b| self.blocks[b].work);
+        ParentScores {
+            selected_parent: sp_id,
+            blue_score: sp.blue_score.saturating_add(count_score(ghostdag.mergeset_blues.len())),
+            blue_work: sum_work(std::iter::once(sp.blue_work).chain(merged_blues)),
+            past_size: sp.past_size.saturating_add(1 + count_score(mergeset.len())),
+            mergeset,
+            ghostdag,
+        }
+    }
+
+    // Difficulty for a block building on `selected_parent`
+    fn next_work(&self, selected_parent: u64) -> BlueWork {
+        match &self.daa {
+            Some(daa) => daa.next_work(self, selected_parent),
+            None => self.block_work,
+        }
+    }
+
+    pub fn create_block(&mut self, parent_ids: Vec<u64>) -> Result<u64, DagError> {
+        self.create_block_with_txs(parent_ids, vec![])
+    }
+
+    pub fn create_block_with_txs(&mut self, parent_ids: Vec<u64>, txs: Vec<u64>) -> Result<u64, DagError> {
+        let id = self.next_id;
+        let timestamp = self.blocks[&self.genesis].timestamp + self.blocks.len() as u64; // insertion order doubles as time
+        // Traced only when it would get past the parent checks that make tracing safe
+        let pending = match &self.stepper {
+            Some(_) if !parent_ids.is_empty() && parent_ids.iter().all(|p| self.blocks.contains_key(p)) => {
+                Some((self.pending_step(&parent_ids), parent_ids.clone()))
+            }
+            _ => None,
+        };
+        self.add_block(NewBlock { id, parents: parent_ids, txs, miner: None, timestamp, version: BLOCK_VERSION })?;
+        if let Some((pending, parents)) = pending {
+            self.finish_step(pending, id, parents);
+        }
+        Ok(id)
+    }
+
+    // Create a block stamped with a simulated mining time
+    pub fn create_block_at(&mut self, parent_ids: Vec<u64>, timestamp: u64) -> Result<u64, DagError> {
+        let id = self.next_id;
+        self.add_block(NewBlock { id, parents: parent_ids, txs: vec![], miner: None, timestamp, version: BLOCK_VERSION })?;
+        Ok(id)
+    }
+
+    // Insert a block, or say why not
+    pub fn add_block(&mut self, block: NewBlock) -> Result<(), DagError> {
+        self.validate(&block)?;
+        let prepared = self.prepare(&block.parents);
+        self.commit(block, prepared)
+    }
+
+    // Insert a block under a caller-chosen id (e.g. delivered by the network).
+    // Idempotent: re-delivering a known id is a no-op and returns false, as does
+    // anything `add_block` would refuse.
+    pub fn insert_block(&mut self, id: u64, parent_ids: Vec<u64>, txs: Vec<u64>, miner: Option<u32>) -> bool {
+        let tick = self.blocks.len() as u64; // insertion order doubles as time
+        self.insert_block_at(id, parent_ids, txs, miner, tick)
+    }
+
+    pub fn insert_block_at(&mut self, id: u64, parent_ids: Vec<u64>, txs: Vec<u64>, miner: Option<u32>, timestamp: u64) -> bool {
+        self.add_block(NewBlock { id, parents: parent_ids, txs, miner, timestamp, version: BLOCK_VERSION }).is_ok()
+    }
+
+    // Checks that need nothing but the block map
+    fn validate(&self, block: &NewBlock) -> Result<(), DagError> {
+        if self.blocks.contains_key(&block.id) {
+            return Err(DagError::DuplicateBlock(block.id));
+        }
+        self.check_parent_list(block)?;
+        match block.parents.iter().find(|p| !self.blocks.contains_key(p)) {
+            Some(&parent) => Err(DagError::UnknownParent { block: block.id, parent }),
+            None => Ok(()),
+        }
+    }
+
+    // Checks on the parent list alone: some parents, within the limit, none twice
+    fn check_parent_list(&self, block: &NewBlock) -> Result<(), DagError> {
+        if block.parents.is_empty() {
+            return Err(DagError::NoParents(block.id));
+        }
+        if let Some(limit) = self.max_parents
+            && block.parents.len() > limit
+        {
+            return Err(DagError::TooManyParents { block: block.id, count: block.parents.len(), limit });
+        }
+        match block.parents.iter().enumerate().find_map(|(i, p)| block.parents[..i].contains(p).then_some(p)) {
+            Some(&parent) => Err(DagError::DuplicateParent { block: block.id, parent }),
+            None => Ok(()),
+        }
+    }
+
+    // Insert blocks given parents-first. Runs of blocks that don't build on
+    // each other are prepared together (in parallel with the `parallel`
+    // feature) and then committed one by one in the given order, which ends
+    // up exactly where inserting them one at a time would. Adaptive k moves
+    // with every commit and preparing colors with the k of the moment, so
+    // then each block is a run of its own. Returns how many were inserted.
+    pub fn insert_batch(&mut self, blocks: Vec<NewBlock>) -> usize {
+        let adaptive = matches!(self.k_mode, KMode::Adaptive(_));
+        let mut inserted = 0;
+        let mut layer: Vec<NewBlock> = Vec::new();
+        let mut in_layer = HashSet::new();
+        for block in blocks {
+            if adaptive || block.parents.iter().any(|p| in_layer.contains(p)) {
+                inserted += self.insert_layer(std::mem::take(&mut layer));
+                in_layer.clear();
+            }
+            in_layer.insert(block.id);
+            layer.push(block);
+        }
+        inserted + self.insert_layer(layer)
+    }
+
+    fn insert_layer(&mut self, layer: Vec<NewBlock>) -> usize {
+        let layer: Vec<NewBlock> = layer.into_iter().filter(|b| self.validate(b).is_ok()).collect();
+        let prepared = par::map(&layer, |b| self.prepare(&b.parents));
+        let mut inserted = 0;
+        for (block, prepared) in layer.into_iter().zip(prepared) {
+            inserted += self.commit(block, prepared).is_ok() as usize;
+        }
+        inserted
+    }
+
+    // Parents must be known and non-empty; see `validate`
+    fn prepare(&self, parent_ids: &[u64]) -> Prepared {
+        let scores = self.parent_scores(parent_ids);
+        let work = self.next_work(scores.selected_parent);
+        let topo_depth = 1 + parent_ids.iter().map(|p| self.blocks[p].topo_depth).max().unwrap_or(0);
+        let merge_check = match &self.merge_depth {
+            Some(rule) => rule.check(self, scores.selected_parent, &scores.mergeset, &scores.ghostdag.mergeset_blues),
+            None => MergeCheck::default(),
+        };
+        Prepared { scores, work, topo_depth, merge_check }
+    }
+
+    fn commit(&mut self, new: NewBlock, prepared: Prepared) -> Result<(), DagError> {
+        let NewBlock { id, parents: parent_ids, txs, miner, timestamp, version } = new;
+        if self.blocks.contains_key(&id) {
+            return Err(DagError::DuplicateBlock(id));
+        }
+        let after = id.checked_add(1).ok_or(DagError::IdSpaceExhausted(id))?;
+        self.next_id = self.next_id.max(after);
+        let tick = self.blocks.len() as u64;
+
+        let k = self.k_mode.k();
+        let Prepared { scores, work, topo_depth, merge_check } = prepared;
+        let ParentScores { selected_parent, mergeset, ghostdag, blue_score, blue_work, past_size } = scores;
+
+        if merge_check.violating > 0 {
+            self.stats.record_merge_depth_violation();
+            return Err(DagError::MergeDepthViolation(id));
+        }
+        if let Some(limit) = self.mergeset_limit
+            && mergeset.len() > limit
+        {
+            self.stats.record_oversized_mergeset();
+            return Err(DagError::MergesetTooLarge { block: id, size: mergeset.len(), limit });
+        }
+        if let Some(daa) = &self.daa
+            && daa.median_window > 0
+        {
+            let median = daa.past_median_time(self, selected_parent);
+            if timestamp <= median {
+                self.stats.record_stale_timestamp();
+                return Err(DagError::StaleTimestamp { block: id, timestamp, median });
+            }
+        }
+        let deployment = self.deployment_below(selected_parent, &ghostdag.mergeset_blues);
+        if let (Some(fork), Some(below)) = (&self.soft_fork, deployment)
+            && below.phase == Phase::Active
+            && version < fork.version
+        {
+            self.stats.record_outdated_version();
+            return Err(DagError::OutdatedVersion { block: id, version, required: fork.version });
+        }
+        if merge_check.kosherized > 0 {
+            self.stats.record_kosherized(merge_check.kosherized);
+        }
+        self.k_mode.observe(mergeset.len()); // Concurrency this block saw, for adaptive k
+        // Every block outside its own past, since nothing has it in its past yet
+        let anticone = if self.track_anticones { self.outside_past(&parent_ids, self.tips.iter().copied()) } else { Vec::new() };
+
+        let block = Block {
+            id,
+            parents: parent_ids.clone(),
+            color: Color::Blue, // Until the virtual colors it below
+            blue_score,
+            work,
+            blue_work,
+            past_size,
+            topo_depth,
+            selected_parent: Some(selected_parent),
+            txs,
+            miner,
+            timestamp,
+            version,
+        };
+
+        self.blocks.insert(id, block);
+        self.ghostdag.insert(id, ghostdag);
+        if let Some(below) = deployment {
+            self.record_deployment(id, below, version);
+        }
+        self.chain_index.insert(id, Some(selected_parent));
+        for &pid in &parent_ids {
+            self.children.entry(pid).or_default().push(id);
+        }
+
+        // Merge latency: how long each referenced tip waited
+        for &pid in &parent_ids {
+            if let Some(since) = self.tip_since.remove(&pid) {
+                let latency = (tick - since) as usize;
+                let headroom = MERGESET_LIMIT.saturating_sub(self.tips.len());
+                self.stats.record_merge(latency);
+                self.stitch_mode.observe(latency, headroom);
+            }
+        }
+
+        // Update tips: a tip is a block with no children yet
+        self.tips.retain(|t| !self.children.contains_key(t));
+        self.tips.insert(id);
+        self.tip_since.insert(id, tick);
+        if self.track_anticones {
+            self.stats.record_anticone(id, &anticone);
+        }
+
+        // Color by the k-cluster rule, from the virtual: the new block is its
+        // selected parent, with the blues of the virtual's mergeset in its
+        // anticone, or one of that mergeset, counted as GHOSTDAG went
+        let best = self.virtual_selected_tip().expect("the new block is a tip");
+        self.recolor(best, id, tick);
+        let blue_anticone = self.virtual_blue_anticone(id).expect("the new block is in the virtual's mergeset");
+        let color = self.blocks[&id].color;
+        if color == Color::Blue {
+            self.rescue_reds(id, tick);
+        }
+
+        self.update_selected_parent(best);
+
+        self.stats.record_block(self.tips.len(), blue_anticone);
+        self.stats.record_metrics(BlockMetrics {
+            block: id,
+            parents: parent_ids.len() as u64,
+            blue: color == Color::Blue,
+            blue_score,
+            anticone: blue_anticone as u64,
+            k: k as u64,
+            tips: self.tips.len() as u64,
+            virtual_selected_parent: self.selected_parent,
+            red_rate: 0.0, // filled in by Stats
+            reorgs: 0,
+            stitch_activations: 0,
+        });
+
+        self.emit(DagEvent::BlockAdded { id });
+        Ok(())
+    }
+
+
+    fn update_selected_parent(&mut self, best: u64) {
+        let mut violation = None;
+        if best != self.selected_parent && !self.in_past(self.selected_parent, best) {
+            let depth = self.reorg_depth(self.selected_parent, best);
+            self.stats.record_reorg(depth);
+            if !self.in_past(self.finality_point, best) {
+                self.stats.record_finality_violation();
+                violation = Some(FinalityViolation {
+                    finality_point: self.finality_point,
+                    from: self.selected_parent,
+                    to: best,
+                    reorg_depth: depth,
+                });
+                self.finality_point = self.chain_block_at_or_below(best, self.blocks[&self.finality_point].blue_score);
+            }
+        }
+        let from = self.selected_parent;
+        self.selected_parent = best;
+        if from != best {
+            self.emit(DagEvent::SelectedParentChanged { from, to: best });
+            if let Some(violation) = violation {
+                self.emit(DagEvent::FinalityViolation(violation));
+            }
+            self.advance_finality();
+        }
+    }
+
+    // Heaviest = largest past, lowest id on ties; reads the stored past sizes
+    pub fn heaviest_blue_tip(&self) -> Option<u64> {
+        self.blue_tips().max_by_key(|&t| (self.blocks[&t].past_size, std::cmp::Reverse(t)))
+    }
+
+    // Where the virtual goes: the tip it would pick as selected parent, the
+    // way every block picks one, by blue score and then lowest id
+    fn virtual_selected_tip(&self) -> Option<u64> {
+        self.tips().max_by_key(|&t| (self.blocks[&t].blue_score, std::cmp::Reverse(t)))
+    }
+
+    // The pre-incremental rule, recomputing every blue tip's past from scratch.
+    // Kept as a reference for benchmarks and consistency checks.
+    pub fn heaviest_blue_tip_full(&self) -> Option<u64> {
+        let tips: Vec<u64> = self.blue_tips().collect();
+        let past_sizes = par::map(&tips, |&t| self.past_cone(t).len());
+        tips.into_iter()
+            .zip(past_sizes)
+            .max_by_key(|&(t, size)| (size, std::cmp::Reverse(t)))
+            .map(|(t, _)| t)
+    }
+
+    // Finalize chain blocks that are now FINALITY_DEPTH blue score below the virtual
+    fn advance_finality(&mut self) {
+        let tip_score = self.blocks[&self.selected_parent].blue_score;
+        let finalized_score = self.blocks[&self.finality_point].blue_score;
+
+        let mut newly_final = Vec::new();
+        let mut current = Some(self.selected_parent);
+        while let Some(id) = current {
+            let score = self.blocks[&id].blue_score;
+            if score <= finalized_score {
+                break;
+            }
+            if depth_between(tip_score, score) >= FINALITY_DEPTH {
+                newly_final.push(id);
+            }
+            current = self.blocks[&id].selected_parent;
+        }
+
+        for id in newly_final.into_iter().rev() {
+            self.finality_point = id;
+            self.emit(DagEvent::Finalized { id });
+        }
+        self.orphan_reds();
+    }
+
+    // Chain blocks of `old_tip` not on the selected chain of `new_tip`. Walks
+    // both chains down in step (deeper side first) until they meet.
+    fn reorg_depth(&self, old_tip: u64, new_tip: u64) -> usize {
+        let step = |id: u64| self.blocks[&id].selected_parent.unwrap_or(id);
+        let (mut old, mut new) = (old_tip, new_tip);
+        let mut depth = 0;
+
+        while old != new {
+            if self.blocks[&old].topo_depth >= self.blocks[&new].topo_depth {
+                old = step(old);
+                depth += 1;
+            } else {
+                new = step(new);
+            }
+        }
+        depth
+    }
+
+    // Chain of selected parents from genesis up to the virtual's selected parent
+    pub fn selected_chain(&self) -> Vec<u64> {
+        let mut chain = vec![self.selected_parent];
+        while let Some(parent) = self.blocks[chain.last().unwrap()].selected_parent {
+            chain.push(parent);
+        }
+        chain.reverse();
+        chain
+    }
+
+    // Total order: walk the selected chain from genesis; each chain block
+    // contributes its mergeset (topologically, ties by id) and then itself.
+    // The virtual's own mergeset — everything under the other tips — goes last.
+    pub fn ordered_blocks(&self) -> Vec<u64> {
+        let by_topology = |a: &u64, b: &u64| (self.blocks[a].topo_depth, *a).cmp(&(self.blocks[b].topo_depth, *b));
+
+        // Each chain block's mergeset is independent of the others
+        let chain = self.selected_chain();
+        let mergesets = par::map(&chain, |&id| {
+            let block = &self.blocks[&id];
+            let mut mergeset = match block.selected_parent {
+                Some(sp) => self.mergeset_without_selected(sp, &block.parents),
+                None => Vec::new(),
+            };
+            mergeset.sort_by(by_topology);
+            mergeset
+        });
+
+        let mut order = Vec::with_capacity(self.blocks.len());
+        for (id, mergeset) in chain.into_iter().zip(mergesets) {
+            order.extend(mergeset);
+            order.push(id);
+        }
+
+        let mut mergeset = self.mergeset_without_selected(self.selected_parent, &self.virtual_parents());
+        mergeset.sort_by(by_topology);
+        order.extend(mergeset);
+        order
+    }
+
+    // StitchBot: when too fractured, merge whichever tips the stitch policy picks
+    pub fn stitch_if_needed(&mut self) {
+        if self.tips.len() <= self.stitch_mode.threshold() {
+            return;
+        }
+        let mut tips: Vec<u64> = self.tips.iter().copied().collect();
+        tips.sort_unstable();
+
+        // The policy reads the DAG, so take it out while it decides
+        let mut policy = std::mem::replace(&mut self.stitch_policy, Box::new(MergeAll));
+        let selected = policy.select(self, &tips);
+        self.stitch_policy = policy;
+        let Some(selected) = selected.filter(|s| s.len() > 1) else {
+            return;
+        };
+
+        self.narrate(
+            Level::Info,
+            "🦸",
+            &format!("StitchBot ACTIVATED! Tips: {} → merging {} ({})", tips.len(), selected.len(), self.stitch_policy.name()),
+        );
+        self.narrate(Level::Debug, "", &format!("merging tips {:?}", selected));
+        self.stats.record_stitch();
+
+        // Under a mergeset limit the tips may not fit in one block: merge as
+        // many as fit, then have the next merge block build on that one
+        let mut pending = selected;
+        if self.mergeset_limit.is_some() || self.max_parents.is_some() {
+            pending.sort_by_key(|&t| (std::cmp::Reverse(self.blocks[&t].past_size), t));
+        }
+        while pending.len() > 1 {
+            let (parents, rest) = self.bounded_merge(&pending);
+            if parents.len() < 2 {
+                self.narrate(Level::Warn, "⛔", &format!("{} tips left unmerged: no two fit under the mergeset limit", pending.len()));
+                return;
+            }
+
+            let merge_block_id = match self.create_block(parents.clone()) {
+                Ok(id) => id,
+                Err(e) => {
+                    self.narrate(Level::Warn, "⛔", &format!("Merge block rejected: {}", e));
+                    return;
+                }
+            };
+            self.emit(DagEvent::StitchActivated { merge_block: merge_block_id, tips: parents.len() });
+            self.narrate(Level::Info, "🪡", &format!("Created merge block {} referencing {} tips", merge_block_id, parents.len()));
+
+            pending = std::iter::once(merge_block_id).chain(rest).collect();
+        }
+    }
+
+    // Greedily split `candidates` into parents within the parent count and
+    // whose mergeset fits the limit (the first candidate always goes in) and
+    // the ones left over
+    fn bounded_merge(&self, candidates: &[u64]) -> (Vec<u64>, Vec<u64>) {
+        let max_parents = self.max_parents.unwrap_or(usize::MAX);
+        let mut parents = vec![candidates[0]];
+        let mut rest = Vec::new();
+        for &c in &candidates[1..] {
+            if parents.len() >= max_parents {
+                rest.push(c);
+                continue;
+            }
+            parents.push(c);
+            if let Some(limit) = self.mergeset_limit
+                && self.parent_scores(&parents).mergeset.len() > limit
+            {
+                parents.pop();
+                rest.push(c);
+            }
+        }
+        (parents, rest)
+    }
+
+    // Cut a candidate parent set down to `max_parents`, keeping the highest
+    // blue scores (the selected parent comes first). Dropped candidates stay
+    // tips, so a later block still picks them up.
+    pub fn prune_parents(&self, candidates: &[u64]) -> Result<Vec<u64>, DagError> {
+        match candidates.iter().find(|c| !self.blocks.contains_key(c)) {
+            Some(&unknown) => Err(DagError::UnknownBlock(unknown)),
+            None => Ok(self.prune(candidates)),
+        }
+    }
+
+    pub(crate) fn prune(&self, candidates: &[u64]) -> Vec<u64> {
+        let mut parents = candidates.to_vec();
+        parents.sort_by_key(|&p| (std::cmp::Reverse(self.blocks[&p].blue_score), p));
+        if let Some(limit) = self.max_parents {
+            parents.truncate(limit.max(1));
+        }
+        parents
+    }
+
+    fn narrate(&self, level: Level, icon: &str, message: &str) {
+        if self.verbose {
+            (self.narrator)(&log::Record { level, target: "stitch", icon, message, detail: None });
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// Logging facade for everything the crates narrate to a person: StitchBot's
+// running commentary, DAG dumps, end-of-run reports. Library code builds
+// records; the binary decides once, through `init`, how much of that to show,
+// whether to decorate it with emoji and ANSI colors, and whether to also (or
+// only) write it as JSON lines for other tools to read.
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+const YELLOW: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+// Most important first. Reports are the output a command exists to print and
+// are shown even when quiet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Report,
+    Warn,
+    Info,
+    Debug,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    Quiet, // Reports and warnings
+    #[default]
+    Normal, // Plus narration
+    Verbose, // Plus per-block detail
+}
+
+impl Verbosity {
+    fn shows(self, level: Level) -> bool {
+        match self {
+            Verbosity::Quiet => level <= Level::Warn,
+            Verbosity::Normal => level <= Level::Info,
+            Verbosity::Verbose => true,
+        }
+    }
+}
+
+// One thing worth telling. `icon` is kept apart from the message so it can be
+// dropped; `detail` holds multi-line text such as a rendered DAG.
+#[derive(Debug, Clone, Serialize)]
+pub struct Record<'a> {
+    pub level: Level,
+    pub target: &'a str, // What is talking: "stitch", "dag", "run", ...
+    #[serde(skip)]
+    pub icon: &'a str,
+    pub message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<&'a str>,
+}
+
+pub struct Logger {
+    pub verbosity: Verbosity,
+    pub emoji: bool,
+    pub color: Option<bool>, // None: only when stdout is a terminal
+    pub text: bool,          // Human-readable lines on stdout
+    pub json: Option<Box<dyn Write + Send>>,
+}
+
+impl Logger {
+    const DEFAULT: Logger = Logger { verbosity: Verbosity::Normal, emoji: true, color: None, text: true, json: None };
+
+    fn color(&self) -> bool {
+        self.color.unwrap_or_else(|| io::stdout().is_terminal())
+    }
+
+    fn write(&mut self, record: &Record) {
+        if !self.verbosity.shows(record.level) {
+            return;
+        }
+        if let Some(json) = &mut self.json {
+            let line = serde_json::to_string(record).expect("records always serialize");
+            // Tools reading the stream are gone if it breaks; keep running without them
+            if writeln!(json, "{}", line).is_err() {
+                self.json = None;
+            }
+        }
+        if self.text {
+            let mut out = String::new();
+            if self.emoji && !record.icon.is_empty() {
+                out.push_str(record.icon);
+                out.push(' ');
+            }
+            out.push_str(record.message);
+            if let Some(detail) = record.detail {
+                out.push('\n');
+                out.push_str(detail);
+            }
+            if !self.emoji {
+                out = strip_emoji(&out);
+            }
+            let style = match record.level {
+                Level::Warn if self.color() => YELLOW,
+                Level::Debug if self.color() => DIM,
+                _ => "",
+            };
+            if style.is_empty() {
+                println!("{}", out.trim_end_matches('\n'));
+            } else {
+                println!("{}{}{}", style, out.trim_end_matches('\n'), RESET);
+            }
+        }
+    }
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger::DEFAULT);
+
+pub fn init(logger: Logger) {
+    *LOGGER.lock().unwrap() = logger;
+}
+
+pub fn emit(record: &Record) {
+    LOGGER.lock().unwrap().write(record);
+}
+
+pub fn enabled(level: Level) -> bool {
+    let logger = LOGGER.lock().unwrap();
+    (logger.text || logger.json.is_some()) && logger.verbosity.shows(level)
+}
+
+// Whether text output may carry ANSI colors. Detail is shared with the JSON
+// stream, so any JSON output turns them off.
+pub fn color() -> bool {
+    let logger = LOGGER.lock().unwrap();
+    logger.text && logger.json.is_none() && logger.color()
+}
+
+pub fn report(target: &str, text: &str) {
+    let (message, detail) = match text.trim_end().split_once('\n') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (text.trim_end(), None),
+    };
+    emit(&Record { level: Level::Report, target, icon: "", message, detail });
+}
+
+pub fn warn(target: &str, icon: &str, message: &str) {
+    emit(&Record { level: Level::Warn, target, icon, message, detail: None });
+}
+
+pub fn info(target: &str, icon: &str, message: &str) {
+    emit(&Record { level: Level::Info, target, icon, message, detail: None });
+}
+
+pub fn debug(target: &str, message: &str) {
+    emit(&Record { level: Level::Debug, target, icon: "", message, detail: None });
+}
+
+// Drop pictographs and the joiners and selectors that go with them, along
+// with the space that usually follows one
+pub fn strip_emoji(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_emoji(c) {
+            while chars.peek().is_some_and(|&n| is_emoji(n)) {
+                chars.next();
+            }
+            if chars.peek() == Some(&' ') {
+                chars.next();
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF   // Pictographs, emoticons, transport, supplemental symbols
+        | 0x2600..=0x27BF   // Miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF   // Arrows and stars such as ⭐
+        | 0x2300..=0x23FF   // Technical symbols such as ⏳ ⛔'s neighbours
+        | 0xFE0F | 0x200D   // Variation selector and zero-width joiner
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_follow_verbosity_and_emoji_strips_cleanly() {
+        assert!(Verbosity::Quiet.shows(Level::Report) && Verbosity::Quiet.shows(Level::Warn));
+        assert!(!Verbosity::Quiet.shows(Level::Info));
+        assert!(Verbosity::Normal.shows(Level::Info) && !Verbosity::Normal.shows(Level::Debug));
+        assert!(Verbosity::Verbose.shows(Level::Debug));
+
+        assert_eq!(strip_emoji("🦸 StitchBot ACTIVATED! Tips: 11 → merging 11"), "StitchBot ACTIVATED! Tips: 11 → merging 11");
+        assert_eq!(strip_emoji("⛏️ pool-east   45.0%"), "pool-east   45.0%");
+        assert_eq!(strip_emoji("🛡️  Finality held"), " Finality held");
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ToyDag;
+
+// One miner's pending transactions and their fees. Block templates take the
+// highest fees first; a tx leaves the pool once the miner sees it in a block.
+#[derive(Debug, Clone, Default)]
+pub struct Mempool {
+    fees: HashMap<u64, u64>,
+}
+
+impl Mempool {
+    pub fn add(&mut self, tx: u64, fee: u64) {
+        self.fees.insert(tx, fee);
+    }
+
+    pub fn len(&self) -> usize {
+        self.fees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fees.is_empty()
+    }
+
+    pub fn fee(&self, tx: u64) -> Option<u64> {
+        self.fees.get(&tx).copied()
+    }
+
+    // Drop txs a block just carried
+    pub fn remove(&mut self, txs: &[u64]) {
+        for tx in txs {
+            self.fees.remove(tx);
+        }
+    }
+
+    // Up to `max` txs for a block template, highest fee first, ties by id
+    pub fn select(&self, max: usize) -> Vec<u64> {
+        let mut txs: Vec<(u64, u64)> = self.fees.iter().map(|(&tx, &fee)| (tx, fee)).collect();
+        txs.sort_unstable_by_key(|&(tx, fee)| (std::cmp::Reverse(fee), tx));
+        txs.into_iter().take(max).map(|(tx, _)| tx).collect()
+    }
+}
+
+// Transactions as the total order sees them. Parallel blocks may carry the
+// same tx; only its first appearance in the order counts, the rest are
+// duplicates that took block space for nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxAcceptance {
+    pub accepted: Vec<u64>, // Unique txs, in acceptance order
+    pub duplicates: usize,  // Later copies of already accepted txs
+}
+
+impl TxAcceptance {
+    // Tx slots used across all blocks
+    pub fn included(&self) -> usize {
+        self.accepted.len() + self.duplicates
+    }
+}
+
+impl ToyDag {
+    pub fn tx_acceptance(&self) -> TxAcceptance {
+        let mut seen = HashSet::new();
+        let mut acceptance = TxAcceptance::default();
+        for id in self.ordered_blocks() {
+            for &tx in &self.blocks[&id].txs {
+                if seen.insert(tx) {
+                    acceptance.accepted.push(tx);
+                } else {
+                    acceptance.duplicates += 1;
+                }
+            }
+        }
+        acceptance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_take_the_highest_fees() {
+        let mut pool = Mempool::default();
+        for (tx, fee) in [(1, 5), (2, 50), (3, 5), (4, 20)] {
+            pool.add(tx, fee);
+        }
+        assert_eq!(pool.select(3), vec![2, 4, 1]);
+        pool.remove(&[2, 9]);
+        assert_eq!(pool.select(10), vec![4, 1, 3]);
+    }
+
+    #[test]
+    fn parallel_copies_count_once() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block_with_txs(vec![0], vec![1, 2]).unwrap();
+        dag.create_block_with_txs(vec![0], vec![2, 3]).unwrap();
+        dag.create_block_with_txs(vec![1, 2], vec![3]).unwrap();
+
+        let acceptance = dag.tx_acceptance();
+        assert_eq!(acceptance.accepted, vec![1, 2, 3]);
+        assert_eq!(acceptance.duplicates, 2);
+        assert_eq!(acceptance.included(), 5);
+    }
+}
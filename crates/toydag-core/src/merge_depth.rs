@@ -0,0 +1,91 @@
+use crate::ToyDag;
+
+// Merge-depth bound: a block may not merge anything that sits below its
+// merge-depth root, the chain block `depth` blue score under its selected
+// parent. With `kosherize` on, such a block is still allowed when a mergeset
+// block the new block colors blue, above the root, already has it in its past.
+#[derive(Debug, Clone)]
+pub struct MergeDepth {
+    pub depth: u64,
+    pub kosherize: bool,
+}
+
+// How a block's mergeset fared against the bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeCheck {
+    pub kosherized: usize, // Below the root but vouched for
+    pub violating: usize,  // Below the root with nothing vouching
+}
+
+impl MergeDepth {
+    pub fn new(depth: u64) -> Self {
+        MergeDepth { depth, kosherize: true }
+    }
+
+    // Highest chain block at least `depth` blue score under `selected_parent`;
+    // None while the chain is still shorter than that
+    pub fn root(&self, dag: &ToyDag, selected_parent: u64) -> Option<u64> {
+        dag.chain_ancestor_at_depth(selected_parent, self.depth)
+    }
+
+    pub fn check(&self, dag: &ToyDag, selected_parent: u64, mergeset: &[u64], blues: &[u64]) -> MergeCheck {
+        let Some(root) = self.root(dag, selected_parent) else {
+            return MergeCheck::default();
+        };
+
+        let (above, below): (Vec<u64>, Vec<u64>) = mergeset.iter().partition(|&&m| dag.in_past(root, m));
+        let kosherizing: Vec<u64> = above.into_iter().filter(|k| blues.contains(k)).collect();
+
+        let mut result = MergeCheck::default();
+        for m in below {
+            if self.kosherize && kosherizing.iter().any(|&k| dag.in_past(m, k)) {
+                result.kosherized += 1;
+            } else {
+                result.violating += 1;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Chain 0..=8, a side block 9 off block 2, and block 10 that merged 9
+    // while it was still shallow enough, building on chain block 5
+    fn fork(kosherize: bool) -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for id in 1..=8 {
+            dag.create_block(vec![id - 1]).unwrap();
+        }
+        dag.merge_depth = Some(MergeDepth { depth: 3, kosherize });
+        assert_eq!(dag.create_block(vec![2]), Ok(9));
+        assert!(dag.insert_block(10, vec![5, 9], vec![], None));
+        dag
+    }
+
+    #[test]
+    fn deep_merge_is_rejected() {
+        let mut dag = fork(true);
+        assert!(!dag.insert_block(11, vec![8, 9], vec![], None));
+        assert!(!dag.contains(11));
+        assert_eq!(dag.stats.merge_depth_violations, 1);
+    }
+
+    #[test]
+    fn kosherizing_block_vouches_for_deep_merge() {
+        let mut dag = fork(true);
+        assert!(dag.insert_block(11, vec![8, 10], vec![], None));
+        assert_eq!(dag.stats.kosherized_merges, 1);
+        assert_eq!(dag.stats.merge_depth_violations, 0);
+    }
+
+    #[test]
+    fn strict_rule_ignores_kosherizing_blocks() {
+        let mut dag = fork(false);
+        assert!(!dag.insert_block(11, vec![8, 10], vec![], None));
+        assert_eq!(dag.stats.merge_depth_violations, 1);
+    }
+}
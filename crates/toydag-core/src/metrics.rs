@@ -0,0 +1,120 @@
+// Per-block time-series export for external analysis (pandas, plotters, ...).
+//
+// One row per inserted block, in insertion order. Column schema:
+//
+//   block                    u64   id of the inserted block (also the tick: one block per tick)
+//   parents                  u64   number of parents it references
+//   color                    str   "blue" or "red"
+//   blue_score               u64   blue blocks in its past
+//   anticone                 u64   blues the coloring rule counted in its anticone
+//   k                        u64   k the coloring rule used for this block
+//   tips                     u64   tip count after insertion
+//   virtual_selected_parent  u64   virtual's selected tip after insertion
+//   red_rate                 f64   cumulative fraction of red (orphaned) blocks so far
+//   reorgs                   u64   cumulative selected-chain reorgs so far
+//   stitch_activations       u64   cumulative StitchBot activations so far
+//
+// `.parquet` paths are written as Parquet when built with the `parquet` feature;
+// every other path is written as CSV with a header row.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMetrics {
+    pub block: u64,
+    pub parents: u64,
+    pub blue: bool,
+    pub blue_score: u64,
+    pub anticone: u64,
+    pub k: u64,
+    pub tips: u64,
+    pub virtual_selected_parent: u64,
+    pub red_rate: f64,
+    pub reorgs: u64,
+    pub stitch_activations: u64,
+}
+
+pub const COLUMNS: [&str; 11] = [
+    "block",
+    "parents",
+    "color",
+    "blue_score",
+    "anticone",
+    "k",
+    "tips",
+    "virtual_selected_parent",
+    "red_rate",
+    "reorgs",
+    "stitch_activations",
+];
+
+pub fn write(rows: &[BlockMetrics], path: &Path) -> Result<(), String> {
+    if path.extension().is_some_and(|ext| ext == "parquet") {
+        write_parquet(rows, path)
+    } else {
+        fs::write(path, to_csv(rows)).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+pub fn to_csv(rows: &[BlockMetrics]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for r in rows {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{:.6},{},{}",
+            r.block,
+            r.parents,
+            if r.blue { "blue" } else { "red" },
+            r.blue_score,
+            r.anticone,
+            r.k,
+            r.tips,
+            r.virtual_selected_parent,
+            r.red_rate,
+            r.reorgs,
+            r.stitch_activations
+        );
+    }
+    out
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(rows: &[BlockMetrics], path: &Path) -> Result<(), String> {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+
+    let u64_col = |f: fn(&BlockMetrics) -> u64| -> ArrayRef { Arc::new(UInt64Array::from_iter_values(rows.iter().map(f))) };
+
+    let columns: Vec<ArrayRef> = vec![
+        u64_col(|r| r.block),
+        u64_col(|r| r.parents),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|r| if r.blue { "blue" } else { "red" }))),
+        u64_col(|r| r.blue_score),
+        u64_col(|r| r.anticone),
+        u64_col(|r| r.k),
+        u64_col(|r| r.tips),
+        u64_col(|r| r.virtual_selected_parent),
+        Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.red_rate))),
+        u64_col(|r| r.reorgs),
+        u64_col(|r| r.stitch_activations),
+    ];
+
+    let batch = RecordBatch::try_from_iter(COLUMNS.iter().zip(columns))
+        .map_err(|e| e.to_string())?;
+    let file = fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None).map_err(|e| e.to_string())?;
+    writer.write(&batch).map_err(|e| e.to_string())?;
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_rows: &[BlockMetrics], path: &Path) -> Result<(), String> {
+    Err(format!("{}: Parquet output requires building with `--features parquet`", path.display()))
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::ToyDag;
+use crate::score::depth_between;
+
+// Toy finality receipt: "tx X accepted by chain block Y at blue score Z with depth D".
+// The signature is a keyed hash, not real crypto — it only models the artifact shape.
+// It is FNV-1a written out here, so a receipt issued by one build or platform
+// still verifies on another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub tx: u64,
+    pub chain_block: u64,
+    pub blue_score: u64,
+    pub depth: u64,
+    pub signature: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptError {
+    BadSignature,
+    TxNotAccepted(u64),
+    ChainBlockMismatch { expected: u64, found: u64 },
+    BlueScoreMismatch { expected: u64, found: u64 },
+    InsufficientDepth { expected: u64, found: u64 },
+}
+
+impl fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptError::BadSignature => write!(f, "signature does not match receipt contents"),
+            ReceiptError::TxNotAccepted(tx) => write!(f, "tx {} is not accepted by the selected chain", tx),
+            ReceiptError::ChainBlockMismatch { expected, found } => {
+                write!(f, "expected accepting chain block {}, snapshot has {}", expected, found)
+            }
+            ReceiptError::BlueScoreMismatch { expected, found } => {
+                write!(f, "expected blue score {}, snapshot has {}", expected, found)
+            }
+            ReceiptError::InsufficientDepth { expected, found } => {
+                write!(f, "expected depth of at least {}, snapshot has {}", expected, found)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Receipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tx {} accepted by chain block {} at blue score {} with depth {} (sig {:016x})",
+            self.tx, self.chain_block, self.blue_score, self.depth, self.signature
+        )
+    }
+}
+
+impl Receipt {
+    // Build and sign a receipt from the current DAG state, if the tx is accepted
+    pub fn issue(dag: &ToyDag, tx: u64, key: u64) -> Option<Receipt> {
+        let (chain_block, blue_score, depth) = acceptance(dag, tx)?;
+        Some(Receipt {
+            tx,
+            chain_block,
+            blue_score,
+            depth,
+            signature: sign(key, tx, chain_block, blue_score, depth),
+        })
+    }
+
+    // Check the signature, then check the claim still holds in `snapshot`.
+    // Depth may have grown since issuance; it must not have shrunk.
+    pub fn verify(&self, snapshot: &ToyDag, key: u64) -> Result<(), ReceiptError> {
+        if self.signature != sign(key, self.tx, self.chain_block, self.blue_score, self.depth) {
+            return Err(ReceiptError::BadSignature);
+        }
+
+        let (chain_block, blue_score, depth) =
+            acceptance(snapshot, self.tx).ok_or(ReceiptError::TxNotAccepted(self.tx))?;
+
+        if chain_block != self.chain_block {
+            return Err(ReceiptError::ChainBlockMismatch { expected: self.chain_block, found: chain_block });
+        }
+        if blue_score != self.blue_score {
+            return Err(ReceiptError::BlueScoreMismatch { expected: self.blue_score, found: blue_score });
+        }
+        if depth < self.depth {
+            return Err(ReceiptError::InsufficientDepth { expected: self.depth, found: depth });
+        }
+        Ok(())
+    }
+}
+
+// Accepting chain block = first selected-chain block whose past contains a
+// block carrying the tx. Each chain block's past is its selected parent's
+// plus its mergeset, so one walk up from genesis meets the carriers in the
+// order the chain accepts them.
+fn acceptance(dag: &ToyDag, tx: u64) -> Option<(u64, u64, u64)> {
+    let carriers: HashSet<u64> = dag.blocks.values().filter(|b| b.txs.contains(&tx)).map(|b| b.id).collect();
+    if carriers.is_empty() {
+        return None;
+    }
+    let tip_score = dag.blocks[&dag.selected_parent].blue_score;
+
+    dag.selected_chain()
+        .into_iter()
+        .find(|&c| carriers.contains(&c) || (c != dag.genesis && dag.ghostdag[&c].mergeset().any(|m| carriers.contains(&m))))
+        .map(|c| {
+            let score = dag.blocks[&c].blue_score;
+            (c, score, depth_between(tip_score, score))
+        })
+}
+
+// 64-bit FNV-1a over the fields' little-endian bytes, key first
+fn sign(key: u64, tx: u64, chain_block: u64, blue_score: u64, depth: u64) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    [key, tx, chain_block, blue_score, depth]
+        .iter()
+        .flat_map(|field| field.to_le_bytes())
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: u64 = 0x5eed;
+
+    // Tx 7 rides in block 1; blocks 2 and 3 bury it under the chain
+    fn dag() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.insert_block(1, vec![0], vec![7], None);
+        dag.insert_block(2, vec![1], vec![], None);
+        dag.insert_block(3, vec![2], vec![], None);
+        dag
+    }
+
+    #[test]
+    fn signatures_are_pinned_across_builds() {
+        // Worked out with an independent FNV-1a; a change here breaks every
+        // receipt already handed out
+        assert_eq!(sign(0, 0, 0, 0, 0), 0x40d6_9e0c_f0f6_5c45);
+        assert_eq!(sign(KEY, 7, 1, 1, 2), 0x71f2_10cc_0fcf_3747);
+    }
+
+    #[test]
+    fn issued_receipt_verifies_and_survives_deeper_burial() {
+        let mut dag = dag();
+        let receipt = Receipt::issue(&dag, 7, KEY).unwrap();
+        assert_eq!((receipt.chain_block, receipt.blue_score, receipt.depth), (1, 1, 2));
+        assert_eq!(receipt.verify(&dag, KEY), Ok(()));
+        dag.insert_block(4, vec![3], vec![], None);
+        assert_eq!(receipt.verify(&dag, KEY), Ok(()));
+        assert_eq!(Receipt::issue(&dag, 8, KEY), None);
+    }
+
+    #[test]
+    fn tampered_receipts_and_wrong_keys_are_refused() {
+        let dag = dag();
+        let receipt = Receipt::issue(&dag, 7, KEY).unwrap();
+        assert_eq!(receipt.verify(&dag, KEY + 1), Err(ReceiptError::BadSignature));
+        for tampered in [
+            Receipt { tx: 8, ..receipt.clone() },
+            Receipt { chain_block: 2, ..receipt.clone() },
+            Receipt { blue_score: 0, ..receipt.clone() },
+            Receipt { depth: 5, ..receipt.clone() },
+            Receipt { signature: receipt.signature ^ 1, ..receipt.clone() },
+        ] {
+            assert_eq!(tampered.verify(&dag, KEY), Err(ReceiptError::BadSignature), "{}", tampered);
+        }
+
+        // Re-signed claims still have to hold in the snapshot
+        let deeper = Receipt { depth: 5, signature: sign(KEY, 7, 1, 1, 5), ..receipt.clone() };
+        assert_eq!(deeper.verify(&dag, KEY), Err(ReceiptError::InsufficientDepth { expected: 5, found: 2 }));
+        let moved = Receipt { chain_block: 2, signature: sign(KEY, 7, 2, 1, 2), ..receipt };
+        assert_eq!(moved.verify(&dag, KEY), Err(ReceiptError::ChainBlockMismatch { expected: 2, found: 1 }));
+    }
+
+    // Tx 7 rides in side block 1 and in chain block 3. Block 3 accepts its
+    // own copy before block 4 merges block 1, lower id or not.
+    #[test]
+    fn the_first_accepted_copy_counts() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.insert_block(1, vec![0], vec![7], None);
+        dag.insert_block(2, vec![0], vec![], None);
+        dag.insert_block(3, vec![2], vec![7], None);
+        dag.insert_block(4, vec![3, 1], vec![], None);
+        assert_eq!(dag.selected_chain(), vec![0, 2, 3, 4]);
+        assert_eq!(acceptance(&dag, 7), Some((3, 2, 2)));
+
+        dag.insert_block(5, vec![0], vec![8], None);
+        dag.insert_block(6, vec![4, 5], vec![], None);
+        assert_eq!(acceptance(&dag, 8), Some((6, 6, 0)));
+    }
+}
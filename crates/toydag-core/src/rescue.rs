@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::ToyDag;
+use crate::compact::{map_bytes, vec_bytes};
+use crate::score::{BlueWork, sum_work};
+
+// What became of a red block. Red blocks earn nothing themselves, but once a
+// later blue block has one in its past its transactions are ordered and its
+// work backs that blue block's past. One that falls below the finality point
+// without that happening is orphaned for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedFate {
+    Pending,
+    Rescued { by: u64, after: u64 }, // First blue block with it in its past, and blocks inserted in between
+    Orphaned,
+}
+
+// Fate of every red block seen so far; `pending` holds the undecided ones
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RedTracker {
+    fates: HashMap<u64, (u64, RedFate)>, // Insertion tick and fate
+    pending: Vec<u64>,
+}
+
+impl RedTracker {
+    pub(crate) fn memory(&self) -> usize {
+        map_bytes(&self.fates) + vec_bytes(&self.pending)
+    }
+}
+
+impl ToyDag {
+    pub fn red_fate(&self, id: u64) -> Option<RedFate> {
+        self.reds.fates.get(&id).map(|&(_, fate)| fate)
+    }
+
+    // Work of red blocks that can no longer be merged into the blue past
+    pub fn orphaned_work(&self) -> BlueWork {
+        sum_work(self.reds.fates.iter().filter(|(_, (_, fate))| *fate == RedFate::Orphaned).map(|(id, _)| self.blocks[id].work))
+    }
+
+    pub(crate) fn track_red(&mut self, id: u64, tick: u64) {
+        self.reds.fates.insert(id, (tick, RedFate::Pending));
+        self.reds.pending.push(id);
+    }
+
+    // The virtual turned a red block blue: it has no fate as a red block
+    pub(crate) fn untrack_red(&mut self, id: u64) {
+        self.reds.fates.remove(&id);
+        self.reds.pending.retain(|&red| red != id);
+    }
+
+    // A block the virtual has just colored blue, on insertion or by a flip,
+    // rescues every pending red block in its past. Colors are the virtual's,
+    // so rescuers are always in its blue past; a rescue stands if the
+    // rescuer is later repainted red.
+    pub(crate) fn rescue_reds(&mut self, blue: u64, tick: u64) {
+        let (rescued, pending) = std::mem::take(&mut self.reds.pending)
+            .into_iter()
+            .partition(|&red| self.in_past(red, blue));
+        self.reds.pending = pending;
+        for red in rescued {
+            let entry = self.reds.fates.get_mut(&red).expect("pending reds are tracked");
+            let after = tick - entry.0;
+            entry.1 = RedFate::Rescued { by: blue, after };
+            self.stats.record_rescue(after as usize);
+        }
+    }
+
+    // Pending red blocks at or below the finality point's blue score, outside
+    // its past, can't be merged by anything that respects finality. One in
+    // its past (repainted red after its blue descendants went in) is already
+    // merged, and the next blue chain block rescues it.
+    pub(crate) fn orphan_reds(&mut self) {
+        let final_point = self.finality_point;
+        let final_score = self.blocks[&final_point].blue_score;
+        let (orphaned, pending): (Vec<u64>, Vec<u64>) = std::mem::take(&mut self.reds.pending)
+            .into_iter()
+            .partition(|&red| self.blocks[&red].blue_score <= final_score && !self.in_past(red, final_point));
+        self.reds.pending = pending;
+        for red in orphaned {
+            self.reds.fates.get_mut(&red).expect("pending reds are tracked").1 = RedFate::Orphaned;
+            self.stats.record_orphan(self.blocks[&red].work);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knight::KMode;
+    use crate::{Color, FINALITY_DEPTH};
+
+    // Blocks 1 and 2 side by side off genesis. At k = 0, block 2 arrives with
+    // the blue block 1 in its anticone and comes out red.
+    fn with_red() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = KMode::Fixed(0);
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        assert_eq!(dag.blocks[&2].color, Color::Red);
+        assert_eq!(dag.red_fate(2), Some(RedFate::Pending));
+        dag
+    }
+
+    #[test]
+    fn blue_block_merging_a_red_rescues_it() {
+        let mut dag = with_red();
+        dag.create_block(vec![1]).unwrap();
+        dag.create_block(vec![3, 2]).unwrap();
+        assert_eq!(dag.red_fate(2), Some(RedFate::Rescued { by: 4, after: 2 }));
+        assert_eq!(dag.red_fate(1), None);
+        assert_eq!(dag.stats.rescued_reds, 1);
+    }
+
+    #[test]
+    fn red_left_below_finality_is_orphaned() {
+        let mut dag = with_red();
+        let mut tip = 1;
+        for _ in 0..FINALITY_DEPTH + 1 {
+            tip = dag.create_block(vec![tip]).unwrap();
+        }
+        assert_eq!(dag.red_fate(2), Some(RedFate::Orphaned));
+        assert_eq!(dag.orphaned_work(), 1);
+        assert_eq!(dag.stats.orphaned_reds, 1);
+    }
+
+    // Block 1 turning red once the chain above it is final: it is in the
+    // finality point's past, so it waits for the next blue block instead of
+    // being written off
+    #[test]
+    fn red_inside_the_final_past_is_not_orphaned() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let mut tip = 0;
+        for _ in 0..FINALITY_DEPTH + 3 {
+            tip = dag.create_block(vec![tip]).unwrap();
+        }
+        assert!(dag.blocks[&dag.finality_point].blue_score > dag.blocks[&1].blue_score);
+        dag.track_red(1, 0);
+        dag.orphan_reds();
+        assert_eq!(dag.red_fate(1), Some(RedFate::Pending));
+        assert_eq!(dag.stats.orphaned_reds, 0);
+
+        let next = dag.create_block(vec![tip]).unwrap();
+        assert!(matches!(dag.red_fate(1), Some(RedFate::Rescued { by, .. }) if by == next));
+    }
+}
@@ -0,0 +1,126 @@
+// Numeric types and arithmetic for accumulated scores. Everything here
+// saturates instead of wrapping or panicking, so long runs with extreme
+// parameters degrade to "pinned at max" rather than silently going wrong.
+
+// Blue work is u128 by default; the `narrow-work` feature drops it to u64,
+// which is handy for watching saturation happen in ordinary-length runs
+#[cfg(not(feature = "narrow-work"))]
+pub type BlueWork = u128;
+#[cfg(feature = "narrow-work")]
+pub type BlueWork = u64;
+
+pub fn sum_work(work: impl IntoIterator<Item = BlueWork>) -> BlueWork {
+    work.into_iter().fold(0, BlueWork::saturating_add)
+}
+
+// Block counts become scores; a count that doesn't fit pins at u64::MAX
+pub fn count_score(count: usize) -> u64 {
+    u64::try_from(count).unwrap_or(u64::MAX)
+}
+
+// Depth of a block below a tip, in score units; never underflows
+pub fn depth_between(tip_score: u64, score: u64) -> u64 {
+    tip_score.saturating_sub(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToyDag;
+
+    #[test]
+    fn sum_work_saturates_at_huge_difficulty() {
+        let huge = BlueWork::MAX / 2;
+        assert_eq!(sum_work([huge, huge, 10]), BlueWork::MAX);
+        assert_eq!(sum_work([1, 2, 3]), 6);
+        assert_eq!(sum_work([]), 0);
+    }
+
+    // A running total fed through `sum_work` agrees with exact addition for
+    // as long as that fits, then stays pinned
+    #[test]
+    fn running_total_matches_exact_addition_until_it_pins() {
+        let step = BlueWork::MAX / 7 + 1;
+        let (mut total, mut exact): (BlueWork, Option<BlueWork>) = (0, Some(0));
+        for _ in 0..10 {
+            total = sum_work([total, step]);
+            exact = exact.and_then(|e| e.checked_add(step));
+            assert_eq!(total, exact.unwrap_or(BlueWork::MAX));
+        }
+        assert_eq!(total, BlueWork::MAX);
+    }
+
+    // Pairs of siblings merged round after round: every block's blue work
+    // covers each blue parent's, saturating, however wide the merge
+    #[test]
+    fn dag_blue_work_covers_every_blue_parent_at_extreme_work() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.block_work = BlueWork::MAX / 5;
+        for _ in 0..6 {
+            let tips: Vec<u64> = dag.tips().collect();
+            dag.create_block(tips.clone()).unwrap();
+            dag.create_block(tips).unwrap();
+        }
+        for block in dag.blocks.values() {
+            for p in block.parents.iter().map(|p| &dag.blocks[p]).filter(|p| p.color == crate::Color::Blue) {
+                assert!(block.blue_work >= sum_work([p.blue_work, p.work]), "block {} under parent {}", block.id, p.id);
+            }
+        }
+        let tip = dag.selected_parent();
+        assert_eq!(dag.blocks[&tip].blue_work, BlueWork::MAX);
+        assert_eq!(dag.blocks[&tip].blue_score, 11);
+    }
+
+    // As if billions of blocks sat below genesis: scores start a few short of
+    // u64::MAX, and pairs of siblings inserted on top pin them there
+    #[test]
+    fn scores_pin_at_max_through_insertion() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let genesis = dag.blocks.get_mut(&0).unwrap();
+        genesis.blue_score = u64::MAX - 3;
+        genesis.past_size = u64::MAX - 3;
+
+        for round in 0..4 {
+            let tips: Vec<u64> = dag.tips().collect();
+            assert!(dag.insert_block(2 * round + 1, tips.clone(), vec![], None));
+            assert!(dag.insert_block(2 * round + 2, tips, vec![], None));
+        }
+        for block in dag.blocks.values() {
+            for p in block.parents.iter().map(|p| &dag.blocks[p]) {
+                assert!(block.past_size > p.past_size || block.past_size == u64::MAX, "block {} under parent {}", block.id, p.id);
+            }
+            if let Some(sp) = block.selected_parent {
+                assert!(block.blue_score > dag.blocks[&sp].blue_score || block.blue_score == u64::MAX, "block {}", block.id);
+            }
+        }
+        let tip = &dag.blocks[&dag.selected_parent()];
+        assert_eq!((tip.blue_score, tip.past_size), (u64::MAX, u64::MAX));
+        assert_eq!(dag.selected_chain().len(), 5);
+        assert_eq!(dag.ordered_blocks().len(), dag.block_count());
+    }
+
+    #[test]
+    fn score_helpers_never_wrap() {
+        assert_eq!(count_score(usize::MAX), u64::try_from(usize::MAX).unwrap_or(u64::MAX));
+        assert_eq!(depth_between(5, 9), 0);
+        assert_eq!(depth_between(u64::MAX, 0), u64::MAX);
+    }
+
+    #[test]
+    fn dag_blue_work_saturates_instead_of_overflowing() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.block_work = BlueWork::MAX / 3;
+
+        let mut tip = 0;
+        for _ in 0..6 {
+            tip = dag.create_block(vec![tip]).unwrap();
+        }
+
+        let block = &dag.blocks[&tip];
+        assert_eq!(block.blue_work, BlueWork::MAX);
+        assert_eq!(block.blue_score, 6);
+    }
+}
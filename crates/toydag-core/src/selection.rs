@@ -0,0 +1,71 @@
+use std::cmp::Reverse;
+
+use crate::ToyDag;
+use crate::error::DagError;
+
+impl ToyDag {
+    // Up to `max` parents out of `candidates`, picked the way a node fills in
+    // the virtual's parents instead of at random. The highest blue score goes
+    // first, as the selected parent. Each further pick is the candidate that
+    // would merge the most blocks not already covered, so tips whose pasts
+    // overlap the selection are passed over for ones that bring in their own
+    // anticone; ties go to the higher blue score, then the lower id.
+    // Candidates that add nothing, or would push the mergeset over
+    // `mergeset_limit`, are left out.
+    pub fn select_parents(&self, candidates: &[u64], max: usize) -> Result<Vec<u64>, DagError> {
+        if let Some(&unknown) = candidates.iter().find(|c| !self.blocks.contains_key(c)) {
+            return Err(DagError::UnknownBlock(unknown));
+        }
+        let Some(&selected) = candidates.iter().max_by_key(|&&c| (self.blocks[&c].blue_score, Reverse(c))) else {
+            return Ok(Vec::new());
+        };
+        let mut parents = vec![selected];
+        let mut remaining: Vec<u64> = candidates.iter().copied().filter(|&c| c != selected).collect();
+        let mut merged = 0;
+
+        while parents.len() < max.max(1) {
+            let best = remaining
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| {
+                    parents.push(c);
+                    let size = self.mergeset_without_selected(selected, &parents).len();
+                    parents.pop();
+                    (size, i, c)
+                })
+                .filter(|&(size, _, _)| size > merged && self.mergeset_limit.is_none_or(|limit| size <= limit))
+                .max_by_key(|&(size, _, c)| (size, self.blocks[&c].blue_score, Reverse(c)));
+            let Some((size, i, c)) = best else {
+                break;
+            };
+            parents.push(c);
+            remaining.swap_remove(i);
+            merged = size;
+        }
+        Ok(parents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ToyDag;
+
+    #[test]
+    fn picks_the_tip_that_merges_the_most_new_blocks() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for _ in 0..3 {
+            dag.create_block(vec![0]).unwrap(); // 1, 2, 3
+        }
+        dag.create_block(vec![1]).unwrap(); // 4: past already mostly under 5
+        dag.create_block(vec![1, 2]).unwrap(); // 5: highest blue score
+        dag.create_block(vec![3]).unwrap(); // 6: a side branch of its own
+
+        let tips = [4, 5, 6];
+        assert_eq!(dag.select_parents(&tips, 2), Ok(vec![5, 6]));
+        assert_eq!(dag.select_parents(&tips, 3), Ok(vec![5, 6, 4]));
+        assert_eq!(dag.select_parents(&[5, 1, 2], 3), Ok(vec![5])); // Already in 5's past
+        dag.mergeset_limit = Some(2);
+        assert_eq!(dag.select_parents(&tips, 3), Ok(vec![5, 6]));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Serialize;
+
+use crate::score::BlueWork;
+use crate::{Color, ToyDag};
+
+// Everything a front-end needs to draw one window of the DAG, in one response
+#[derive(Debug, Clone, Serialize)]
+pub struct DagSlice {
+    pub anchor: u64,
+    pub depth: usize,
+    pub blocks: Vec<SliceBlock>,
+    pub edges: Vec<(u64, u64)>, // (child, parent), both inside the slice
+    pub chain: Vec<u64>,        // Selected-chain blocks inside the slice, oldest first
+    pub virtual_selected_parent: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SliceBlock {
+    pub id: u64,
+    pub color: &'static str,
+    pub blue_score: u64,
+    pub blue_work: BlueWork,
+    pub layer: usize, // Topological depth from genesis
+    pub miner: Option<u32>,
+    pub chain: bool,
+}
+
+impl ToyDag {
+    // Blocks within `depth` parent hops of `anchor` (inclusive), or None if the
+    // anchor is unknown
+    pub fn dag_slice(&self, anchor: u64, depth: usize) -> Option<DagSlice> {
+        if !self.blocks.contains_key(&anchor) {
+            return None;
+        }
+
+        let mut hops: HashMap<u64, usize> = HashMap::from([(anchor, 0)]);
+        let mut queue = VecDeque::from([anchor]);
+        while let Some(id) = queue.pop_front() {
+            let h = hops[&id];
+            if h == depth {
+                continue;
+            }
+            for &parent in &self.blocks[&id].parents {
+                if let Entry::Vacant(slot) = hops.entry(parent) {
+                    slot.insert(h + 1);
+                    queue.push_back(parent);
+                }
+            }
+        }
+
+        let selected_chain = self.selected_chain();
+        let on_chain: HashSet<u64> = selected_chain.iter().copied().collect();
+
+        let mut ids: Vec<u64> = hops.keys().copied().collect();
+        ids.sort_unstable();
+
+        let blocks = ids
+            .iter()
+            .map(|id| {
+                let b = &self.blocks[id];
+                SliceBlock {
+                    id: b.id,
+                    color: match b.color {
+                        Color::Blue => "blue",
+                        Color::Red => "red",
+                    },
+                    blue_score: b.blue_score,
+                    blue_work: b.blue_work,
+                    layer: b.topo_depth,
+                    miner: b.miner,
+                    chain: on_chain.contains(id),
+                }
+            })
+            .collect();
+
+        let edges = ids
+            .iter()
+            .flat_map(|&id| {
+                self.blocks[&id]
+                    .parents
+                    .iter()
+                    .filter(|p| hops.contains_key(p))
+                    .map(move |&p| (id, p))
+            })
+            .collect();
+
+        let chain = selected_chain.into_iter().filter(|id| hops.contains_key(id)).collect();
+
+        Some(DagSlice {
+            anchor,
+            depth,
+            blocks,
+            edges,
+            chain,
+            virtual_selected_parent: self.selected_parent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_holds_blocks_edges_and_chain_within_depth() {
+        // 0 ← 1, 2 ← 3 ← 4: block 3 merges both sides
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let a = dag.create_block(vec![0]).unwrap();
+        let b = dag.create_block(vec![0]).unwrap();
+        let m = dag.create_block(vec![a, b]).unwrap();
+        let tip = dag.create_block(vec![m]).unwrap();
+        let side = dag[m].selected_parent().unwrap();
+
+        let slice = dag.dag_slice(tip, 2).unwrap();
+        let ids: Vec<u64> = slice.blocks.iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![a, b, m, tip]); // Genesis is three hops down
+        assert_eq!(slice.edges, vec![(m, a), (m, b), (tip, m)]);
+        assert_eq!(slice.chain, vec![side, m, tip]);
+        assert_eq!(slice.virtual_selected_parent, tip);
+        for block in &slice.blocks {
+            assert_eq!(block.chain, slice.chain.contains(&block.id));
+            assert_eq!(block.layer, dag[block.id].topo_depth());
+            assert_eq!(block.color, "blue");
+        }
+
+        assert_eq!(dag.dag_slice(tip, 0).unwrap().blocks.len(), 1);
+        assert_eq!(dag.dag_slice(tip, 3).unwrap().edges.len(), 5);
+        assert!(dag.dag_slice(99, 2).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Deserialize;
+
+use crate::ToyDag;
+
+// A version-bits style rule upgrade. Blue blocks are counted in GHOSTDAG
+// order in periods of `window`; a block signals readiness by carrying
+// `version` or higher. A period in which at least `threshold` of the blue
+// blocks signal locks the fork in, and one more period later it is active:
+// from then on a block whose past has the fork active must carry `version`
+// too, and blocks from miners that never upgraded are refused.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SoftFork {
+    pub version: u32,
+    pub window: u64,
+    pub threshold: f64,
+}
+
+impl SoftFork {
+    pub fn new(version: u32) -> Self {
+        SoftFork { version, window: 100, threshold: 0.9 }
+    }
+
+    // A period needs at least one blue block and one signal to lock in
+    pub fn check(&self) -> Result<(), String> {
+        if self.window == 0 {
+            return Err("soft fork window must be at least one block".to_string());
+        }
+        if !(self.threshold > 0.0 && self.threshold <= 1.0) {
+            return Err(format!("soft fork threshold {} is not in (0, 1]", self.threshold));
+        }
+        Ok(())
+    }
+
+    // Periods close at `window.max(1)` blocks, so measure against the same
+    fn needed(&self) -> u64 {
+        (self.threshold * self.window.max(1) as f64).ceil() as u64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Phase {
+    #[default]
+    Signaling,
+    LockedIn,
+    Active,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Signaling => "signaling",
+            Phase::LockedIn => "locked in",
+            Phase::Active => "active",
+        }
+    }
+}
+
+// Where a deployment stands as of one block: over its past and the block
+// itself. Each block's is its selected parent's carried through its own
+// mergeset, so every chain has its own tally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deployment {
+    pub phase: Phase,
+    pub periods: u64,       // Periods completed
+    pub last_signaled: u64, // Signaling blue blocks in the last completed period
+    counted: u64,           // Blue blocks so far in the current period
+    signaled: u64,
+}
+
+impl Deployment {
+    fn count(&mut self, fork: &SoftFork, version: u32) {
+        self.counted += 1;
+        self.signaled += (version >= fork.version) as u64;
+        if self.counted < fork.window.max(1) {
+            return;
+        }
+        self.phase = match self.phase {
+            Phase::Signaling if self.signaled >= fork.needed() => Phase::LockedIn,
+            Phase::Signaling => Phase::Signaling,
+            Phase::LockedIn | Phase::Active => Phase::Active,
+        };
+        self.periods += 1;
+        self.last_signaled = self.signaled;
+        self.counted = 0;
+        self.signaled = 0;
+    }
+}
+
+impl ToyDag {
+    // The deployment as of `block`; None without a soft fork
+    pub fn deployment(&self, block: u64) -> Option<Deployment> {
+        self.soft_fork.as_ref().map(|_| self.deployments.get(&block).copied().unwrap_or_default())
+    }
+
+    // As of the virtual's selected parent, which is what the network goes by
+    pub fn fork_phase(&self) -> Option<Phase> {
+        self.deployment(self.selected_parent).map(|d| d.phase)
+    }
+
+    // The deployment over everything below a new block: its selected
+    // parent's, carried through the blues of its mergeset (the selected
+    // parent, first, already counted itself)
+    pub(crate) fn deployment_below(&self, selected_parent: u64, mergeset_blues: &[u64]) -> Option<Deployment> {
+        let fork = self.soft_fork.as_ref()?;
+        let mut deployment = self.deployment(selected_parent)?;
+        for m in mergeset_blues.iter().filter(|&&m| m != selected_parent) {
+            deployment.count(fork, self.blocks[m].version);
+        }
+        Some(deployment)
+    }
+
+    // Count the new block in; it is blue in its own view
+    pub(crate) fn record_deployment(&mut self, id: u64, mut below: Deployment, version: u32) {
+        if let Some(fork) = &self.soft_fork {
+            below.count(fork, version);
+            self.deployments.insert(id, below);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BLOCK_VERSION, NewBlock};
+    use crate::error::DagError;
+
+    fn mine(dag: &mut ToyDag, version: u32) -> Result<u64, DagError> {
+        let id = dag.next_id();
+        let parents = vec![dag.selected_parent()];
+        dag.add_block(NewBlock { id, parents, txs: vec![], miner: None, timestamp: id, version })?;
+        Ok(id)
+    }
+
+    #[test]
+    fn fork_locks_in_then_activates_and_refuses_old_versions() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.soft_fork = Some(SoftFork { version: 2, window: 4, threshold: 0.75 });
+
+        // Period 1: two of four signal, not enough
+        for version in [2, 1, 2, 1] {
+            mine(&mut dag, version).unwrap();
+        }
+        assert_eq!(dag.fork_phase(), Some(Phase::Signaling));
+        // Period 2: three of four lock it in; period 3 still takes old blocks
+        for version in [2, 2, 1, 2, 1, 1, 1, 1] {
+            mine(&mut dag, version).unwrap();
+        }
+        let deployment = dag.deployment(dag.selected_parent()).unwrap();
+        assert_eq!((deployment.phase, deployment.periods, deployment.last_signaled), (Phase::Active, 3, 0));
+
+        assert_eq!(mine(&mut dag, BLOCK_VERSION), Err(DagError::OutdatedVersion { block: 13, version: 1, required: 2 }));
+        assert_eq!(dag.stats.outdated_versions, 1);
+        assert!(mine(&mut dag, 3).is_ok());
+        assert_eq!(ToyDag::new().fork_phase(), None);
+    }
+
+    #[test]
+    fn a_zero_window_is_refused_and_never_locks_in_unsignaled() {
+        let fork = SoftFork { version: 2, window: 0, threshold: 0.9 };
+        assert!(fork.check().is_err());
+        assert!(SoftFork { threshold: 0.0, ..SoftFork::new(2) }.check().is_err());
+        assert!(SoftFork::new(2).check().is_ok());
+
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.soft_fork = Some(fork);
+        for _ in 0..5 {
+            mine(&mut dag, BLOCK_VERSION).unwrap();
+        }
+        assert_eq!(dag.fork_phase(), Some(Phase::Signaling));
+    }
+}
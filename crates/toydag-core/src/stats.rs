@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::metrics::BlockMetrics;
+use crate::score::BlueWork;
+use crate::{Color, ToyDag};
+
+// Run-wide metrics, updated by the DAG as blocks arrive
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub tip_counts: Vec<usize>,     // Tip count after each insertion
+    pub anticone_sizes: Vec<usize>, // Blues the coloring rule counted in each block's anticone
+    pub anticones: HashMap<u64, usize>, // Each block's anticone so far, when the DAG tracks them
+    pub reorg_depths: Vec<usize>,   // Chain blocks dropped by each selected-chain reorg
+    pub finality_violations: usize, // Reorgs that dropped a finalized block
+    pub merge_latencies: Vec<usize>, // Blocks a tip waited before being referenced
+    pub stitch_activations: usize,
+    pub merge_depth_violations: usize, // Blocks rejected for merging below their merge-depth root
+    pub kosherized_merges: usize,      // Deep merges allowed because a kosherizing block covered them
+    pub oversized_mergesets: usize,    // Blocks rejected for merging more than the mergeset limit
+    pub stale_timestamps: usize,       // Blocks rejected for a timestamp not past the past median time
+    pub outdated_versions: usize,      // Blocks rejected for a version below an active soft fork's
+    pub rescued_reds: usize,           // Red blocks that later made it into a blue block's past
+    pub rescue_delays: Vec<usize>,     // Blocks inserted between each red block and its rescue
+    pub orphaned_reds: usize,          // Red blocks that fell below finality unmerged
+    pub orphaned_work: BlueWork,       // Their combined work, permanently wasted
+    pub timeline: Vec<BlockMetrics>, // Per-block rows for `--metrics-out`
+    red_blocks: usize,
+}
+
+impl Stats {
+    pub fn record_block(&mut self, tips: usize, anticone: usize) {
+        self.tip_counts.push(tips);
+        self.anticone_sizes.push(anticone);
+    }
+
+    // A new block is concurrent with everything already there outside its
+    // past, and each of those is concurrent with it
+    pub fn record_anticone(&mut self, block: u64, concurrent: &[u64]) {
+        self.anticones.insert(block, concurrent.len());
+        for id in concurrent {
+            *self.anticones.entry(*id).or_default() += 1;
+        }
+    }
+
+    // Completes the cumulative columns from the counters tracked here
+    pub fn record_metrics(&mut self, mut row: BlockMetrics) {
+        if !row.blue {
+            self.red_blocks += 1;
+        }
+        row.red_rate = ratio(self.red_blocks, self.timeline.len() + 1);
+        row.reorgs = self.reorg_depths.len() as u64;
+        row.stitch_activations = self.stitch_activations as u64;
+        self.timeline.push(row);
+    }
+
+    pub fn record_merge(&mut self, latency: usize) {
+        self.merge_latencies.push(latency);
+    }
+
+    pub fn record_reorg(&mut self, depth: usize) {
+        self.reorg_depths.push(depth);
+    }
+
+    pub fn record_finality_violation(&mut self) {
+        self.finality_violations += 1;
+    }
+
+    pub fn record_stitch(&mut self) {
+        self.stitch_activations += 1;
+    }
+
+    pub fn record_merge_depth_violation(&mut self) {
+        self.merge_depth_violations += 1;
+    }
+
+    pub fn record_kosherized(&mut self, blocks: usize) {
+        self.kosherized_merges += blocks;
+    }
+
+    pub fn record_oversized_mergeset(&mut self) {
+        self.oversized_mergesets += 1;
+    }
+
+    pub fn record_stale_timestamp(&mut self) {
+        self.stale_timestamps += 1;
+    }
+
+    pub fn record_outdated_version(&mut self) {
+        self.outdated_versions += 1;
+    }
+
+    pub fn record_rescue(&mut self, delay: usize) {
+        self.rescued_reds += 1;
+        self.rescue_delays.push(delay);
+    }
+
+    pub fn record_orphan(&mut self, work: BlueWork) {
+        self.orphaned_reds += 1;
+        self.orphaned_work = self.orphaned_work.saturating_add(work);
+    }
+
+    // Ordered (metric, value) pairs shared by the text report and the CSV
+    pub fn summary(&self, dag: &ToyDag) -> Vec<(&'static str, String)> {
+        let total = dag.blocks.len();
+        let blue = dag.blocks.values().filter(|b| b.color == Color::Blue).count();
+        let red = total - blue;
+        let chain_len = dag.selected_chain().len();
+
+        vec![
+            ("blocks", total.to_string()),
+            ("blue_blocks", blue.to_string()),
+            ("red_blocks", red.to_string()),
+            ("blue_ratio", format!("{:.4}", ratio(blue, total))),
+            ("final_tips", dag.tips.len().to_string()),
+            ("mean_tips", format!("{:.2}", mean(&self.tip_counts))),
+            ("max_tips", self.tip_counts.iter().max().copied().unwrap_or(0).to_string()),
+            ("mean_anticone", format!("{:.2}", mean(&self.anticone_sizes))),
+            ("k_mode", dag.k_mode.name().to_string()),
+            ("final_k", dag.k_mode.k().to_string()),
+            ("mean_merge_latency", format!("{:.2}", mean(&self.merge_latencies))),
+            ("selected_chain_len", chain_len.to_string()),
+            ("virtual_blue_work", dag.blocks[&dag.selected_parent].blue_work.to_string()),
+            ("chain_ratio", format!("{:.4}", ratio(chain_len, total))),
+            ("reorgs", self.reorg_depths.len().to_string()),
+            ("max_reorg_depth", self.reorg_depths.iter().max().copied().unwrap_or(0).to_string()),
+            ("finality_violations", self.finality_violations.to_string()),
+            ("stitch_activations", self.stitch_activations.to_string()),
+            ("merge_depth_violations", self.merge_depth_violations.to_string()),
+            ("kosherized_merges", self.kosherized_merges.to_string()),
+            ("oversized_mergesets", self.oversized_mergesets.to_string()),
+            ("stale_timestamps", self.stale_timestamps.to_string()),
+            ("outdated_versions", self.outdated_versions.to_string()),
+            ("rescued_reds", self.rescued_reds.to_string()),
+            ("mean_rescue_delay", format!("{:.2}", mean(&self.rescue_delays))),
+            ("orphaned_reds", self.orphaned_reds.to_string()),
+            ("orphaned_work", self.orphaned_work.to_string()),
+        ]
+    }
+
+    pub fn report(&self, dag: &ToyDag) -> String {
+        let mut out = String::from("=== Run Summary ===\n");
+        for (metric, value) in self.summary(dag) {
+            let _ = writeln!(out, "{:<22} {}", metric, value);
+        }
+        out.push_str("===================\n");
+        out
+    }
+
+    // Blocks per anticone size; the index is the size. Genesis and blocks
+    // inserted while tracking was off are left out.
+    pub fn anticone_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![0; self.anticones.values().max().map_or(0, |&m| m + 1)];
+        for &size in self.anticones.values() {
+            histogram[size] += 1;
+        }
+        histogram
+    }
+
+    // Smallest k that would have colored at most `max_red_rate` of this run's
+    // blocks red, taking a block to stay blue while its anticone is at most k
+    pub fn recommend_k(&self, max_red_rate: f64) -> usize {
+        let allowed = (max_red_rate * self.anticones.len() as f64).floor() as usize;
+        let mut above = self.anticones.len();
+        for (k, blocks) in self.anticone_histogram().into_iter().enumerate() {
+            above -= blocks;
+            if above <= allowed {
+                return k;
+            }
+        }
+        0
+    }
+
+    pub fn anticone_report(&self, dag: &ToyDag, max_red_rate: f64) -> String {
+        const BAR: usize = 40;
+        let total = self.anticones.len();
+        let histogram = self.anticone_histogram();
+        let tallest = histogram.iter().max().copied().unwrap_or(0).max(1);
+        let mut out = String::new();
+        let _ = writeln!(out, "📊 Anticone sizes over {} blocks", total);
+        let _ = writeln!(out, "{:>8} {:>8} {:>7} {:>7}", "anticone", "blocks", "share", "red@k");
+        let mut above = total;
+        for (size, &blocks) in histogram.iter().enumerate() {
+            above -= blocks;
+            if blocks == 0 {
+                continue;
+            }
+            // red@k: share that would be red with k set to this size
+            let _ = writeln!(
+                out,
+                "{:>8} {:>8} {:>6.1}% {:>6.1}% {}",
+                size,
+                blocks,
+                100.0 * ratio(blocks, total),
+                100.0 * ratio(above, total),
+                "█".repeat((blocks * BAR).div_ceil(tallest))
+            );
+        }
+        let k = self.recommend_k(max_red_rate);
+        let red = self.anticones.values().filter(|&&a| a > k).count();
+        let _ = writeln!(
+            out,
+            "🎯 k = {} keeps the red rate at {:.2}% (target {:.2}%); this run used k = {}",
+            k,
+            100.0 * ratio(red, total),
+            100.0 * max_red_rate,
+            dag.k_mode.k()
+        );
+        out
+    }
+
+    pub fn write_csv(&self, dag: &ToyDag, path: &Path) -> io::Result<()> {
+        let mut out = String::from("metric,value\n");
+        for (metric, value) in self.summary(dag) {
+            let _ = writeln!(out, "{},{}", metric, value);
+        }
+        fs::write(path, out)
+    }
+}
+
+pub fn mean(values: &[usize]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<usize>() as f64 / values.len() as f64
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommended_k_leaves_at_most_the_target_share_red() {
+        // 100 blocks: 90 saw no concurrency, 8 saw one block, 1 saw three, 1 saw seven
+        let mut sizes = vec![0; 90];
+        sizes.extend([1; 8]);
+        sizes.extend([3, 7]);
+        let stats = Stats { anticones: sizes.into_iter().enumerate().map(|(id, a)| (id as u64, a)).collect(), ..Stats::default() };
+
+        assert_eq!(stats.anticone_histogram(), vec![90, 8, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(stats.recommend_k(0.0), 7);
+        assert_eq!(stats.recommend_k(0.01), 3);
+        assert_eq!(stats.recommend_k(0.02), 1);
+        assert_eq!(stats.recommend_k(0.10), 0);
+        assert_eq!(Stats::default().recommend_k(0.01), 0);
+    }
+
+    #[test]
+    fn tracked_anticones_grow_as_concurrent_blocks_arrive() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.track_anticones = true;
+        dag.create_block(vec![0]).unwrap(); // 1
+        dag.create_block(vec![0]).unwrap(); // 2, concurrent with 1
+        dag.create_block(vec![1]).unwrap(); // 3, concurrent with 2
+        dag.create_block(vec![2, 3]).unwrap(); // 4, sees everything
+        assert_eq!(dag.stats.anticones, HashMap::from([(1, 1), (2, 2), (3, 1), (4, 0)]));
+        assert_eq!(dag.stats.recommend_k(0.0), 2);
+    }
+
+    // Three siblings, then a block merging them: 2 and 3 arrive with one and
+    // two blues beside them, and 1 and 4 with none
+    fn siblings_merged() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for _ in 0..3 {
+            dag.create_block(vec![0]).unwrap();
+        }
+        dag.create_block(vec![1, 2, 3]).unwrap();
+        dag
+    }
+
+    #[test]
+    fn mean_anticone_counts_the_blues_beside_each_new_block() {
+        let dag = siblings_merged();
+        assert_eq!(dag.stats.anticone_sizes, vec![0, 1, 2, 0]);
+        let summary = dag.stats.summary(&dag);
+        assert!(summary.contains(&("mean_anticone", "0.75".to_string())));
+    }
+
+    // The per-block metrics carry the same anticone counts the summary averages
+    #[test]
+    fn metrics_rows_carry_the_anticones() {
+        let dag = siblings_merged();
+        let rows = &dag.stats.timeline;
+        let anticones: Vec<usize> = rows.iter().map(|r| r.anticone as usize).collect();
+        assert_eq!(anticones, dag.stats.anticone_sizes);
+
+        let csv = crate::metrics::to_csv(rows);
+        let column = csv.lines().next().unwrap().split(',').position(|c| c == "anticone").unwrap();
+        let column: Vec<&str> = csv.lines().skip(1).map(|l| l.split(',').nth(column).unwrap()).collect();
+        assert_eq!(column, vec!["0", "1", "2", "0"]);
+    }
+}
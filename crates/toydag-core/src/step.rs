@@ -0,0 +1,176 @@
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use crate::{Color, ToyDag};
+
+// A merged block as the new block's GHOSTDAG weighs it: how many blues of
+// the new block's blue set sit in its anticone. Over k, or beside a blue that
+// already has k, and the new block colors it red.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub id: u64,
+    pub color: Color, // In the new block's view
+    pub blue_anticone: usize,
+}
+
+// One `create_block` laid open: what the block merged, how its color was
+// decided, and what it did to the virtual
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub block: u64,
+    pub parents: Vec<u64>,
+    pub selected_parent: u64,
+    pub mergeset: Vec<Candidate>, // Merged blocks besides the selected parent, by id
+    pub anticone: usize,          // What the coloring rule counted
+    pub k: usize,
+    pub color: Color,
+    pub blue_score: u64,
+    pub virtual_before: u64, // Virtual selected parent before and after the insertion
+    pub virtual_after: u64,
+    pub tips_before: usize,
+    pub tips_after: usize,
+    pub reorg_depth: Option<usize>, // Chain blocks the insertion dropped, when it reorged
+    pub finalized: Option<u64>,     // New finality point, when it moved
+}
+
+// Handed every traced insertion, right after it. A stepper that waits for
+// input before returning pauses the caller between blocks.
+pub trait Stepper {
+    fn on_step(&mut self, dag: &ToyDag, step: &Step);
+}
+
+pub type SharedStepper = Arc<Mutex<dyn Stepper + Send>>;
+
+// What a step knows before the block goes in
+pub(crate) struct Pending {
+    selected_parent: u64,
+    mergeset: Vec<Candidate>,
+    virtual_before: u64,
+    tips_before: usize,
+    reorgs_before: usize,
+    finality_before: u64,
+}
+
+impl ToyDag {
+    // Weighs every merged block the way the new block's GHOSTDAG will
+    pub(crate) fn pending_step(&self, parent_ids: &[u64]) -> Pending {
+        let scores = self.parent_scores(parent_ids);
+        let ghostdag = &scores.ghostdag;
+        let mut mergeset: Vec<Candidate> = scores
+            .mergeset
+            .iter()
+            .map(|&id| {
+                let color = if ghostdag.mergeset_blues.contains(&id) { Color::Blue } else { Color::Red };
+                Candidate { id, color, blue_anticone: ghostdag.blue_anticones[&id] }
+            })
+            .collect();
+        mergeset.sort_unstable_by_key(|c| c.id);
+        Pending {
+            selected_parent: scores.selected_parent,
+            mergeset,
+            virtual_before: self.selected_parent,
+            tips_before: self.tips.len(),
+            reorgs_before: self.stats.reorg_depths.len(),
+            finality_before: self.finality_point,
+        }
+    }
+
+    // Completes the step for `block`, just inserted, and hands it on
+    pub(crate) fn finish_step(&self, pending: Pending, block: u64, parents: Vec<u64>) {
+        let Some(stepper) = &self.stepper else { return };
+        let row = self.stats.timeline.last().expect("the block was just inserted");
+        let step = Step {
+            block,
+            parents,
+            selected_parent: pending.selected_parent,
+            mergeset: pending.mergeset,
+            anticone: row.anticone as usize,
+            k: row.k as usize,
+            color: self.blocks[&block].color,
+            blue_score: self.blocks[&block].blue_score,
+            virtual_before: pending.virtual_before,
+            virtual_after: self.selected_parent,
+            tips_before: pending.tips_before,
+            tips_after: self.tips.len(),
+            reorg_depth: self.stats.reorg_depths.get(pending.reorgs_before).copied(),
+            finalized: Some(self.finality_point).filter(|&f| f != pending.finality_before),
+        };
+        stepper.lock().unwrap().on_step(self, &step);
+    }
+}
+
+impl Step {
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "🔬 Block {} on parents {:?}", self.block, self.parents);
+        let _ = writeln!(out, "   selected parent {}, blue score {}", self.selected_parent, self.blue_score);
+        if self.mergeset.is_empty() {
+            let _ = writeln!(out, "   merges nothing besides its selected parent");
+        } else {
+            let _ = writeln!(out, "   {:>7} {:>6} {:>14}  (k = {})", "merges", "color", "blue anticone", self.k);
+            for c in &self.mergeset {
+                let verdict = if c.blue_anticone > self.k { "over k" } else { "within k" };
+                let _ = writeln!(out, "   {:>7} {:>6} {:>14}  {}", c.id, format!("{:?}", c.color), c.blue_anticone, verdict);
+            }
+        }
+        let why = match self.color {
+            Color::Blue => format!("{} blue blocks in its anticone, within k = {}", self.anticone, self.k),
+            Color::Red => format!("{} blue blocks in its anticone, over k = {}", self.anticone, self.k),
+        };
+        let _ = writeln!(out, "   colored {:?}: {}", self.color, why);
+        if self.virtual_after == self.virtual_before {
+            let _ = writeln!(out, "   virtual stays on {}", self.virtual_before);
+        } else {
+            let _ = writeln!(out, "   virtual moves {} → {}", self.virtual_before, self.virtual_after);
+        }
+        let _ = writeln!(out, "   tips {} → {}", self.tips_before, self.tips_after);
+        if let Some(depth) = self.reorg_depth {
+            let _ = writeln!(out, "   reorg dropped {} chain blocks", depth);
+        }
+        if let Some(f) = self.finalized {
+            let _ = writeln!(out, "   finality point now {}", f);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Collect(Vec<Step>);
+
+    impl Stepper for Collect {
+        fn on_step(&mut self, _dag: &ToyDag, step: &Step) {
+            self.0.push(step.clone());
+        }
+    }
+
+    // Two side branches off genesis, then a block merging both. With k = 0
+    // the second branch arrives with the first's blue in its anticone and is
+    // red, and the merge weighs it over k the same way.
+    #[test]
+    fn steps_show_the_mergeset_and_the_virtual_moving() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = crate::knight::KMode::Fixed(0);
+        let steps = Arc::new(Mutex::new(Collect(Vec::new())));
+        dag.stepper = Some(steps.clone());
+        let a = dag.create_block(vec![0]).unwrap();
+        let b = dag.create_block(vec![0]).unwrap();
+        let m = dag.create_block(vec![a, b]).unwrap();
+
+        let steps = &steps.lock().unwrap().0;
+        assert_eq!(steps.len(), 3);
+        assert_eq!((steps[0].virtual_before, steps[0].virtual_after), (0, a));
+        assert_eq!((steps[1].color, steps[1].anticone, steps[1].k), (Color::Red, 1, 0));
+        assert!(steps[1].report().contains("colored Red: 1 blue blocks in its anticone, over k = 0"));
+        let merge = &steps[2];
+        assert_eq!(merge.block, m);
+        assert_eq!(merge.selected_parent, a);
+        assert_eq!(merge.mergeset, vec![Candidate { id: b, color: Color::Red, blue_anticone: 1 }]);
+        assert_eq!((merge.virtual_after, merge.tips_before, merge.tips_after), (m, 2, 1));
+        assert!(merge.report().contains("over k"));
+        assert_eq!((merge.color, merge.anticone), (Color::Blue, 0));
+    }
+}
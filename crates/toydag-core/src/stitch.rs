@@ -0,0 +1,283 @@
+use crate::ToyDag;
+
+// How StitchBot decides when the DAG is "too fractured"
+#[derive(Debug, Clone)]
+pub enum StitchMode {
+    Fixed(usize),
+    Adaptive(AdaptiveStitch),
+}
+
+impl StitchMode {
+    pub fn threshold(&self) -> usize {
+        match self {
+            StitchMode::Fixed(threshold) => *threshold,
+            StitchMode::Adaptive(adaptive) => adaptive.threshold(),
+        }
+    }
+
+    // Feed one observed merge (tip age in blocks) plus current mergeset headroom
+    pub fn observe(&mut self, merge_latency: usize, headroom: usize) {
+        if let StitchMode::Adaptive(adaptive) = self {
+            adaptive.observe(merge_latency, headroom);
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StitchMode::Fixed(_) => "fixed",
+            StitchMode::Adaptive(_) => "adaptive",
+        }
+    }
+}
+
+// PID-like controller: slow merges or shrinking headroom pull the threshold down,
+// fast merges let it drift back up toward the base threshold
+#[derive(Debug, Clone)]
+pub struct AdaptiveStitch {
+    pub base_threshold: f64,
+    pub target_latency: f64, // Desired blocks between becoming a tip and being merged
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub min_threshold: usize,
+    pub max_threshold: usize,
+    latency_ewma: f64,
+    integral: f64,
+    prev_error: f64,
+    threshold: f64,
+}
+
+impl AdaptiveStitch {
+    pub fn new(base_threshold: usize, target_latency: f64) -> Self {
+        AdaptiveStitch {
+            base_threshold: base_threshold as f64,
+            target_latency,
+            kp: 0.5,
+            ki: 0.05,
+            kd: 0.2,
+            min_threshold: 2,
+            max_threshold: base_threshold * 3,
+            latency_ewma: target_latency,
+            integral: 0.0,
+            prev_error: 0.0,
+            threshold: base_threshold as f64,
+        }
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold.round() as usize
+    }
+
+    pub fn observe(&mut self, merge_latency: usize, headroom: usize) {
+        self.latency_ewma = 0.8 * self.latency_ewma + 0.2 * merge_latency as f64;
+
+        let error = self.latency_ewma - self.target_latency;
+        self.integral = (self.integral + error).clamp(-50.0, 50.0); // anti-windup
+        let derivative = error - self.prev_error;
+        self.prev_error = error;
+
+        let adjust = self.kp * error + self.ki * self.integral + self.kd * derivative;
+
+        // Never let tips pile up past half the remaining mergeset room
+        let min = self.min_threshold as f64;
+        let max = (self.max_threshold as f64).min(headroom as f64 / 2.0).max(min);
+        self.threshold = (self.base_threshold - adjust).clamp(min, max);
+    }
+}
+
+// Which tips a stitch merges once the threshold trips, and whether it may fire
+// at all right now. The threshold itself stays with `StitchMode`.
+pub trait StitchPolicy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Tips to merge, or None to hold off; `tips` comes sorted by id
+    fn select(&mut self, dag: &ToyDag, tips: &[u64]) -> Option<Vec<u64>>;
+
+    // ToyDag is Clone, so its policy has to be too
+    fn clone_box(&self) -> Box<dyn StitchPolicy>;
+}
+
+impl Clone for Box<dyn StitchPolicy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// The original StitchBot: every tip, every time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeAll;
+
+impl StitchPolicy for MergeAll {
+    fn name(&self) -> &'static str {
+        "all"
+    }
+
+    fn select(&mut self, _dag: &ToyDag, tips: &[u64]) -> Option<Vec<u64>> {
+        Some(tips.to_vec())
+    }
+
+    fn clone_box(&self) -> Box<dyn StitchPolicy> {
+        Box::new(*self)
+    }
+}
+
+// The `n` heaviest tips by blue score, lowest id on ties
+#[derive(Debug, Clone, Copy)]
+pub struct TopByBlueScore {
+    pub n: usize,
+}
+
+impl StitchPolicy for TopByBlueScore {
+    fn name(&self) -> &'static str {
+        "top-n"
+    }
+
+    fn select(&mut self, dag: &ToyDag, tips: &[u64]) -> Option<Vec<u64>> {
+        let mut ranked = tips.to_vec();
+        ranked.sort_by_key(|&t| (std::cmp::Reverse(dag.blocks[&t].blue_score), t));
+        ranked.truncate(self.n);
+        Some(ranked)
+    }
+
+    fn clone_box(&self) -> Box<dyn StitchPolicy> {
+        Box::new(*self)
+    }
+}
+
+// Only tips that are mutually unreachable: a tip already in another tip's past
+// adds nothing to a merge. Heavier tips are kept first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Antichain;
+
+impl StitchPolicy for Antichain {
+    fn name(&self) -> &'static str {
+        "antichain"
+    }
+
+    fn select(&mut self, dag: &ToyDag, tips: &[u64]) -> Option<Vec<u64>> {
+        let mut ranked = tips.to_vec();
+        ranked.sort_by_key(|&t| (std::cmp::Reverse(dag.blocks[&t].blue_score), t));
+
+        let mut kept: Vec<u64> = Vec::new();
+        for t in ranked {
+            if kept.iter().all(|&k| !dag.in_past(t, k) && !dag.in_past(k, t)) {
+                kept.push(t);
+            }
+        }
+        Some(kept)
+    }
+
+    fn clone_box(&self) -> Box<dyn StitchPolicy> {
+        Box::new(*self)
+    }
+}
+
+// Wraps another policy and lets it fire at most once per `min_gap` blocks
+#[derive(Clone)]
+pub struct RateLimited {
+    pub inner: Box<dyn StitchPolicy>,
+    pub min_gap: u64,
+    last: Option<u64>, // DAG size at the last stitch
+}
+
+impl RateLimited {
+    pub fn new(inner: Box<dyn StitchPolicy>, min_gap: u64) -> Self {
+        RateLimited { inner, min_gap, last: None }
+    }
+}
+
+impl StitchPolicy for RateLimited {
+    fn name(&self) -> &'static str {
+        "rate-limited"
+    }
+
+    fn select(&mut self, dag: &ToyDag, tips: &[u64]) -> Option<Vec<u64>> {
+        let now = dag.blocks.len() as u64;
+        if self.last.is_some_and(|last| now - last < self.min_gap) {
+            return None;
+        }
+        let selected = self.inner.select(dag, tips)?;
+        self.last = Some(now);
+        Some(selected)
+    }
+
+    fn clone_box(&self) -> Box<dyn StitchPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Genesis with three side blocks, plus block 4 on top of block 1
+    fn fan() -> (ToyDag, Vec<u64>) {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for _ in 0..3 {
+            dag.create_block(vec![0]).unwrap();
+        }
+        dag.create_block(vec![1]).unwrap();
+        let mut tips: Vec<u64> = dag.tips().collect();
+        tips.sort_unstable();
+        (dag, tips)
+    }
+
+    #[test]
+    fn top_n_keeps_heaviest_tips() {
+        let (dag, tips) = fan();
+        assert_eq!(tips, vec![2, 3, 4]);
+        assert_eq!(TopByBlueScore { n: 2 }.select(&dag, &tips), Some(vec![4, 2]));
+    }
+
+    #[test]
+    fn antichain_drops_related_tips() {
+        let (dag, _) = fan();
+        assert_eq!(Antichain.select(&dag, &[1, 2, 4]), Some(vec![4, 2]));
+    }
+
+    #[test]
+    fn rate_limit_holds_off_between_stitches() {
+        let (mut dag, tips) = fan();
+        let mut policy = RateLimited::new(Box::new(MergeAll), 3);
+        assert!(policy.select(&dag, &tips).is_some());
+        dag.create_block(vec![4]).unwrap();
+        assert!(policy.select(&dag, &tips).is_none());
+        dag.create_block(vec![5]).unwrap();
+        dag.create_block(vec![6]).unwrap();
+        assert!(policy.select(&dag, &tips).is_some());
+    }
+
+    #[test]
+    fn oversized_mergeset_is_rejected() {
+        let (mut dag, tips) = fan();
+        dag.mergeset_limit = Some(1);
+        assert!(!dag.insert_block(5, tips, vec![], None));
+        assert_eq!(dag.stats.oversized_mergesets, 1);
+        assert!(dag.insert_block(5, vec![4, 2], vec![], None));
+    }
+
+    #[test]
+    fn stitch_splits_merges_under_the_limit() {
+        let (mut dag, _) = fan();
+        dag.mergeset_limit = Some(1);
+        dag.stitch_mode = StitchMode::Fixed(1);
+        dag.stitch_if_needed();
+
+        assert_eq!(dag.tips().collect::<Vec<_>>(), vec![6]);
+        assert_eq!(dag[5].parents(), &[4, 2]);
+        assert_eq!(dag[6].parents(), &[5, 3]);
+        assert_eq!(dag.stats.oversized_mergesets, 0);
+    }
+
+    #[test]
+    fn stitch_chains_merges_under_max_parents() {
+        let (mut dag, _) = fan();
+        dag.max_parents = Some(2);
+        dag.stitch_mode = StitchMode::Fixed(1);
+        dag.stitch_if_needed();
+
+        assert_eq!(dag.tips().collect::<Vec<_>>(), vec![6]);
+        assert!(dag.blocks().all(|b| b.parents().len() <= 2));
+    }
+}
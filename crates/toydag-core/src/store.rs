@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::events::{DagEvent, Observer};
+use crate::score::BlueWork;
+use crate::{BLOCK_VERSION, Block, Color, Genesis, NewBlock, ToyDag};
+
+// Where blocks live outside the DAG's working set. Stores only ever grow, and
+// `ids` comes back in insertion order, so parents always precede children.
+// A store makes a run durable and resumable; it does not bound memory, since
+// the `ToyDag` fed from it still holds every block.
+pub trait BlockStore {
+    fn put(&mut self, block: &Block) -> io::Result<()>;
+    fn get(&self, id: u64) -> io::Result<Option<Block>>;
+    fn ids(&self) -> Vec<u64>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// The plain in-memory store
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    blocks: HashMap<u64, Block>,
+    order: Vec<u64>,
+}
+
+impl BlockStore for MemoryStore {
+    fn put(&mut self, block: &Block) -> io::Result<()> {
+        if self.blocks.insert(block.id, block.clone()).is_none() {
+            self.order.push(block.id);
+        }
+        Ok(())
+    }
+
+    fn get(&self, id: u64) -> io::Result<Option<Block>> {
+        Ok(self.blocks.get(&id).cloned())
+    }
+
+    fn ids(&self) -> Vec<u64> {
+        self.order.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+}
+
+// Append-only file of length-prefixed binary records. Only an id → offset
+// index stays in memory; bodies are read back on demand. A record cut short
+// by a crash is dropped when the file is reopened.
+pub struct FileStore {
+    file: File,
+    index: HashMap<u64, u64>,
+    order: Vec<u64>,
+    end: u64, // Offset just past the last complete record
+}
+
+impl FileStore {
+    pub fn open(path: &Path) -> io::Result<FileStore> {
+        let file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut store = FileStore { file, index: HashMap::new(), order: Vec::new(), end: 0 };
+
+        let mut reader = BufReader::new(&store.file);
+        while let Some(record) = read_record(&mut reader)? {
+            let id = u64::from_le_bytes(record[..8].try_into().unwrap());
+            if store.index.insert(id, store.end).is_none() {
+                store.order.push(id);
+            }
+            store.end += 4 + record.len() as u64;
+        }
+        store.file.set_len(store.end)?; // Drop a torn tail
+        Ok(store)
+    }
+}
+
+impl BlockStore for FileStore {
+    fn put(&mut self, block: &Block) -> io::Result<()> {
+        if self.index.contains_key(&block.id) {
+            return Ok(());
+        }
+        let record = encode(block);
+        let mut bytes = Vec::with_capacity(4 + record.len());
+        bytes.extend((record.len() as u32).to_le_bytes());
+        bytes.extend(record);
+        self.file.write_all(&bytes)?;
+
+        self.index.insert(block.id, self.end);
+        self.order.push(block.id);
+        self.end += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn get(&self, id: u64) -> io::Result<Option<Block>> {
+        let Some(&offset) = self.index.get(&id) else {
+            return Ok(None);
+        };
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))?;
+        let record = read_record(&mut file)?.ok_or_else(|| invalid("record vanished"))?;
+        decode(&record).map(Some)
+    }
+
+    fn ids(&self) -> Vec<u64> {
+        self.order.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+}
+
+// Writes every new block through to a store as the DAG grows. Genesis is
+// never announced, so it goes in ahead of the first block to an empty store.
+pub struct Persister<S: BlockStore> {
+    pub store: S,
+    pub write_errors: usize,
+}
+
+impl<S: BlockStore> Persister<S> {
+    pub fn new(store: S) -> Self {
+        Persister { store, write_errors: 0 }
+    }
+}
+
+impl<S: BlockStore> Observer for Persister<S> {
+    fn on_event(&mut self, dag: &ToyDag, event: &DagEvent) {
+        let DagEvent::BlockAdded { id } = *event else {
+            return;
+        };
+        if self.store.is_empty() && self.store.put(&dag[dag.genesis()]).is_err() {
+            self.write_errors += 1;
+        }
+        if self.store.put(&dag[id]).is_err() {
+            self.write_errors += 1;
+        }
+    }
+}
+
+impl ToyDag {
+    // Rebuild a DAG from a store, e.g. to resume a run after a restart. Blocks
+    // go back in through the normal insertion path, so everything derived is
+    // recomputed under this DAG's rules. A parentless block is taken as the
+    // genesis; stores that never saw one get the default genesis.
+    pub fn from_store(store: &dyn BlockStore) -> io::Result<ToyDag> {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        for id in store.ids() {
+            let block = store.get(id)?.ok_or_else(|| invalid("indexed block is missing"))?;
+            if block.parents.is_empty() {
+                if dag.block_count() > 1 {
+                    return Err(invalid("genesis stored after other blocks"));
+                }
+                dag = ToyDag::with_genesis(Genesis { id, timestamp: block.timestamp, difficulty: block.work })
+                    .map_err(|e| invalid(&e.to_string()))?;
+                dag.verbose = false;
+                continue;
+            }
+            let Block { parents, txs, miner, timestamp, version, .. } = block;
+            dag.add_block(NewBlock { id, parents, txs, miner, timestamp, version }).map_err(|e| invalid(&e.to_string()))?;
+        }
+        Ok(dag)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// One length-prefixed record, or None at a clean or torn end of file
+fn read_record(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    if read_full(reader, &mut len)? < len.len() {
+        return Ok(None);
+    }
+    let mut record = vec![0u8; u32::from_le_bytes(len) as usize];
+    if read_full(reader, &mut record)? < record.len() {
+        return Ok(None);
+    }
+    Ok(Some(record))
+}
+
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+// Work is always written as u128, so files don't depend on `narrow-work`
+#[allow(clippy::useless_conversion)] // Only useless without `narrow-work`
+fn wide(work: BlueWork) -> u128 {
+    u128::from(work)
+}
+
+fn encode(block: &Block) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(block.id.to_le_bytes());
+    out.extend((block.parents.len() as u32).to_le_bytes());
+    for p in &block.parents {
+        out.extend(p.to_le_bytes());
+    }
+    out.push(matches!(block.color, Color::Red) as u8);
+    out.extend(block.blue_score.to_le_bytes());
+    out.extend(wide(block.work).to_le_bytes());
+    out.extend(wide(block.blue_work).to_le_bytes());
+    out.extend(block.past_size.to_le_bytes());
+    out.extend((block.topo_depth as u64).to_le_bytes());
+    out.extend(block.selected_parent.map_or(u64::MAX, |sp| sp).to_le_bytes());
+    out.extend((block.txs.len() as u32).to_le_bytes());
+    for tx in &block.txs {
+        out.extend(tx.to_le_bytes());
+    }
+    out.extend(block.miner.map_or(u64::MAX, u64::from).to_le_bytes());
+    out.extend(block.timestamp.to_le_bytes());
+    out.extend(block.version.to_le_bytes());
+    out
+}
+
+fn decode(record: &[u8]) -> io::Result<Block> {
+    let mut rest = record;
+    let mut take = |n: usize| -> io::Result<&[u8]> {
+        if rest.len() < n {
+            return Err(invalid("short record"));
+        }
+        let (head, tail) = rest.split_at(n);
+        rest = tail;
+        Ok(head)
+    };
+    macro_rules! read {
+        ($t:ty) => {
+            <$t>::from_le_bytes(take(size_of::<$t>())?.try_into().unwrap())
+        };
+    }
+
+    let id = read!(u64);
+    let parents = (0..read!(u32)).map(|_| Ok(read!(u64))).collect::<io::Result<Vec<u64>>>()?;
+    let color = if read!(u8) == 0 { Color::Blue } else { Color::Red };
+    let blue_score = read!(u64);
+    let work = BlueWork::try_from(read!(u128)).map_err(|_| invalid("work overflows this build's BlueWork"))?;
+    let blue_work = BlueWork::try_from(read!(u128)).map_err(|_| invalid("work overflows this build's BlueWork"))?;
+    let past_size = read!(u64);
+    let topo_depth = read!(u64) as usize;
+    let selected_parent = Some(read!(u64)).filter(|&sp| sp != u64::MAX);
+    let txs = (0..read!(u32)).map(|_| Ok(read!(u64))).collect::<io::Result<Vec<u64>>>()?;
+    let miner = Some(read!(u64)).filter(|&m| m != u64::MAX).map(|m| m as u32);
+    let timestamp = read!(u64);
+    // Records written before blocks had versions end here
+    let version = take(size_of::<u32>()).map_or(BLOCK_VERSION, |b| u32::from_le_bytes(b.try_into().unwrap()));
+
+    Ok(Block {
+        id,
+        parents,
+        color,
+        blue_score,
+        work,
+        blue_work,
+        past_size,
+        topo_depth,
+        selected_parent,
+        txs,
+        miner,
+        timestamp,
+        version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        dag.insert_block(3, vec![1, 2], vec![7, 8], Some(2));
+        dag
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("toydag-{}-{}.blocks", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn file_store_survives_reopening() {
+        let path = temp_path("reopen");
+        let dag = sample();
+        {
+            let mut store = FileStore::open(&path).unwrap();
+            for b in dag.blocks() {
+                store.put(b).unwrap();
+            }
+        }
+
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.len(), dag.block_count());
+        let block = store.get(3).unwrap().unwrap();
+        assert_eq!(block.parents(), &[1, 2]);
+        assert_eq!(block.txs(), &[7, 8]);
+        assert_eq!(block.miner(), Some(2));
+        assert_eq!(block.blue_score(), dag[3].blue_score());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn torn_tail_is_dropped() {
+        let path = temp_path("torn");
+        let mut store = FileStore::open(&path).unwrap();
+        let dag = sample();
+        store.put(&dag[0]).unwrap();
+        store.put(&dag[1]).unwrap();
+        drop(store);
+
+        let full = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new().write(true).open(&path).unwrap().set_len(full - 3).unwrap();
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.ids(), vec![0]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn resumed_dag_matches_the_original() {
+        let dag = sample();
+        let persister = std::sync::Arc::new(std::sync::Mutex::new(Persister::new(MemoryStore::default())));
+        let mut live = ToyDag::new();
+        live.verbose = false;
+        live.subscribe(persister.clone());
+        for id in 1..=3 {
+            let b = &dag[id];
+            live.insert_block(id, b.parents().to_vec(), b.txs().to_vec(), b.miner());
+        }
+
+        let resumed = ToyDag::from_store(&persister.lock().unwrap().store).unwrap();
+        assert_eq!(resumed.ordered_blocks(), live.ordered_blocks());
+        assert_eq!(resumed[3].blue_work(), live[3].blue_work());
+    }
+
+    #[test]
+    fn custom_genesis_is_restored() {
+        let mut dag = ToyDag::with_genesis(Genesis { id: 100, timestamp: 5_000, difficulty: 4 }).unwrap();
+        dag.verbose = false;
+        let first = dag.create_block(vec![100]).unwrap();
+        dag.create_block(vec![first]).unwrap();
+        assert_eq!(first, 101);
+        assert_eq!(dag[first].blue_work(), 4);
+
+        let mut store = MemoryStore::default();
+        for id in dag.ordered_blocks() {
+            store.put(&dag[id]).unwrap();
+        }
+        let resumed = ToyDag::from_store(&store).unwrap();
+        assert_eq!(resumed.genesis(), 100);
+        assert_eq!(resumed[100].timestamp(), 5_000);
+        assert_eq!(resumed.ordered_blocks(), dag.ordered_blocks());
+        assert_eq!(resumed.next_id(), dag.next_id());
+    }
+
+    #[test]
+    fn persister_writes_a_custom_genesis() {
+        let persister = std::sync::Arc::new(std::sync::Mutex::new(Persister::new(MemoryStore::default())));
+        let mut dag = ToyDag::with_genesis(Genesis { id: 100, timestamp: 5_000, difficulty: 4 }).unwrap();
+        dag.verbose = false;
+        dag.subscribe(persister.clone());
+        let first = dag.create_block(vec![100]).unwrap();
+        dag.create_block(vec![first]).unwrap();
+
+        let persister = persister.lock().unwrap();
+        assert_eq!(persister.store.ids(), vec![100, 101, 102]);
+        assert_eq!(persister.write_errors, 0);
+        let resumed = ToyDag::from_store(&persister.store).unwrap();
+        assert_eq!(resumed.genesis(), 100);
+        assert_eq!(resumed.ordered_blocks(), dag.ordered_blocks());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::score::BlueWork;
+use crate::{ParentScores, ToyDag};
+
+// Everything a miner needs to build the next block: the virtual's parents and
+// where a block on top of them would land. Stale once the tips move on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTemplate {
+    pub id: u64,
+    pub parents: Vec<u64>, // Selected parent first
+    pub selected_parent: u64,
+    pub blue_score: u64,
+    pub blue_work: BlueWork,
+    pub work: BlueWork,        // Difficulty the block would be mined at
+    pub order_position: usize, // Index in the total order, once it is the virtual's selected parent
+}
+
+impl ToyDag {
+    pub fn build_block_template(&self) -> BlockTemplate {
+        let parents = self.prune(&self.virtual_parents());
+        let ParentScores { selected_parent, blue_score, blue_work, past_size, .. } = self.parent_scores(&parents);
+        BlockTemplate {
+            id: self.next_id,
+            parents,
+            selected_parent,
+            blue_score,
+            blue_work,
+            work: self.next_work(selected_parent),
+            order_position: past_size as usize, // Its whole past comes first
+        }
+    }
+
+    // Mine a template into the DAG. False if its id has been taken since it
+    // was built, or the block is rejected like any other insert.
+    pub fn accept_template(&mut self, template: BlockTemplate, txs: Vec<u64>, miner: Option<u32>) -> bool {
+        self.insert_block(template.id, template.parents, txs, miner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_matches_the_accepted_block() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![1]).unwrap();
+
+        let template = dag.build_block_template();
+        assert_eq!(template.parents, vec![3, 2]);
+        assert!(dag.accept_template(template.clone(), vec![7], Some(1)));
+
+        let block = &dag[template.id];
+        assert_eq!(block.blue_score(), template.blue_score);
+        assert_eq!(block.blue_work(), template.blue_work);
+        assert_eq!(dag.selected_parent(), template.id);
+        assert_eq!(dag.ordered_blocks()[template.order_position], template.id);
+    }
+
+    #[test]
+    fn template_prunes_to_max_parents() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![1]).unwrap();
+        dag.max_parents = Some(2);
+
+        let template = dag.build_block_template();
+        assert_eq!(template.parents, vec![4, 2]);
+        assert!(!dag.insert_block(9, vec![4, 2, 3], vec![], None));
+        assert!(dag.accept_template(template, vec![], None));
+        assert_eq!(dag.tip_count(), 2); // Block 3 waits for a later block
+    }
+
+    #[test]
+    fn stale_template_is_refused() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let template = dag.build_block_template();
+        dag.create_block(vec![0]).unwrap();
+        assert!(!dag.accept_template(template, vec![], None));
+    }
+}
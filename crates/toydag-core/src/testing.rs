@@ -0,0 +1,220 @@
+// Property-test harness: proptest generators for random valid DAGs and the
+// GHOSTDAG invariants they should satisfy. Other crates get it through the
+// `testing` feature.
+use std::collections::HashSet;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use crate::{K, ToyDag};
+
+// Parent lists for blocks 1..=n in insertion order. Block i draws 1..=max_parents
+// parents from the ids before it, so every list is valid when it is inserted.
+// Anticones are unbounded — late blocks can ignore whole branches.
+pub fn arb_parents(max_blocks: usize, max_parents: usize) -> impl Strategy<Value = Vec<Vec<u64>>> {
+    vec(vec(any::<Index>(), 1..=max_parents), 0..=max_blocks).prop_map(|picks| {
+        picks
+            .into_iter()
+            .enumerate()
+            .map(|(i, indices)| {
+                let mut parents: Vec<u64> = indices.iter().map(|ix| ix.index(i + 1) as u64).collect();
+                parents.sort_unstable();
+                parents.dedup();
+                parents
+            })
+            .collect()
+    })
+}
+
+// Bounded-delay network: blocks are mined in rounds of 1..=max_width, and every
+// block references the whole previous round. A block's anticone is then just the
+// rest of its round, so honest DAGs from here never exceed `max_width - 1`.
+pub fn arb_rounds(max_rounds: usize, max_width: usize) -> impl Strategy<Value = Vec<Vec<u64>>> {
+    vec(1..=max_width, 0..=max_rounds).prop_map(|widths| {
+        let mut lists = Vec::new();
+        let mut previous = vec![0u64];
+        let mut next_id = 1;
+        for width in widths {
+            let round: Vec<u64> = (next_id..next_id + width as u64).collect();
+            lists.extend(round.iter().map(|_| previous.clone()));
+            next_id += width as u64;
+            previous = round;
+        }
+        lists
+    })
+}
+
+// Insert generated parent lists into a fresh DAG. StitchBot stays out of it, so
+// the topology is exactly what the generator produced.
+pub fn build(parents: &[Vec<u64>]) -> ToyDag {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    for list in parents {
+        dag.create_block(list.clone()).expect("generated parents are already in the DAG");
+    }
+    dag
+}
+
+pub fn genesis_in_every_past(dag: &ToyDag) -> Result<(), String> {
+    for block in dag.blocks() {
+        if !dag.past_cone(block.id).contains(&dag.genesis()) {
+            return Err(format!("genesis is not in the past of block {}", block.id));
+        }
+    }
+    Ok(())
+}
+
+// Every blue block sees at most `k` other blue blocks in its anticone
+pub fn blue_set_is_k_cluster(dag: &ToyDag, k: usize) -> Result<(), String> {
+    match dag.verify_blue_set_for(k).first() {
+        Some(v) => Err(format!(
+            "blue block {} has {} blue blocks in its anticone (k = {})",
+            v.block,
+            v.blue_anticone.len(),
+            k
+        )),
+        None => Ok(()),
+    }
+}
+
+// The total order lists every block once, each after all of its parents
+pub fn order_respects_topology(dag: &ToyDag) -> Result<(), String> {
+    let order = dag.ordered_blocks();
+    if order.len() != dag.block_count() {
+        return Err(format!("order has {} entries for {} blocks", order.len(), dag.block_count()));
+    }
+
+    let mut placed = HashSet::new();
+    for id in order {
+        if let Some(parent) = dag.blocks[&id].parents.iter().find(|p| !placed.contains(*p)) {
+            return Err(format!("block {} is ordered before its parent {}", id, parent));
+        }
+        if !placed.insert(id) {
+            return Err(format!("block {} is ordered twice", id));
+        }
+    }
+    Ok(())
+}
+
+// The tip set is exactly the blocks nothing else builds on
+pub fn tips_are_childless(dag: &ToyDag) -> Result<(), String> {
+    let referenced: HashSet<u64> = dag.blocks().flat_map(|b| b.parents.iter().copied()).collect();
+    let tips: HashSet<u64> = dag.tips().collect();
+    let expected: HashSet<u64> = dag.blocks().map(|b| b.id).filter(|id| !referenced.contains(id)).collect();
+    if tips != expected {
+        let mut wrong: Vec<u64> = tips.symmetric_difference(&expected).copied().collect();
+        wrong.sort_unstable();
+        return Err(format!("tip set disagrees with the DAG on blocks {:?}", wrong));
+    }
+    Ok(())
+}
+
+pub fn check_invariants(dag: &ToyDag) -> Result<(), String> {
+    genesis_in_every_past(dag)?;
+    tips_are_childless(dag)?;
+    blue_set_is_k_cluster(dag, K)?;
+    order_respects_topology(dag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::knight::{AdaptiveK, KMode};
+    use crate::{BLOCK_VERSION, NewBlock};
+
+    proptest! {
+        #[test]
+        fn any_dag_keeps_genesis_in_every_past(parents in arb_parents(60, 4)) {
+            let dag = build(&parents);
+            prop_assert_eq!(genesis_in_every_past(&dag), Ok(()));
+        }
+
+        #[test]
+        fn any_dag_keeps_tips_childless(parents in arb_parents(60, 4)) {
+            let dag = build(&parents);
+            prop_assert_eq!(tips_are_childless(&dag), Ok(()));
+        }
+
+        #[test]
+        fn any_dag_orders_parents_first(parents in arb_parents(60, 4)) {
+            let dag = build(&parents);
+            prop_assert_eq!(order_respects_topology(&dag), Ok(()));
+        }
+
+        #[test]
+        fn batch_insert_matches_one_at_a_time(parents in arb_rounds(12, K + 1), adaptive in any::<bool>()) {
+            let k_mode = if adaptive { KMode::Adaptive(AdaptiveK::new(2, 1.0)) } else { KMode::Fixed(K) };
+            let mut serial = ToyDag::new();
+            serial.verbose = false;
+            serial.k_mode = k_mode.clone();
+            for list in &parents {
+                serial.create_block(list.clone()).expect("generated parents are already in the DAG");
+            }
+            let mut batched = ToyDag::new();
+            batched.verbose = false;
+            batched.k_mode = k_mode;
+            let blocks = parents
+                .iter()
+                .enumerate()
+                .map(|(i, list)| {
+                    let id = i as u64 + 1;
+                    NewBlock { id, parents: list.clone(), txs: vec![], miner: None, timestamp: id, version: BLOCK_VERSION }
+                })
+                .collect();
+            prop_assert_eq!(batched.insert_batch(blocks), parents.len());
+            prop_assert_eq!(batched.ordered_blocks(), serial.ordered_blocks());
+            for b in serial.blocks() {
+                prop_assert_eq!(batched[b.id].blue_work, b.blue_work);
+                prop_assert_eq!(batched[b.id].color, b.color);
+            }
+        }
+
+        #[test]
+        fn bounded_delay_dag_satisfies_all_invariants(parents in arb_rounds(12, K + 1)) {
+            let dag = build(&parents);
+            prop_assert_eq!(check_invariants(&dag), Ok(()));
+        }
+
+        // The virtual keeps the blue set a k-cluster however wide the DAG
+        // gets, so this holds for unbounded anticones and small k too
+        #[test]
+        fn any_dag_is_a_k_cluster(parents in arb_parents(40, 4), k in 0..4usize) {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag.k_mode = KMode::Fixed(k);
+            for list in &parents {
+                dag.create_block(list.clone()).expect("generated parents are already in the DAG");
+            }
+            prop_assert_eq!(blue_set_is_k_cluster(&dag, k), Ok(()));
+        }
+    }
+
+    // Four siblings, then two blocks merging all four. Block 5's merge of
+    // three raises adaptive k to 3 before block 6 is colored, so block 6
+    // keeps all four siblings blue, batch or not.
+    #[test]
+    fn adaptive_batches_color_like_serial_inserts() {
+        let parents = [vec![0], vec![0], vec![0], vec![0], vec![1, 2, 3, 4], vec![1, 2, 3, 4]];
+        let fresh = || {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag.k_mode = KMode::Adaptive(AdaptiveK::new(2, 1.0));
+            dag
+        };
+        let mut serial = fresh();
+        for list in &parents {
+            serial.create_block(list.clone()).unwrap();
+        }
+        let mut batched = fresh();
+        let blocks = (1..=6u64)
+            .map(|id| NewBlock { id, parents: parents[id as usize - 1].clone(), txs: vec![], miner: None, timestamp: id, version: BLOCK_VERSION })
+            .collect();
+        assert_eq!(batched.insert_batch(blocks), 6);
+        assert_eq!(batched.ordered_blocks(), serial.ordered_blocks());
+        for b in serial.blocks() {
+            assert_eq!((batched[b.id].blue_score, batched[b.id].color), (b.blue_score, b.color), "block {}", b.id);
+        }
+        assert_eq!(batched[6].blue_score, 5);
+    }
+}
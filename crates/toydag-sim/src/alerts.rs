@@ -0,0 +1,249 @@
+use std::collections::{HashMap, VecDeque};
+
+use toydag_core::ToyDag;
+use toydag_core::events::{DagEvent, Observer};
+use toydag_core::log;
+
+const HYSTERESIS: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainQualityAlert {
+    pub at_block: u64, // Virtual selected parent when the alert fired
+    pub miner: u32,
+    pub share: f64,
+}
+
+// Online chain-quality monitor: watches the last `window` selected-chain blocks
+// and alerts once when a single miner's share crosses `threshold`. The alert
+// re-arms only after that miner drops `HYSTERESIS` below the threshold, so a
+// selected chain flapping between siblings doesn't spam alerts.
+//
+// The window is kept as the chain moves: a new selected parent only drops the
+// blocks above its fork with the old chain and adds the ones above it on the
+// new side, so a step costs the reorg's depth rather than the chain's length.
+pub struct ChainQualityDetector {
+    pub window: usize,
+    pub threshold: f64,
+    pub alerts: Vec<ChainQualityAlert>,
+    pub verbose: bool,
+    alerting: Vec<u32>,
+    tip: Option<u64>,                // Chain tip the window was last moved to
+    recent: VecDeque<(u64, u32)>,    // Attributed chain blocks and their miners, oldest first
+    counts: HashMap<u32, usize>,     // Blocks per miner in `recent`
+}
+
+impl ChainQualityDetector {
+    pub fn new(window: usize, threshold: f64) -> Self {
+        ChainQualityDetector {
+            window,
+            threshold,
+            alerts: Vec::new(),
+            verbose: true,
+            alerting: Vec::new(),
+            tip: None,
+            recent: VecDeque::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    // Share of each miner over the most recent attributed chain blocks, as of
+    // the last selected parent change
+    pub fn shares(&self) -> HashMap<u32, f64> {
+        if self.window == 0 || self.recent.len() < self.window {
+            return HashMap::new(); // Not enough history to judge yet
+        }
+        self.counts
+            .iter()
+            .map(|(&miner, &n)| (miner, n as f64 / self.recent.len() as f64))
+            .collect()
+    }
+
+    // Move the window from the last tip to `to`
+    fn advance(&mut self, dag: &ToyDag, to: u64) {
+        let fork = self.tip.map(|old| common_chain_ancestor(dag, old, to));
+        self.tip = Some(to);
+
+        // Blocks above the fork left the chain
+        let fork_height = fork.and_then(|f| dag.chain_height(f));
+        while let Some(&(id, miner)) = self.recent.back()
+            && fork_height.is_none_or(|h| dag.chain_height(id) > Some(h))
+        {
+            self.recent.pop_back();
+            self.forget(miner);
+        }
+
+        // Blocks above it on the new side joined, newest found first
+        let mut joined = Vec::new();
+        let mut current = Some(to);
+        while let Some(id) = current
+            && current != fork
+            && joined.len() < self.window
+        {
+            if let Some(miner) = dag[id].miner() {
+                joined.push((id, miner));
+            }
+            current = dag[id].selected_parent();
+        }
+        for &(id, miner) in joined.iter().rev() {
+            self.recent.push_back((id, miner));
+            *self.counts.entry(miner).or_default() += 1;
+        }
+        while self.recent.len() > self.window {
+            let (_, miner) = self.recent.pop_front().unwrap();
+            self.forget(miner);
+        }
+
+        // A reorg can leave the window short; refill it from below
+        let mut current = match self.recent.front() {
+            Some(&(oldest, _)) => dag[oldest].selected_parent(),
+            None => fork,
+        };
+        while let Some(id) = current
+            && self.recent.len() < self.window
+        {
+            if let Some(miner) = dag[id].miner() {
+                self.recent.push_front((id, miner));
+                *self.counts.entry(miner).or_default() += 1;
+            }
+            current = dag[id].selected_parent();
+        }
+    }
+
+    fn forget(&mut self, miner: u32) {
+        if let Some(n) = self.counts.get_mut(&miner) {
+            *n -= 1;
+            if *n == 0 {
+                self.counts.remove(&miner);
+            }
+        }
+    }
+}
+
+// Highest block on both selected chains
+fn common_chain_ancestor(dag: &ToyDag, mut a: u64, mut b: u64) -> u64 {
+    while a != b {
+        if dag.chain_height(a) >= dag.chain_height(b) {
+            a = dag[a].selected_parent().expect("chains meet at genesis");
+        } else {
+            b = dag[b].selected_parent().expect("chains meet at genesis");
+        }
+    }
+    a
+}
+
+impl Observer for ChainQualityDetector {
+    fn on_event(&mut self, dag: &ToyDag, event: &DagEvent) {
+        let DagEvent::SelectedParentChanged { to, .. } = *event else {
+            return;
+        };
+
+        self.advance(dag, to);
+        let shares = self.shares();
+        self.alerting.retain(|m| shares.get(m).is_some_and(|&s| s > self.threshold - HYSTERESIS));
+
+        let mut offenders: Vec<(u32, f64)> = shares
+            .into_iter()
+            .filter(|&(m, s)| s > self.threshold && !self.alerting.contains(&m))
+            .collect();
+        offenders.sort_by_key(|&(m, _)| m);
+
+        for (miner, share) in offenders {
+            if self.verbose {
+                log::warn(
+                    "quality",
+                    "🚨",
+                    &format!("Chain-quality alert at block {}: miner {} holds {:.0}% of the last {} chain blocks", to, miner, share * 100.0, self.window),
+                );
+            }
+            self.alerting.push(miner);
+            self.alerts.push(ChainQualityAlert { at_block: to, miner, share });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use toydag_core::{BLOCK_VERSION, NewBlock};
+
+    use super::*;
+    use crate::network::{Network, NetworkConfig};
+
+    fn mine(dag: &mut ToyDag, parent: u64, miner: u32) -> u64 {
+        let id = dag.next_id();
+        let block = NewBlock { id, parents: vec![parent], txs: vec![], miner: Some(miner), timestamp: id, version: BLOCK_VERSION };
+        dag.add_block(block).unwrap();
+        id
+    }
+
+    fn watched(window: usize, threshold: f64) -> (ToyDag, Arc<Mutex<ChainQualityDetector>>) {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let detector = Arc::new(Mutex::new(ChainQualityDetector { verbose: false, ..ChainQualityDetector::new(window, threshold) }));
+        dag.subscribe(detector.clone());
+        (dag, detector)
+    }
+
+    // What the window should hold, read off the whole selected chain
+    fn rebuilt_shares(dag: &ToyDag, window: usize) -> HashMap<u32, f64> {
+        let recent: Vec<u32> = dag.selected_chain().iter().rev().filter_map(|&id| dag[id].miner()).take(window).collect();
+        if recent.len() < window {
+            return HashMap::new();
+        }
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        for &m in &recent {
+            *counts.entry(m).or_default() += 1;
+        }
+        counts.into_iter().map(|(m, n)| (m, n as f64 / window as f64)).collect()
+    }
+
+    #[test]
+    fn alerts_once_while_over_and_rearms_below_the_hysteresis() {
+        let (mut dag, detector) = watched(4, 0.6);
+        let mut tip = dag.genesis();
+        let mut alerts_after = |dag: &mut ToyDag, miners: &[u32]| {
+            for &m in miners {
+                tip = mine(dag, tip, m);
+            }
+            detector.lock().unwrap().alerts.len()
+        };
+
+        assert_eq!(alerts_after(&mut dag, &[0, 1, 0, 1]), 0); // Half each
+        assert_eq!(alerts_after(&mut dag, &[0, 0]), 1); // 0 1 0 0: miner 0 at 75%
+        assert_eq!(alerts_after(&mut dag, &[0]), 1); // 1 0 0 0: still over, still quiet
+        assert_eq!(alerts_after(&mut dag, &[1, 1]), 1); // 0 0 1 1: back to half, re-armed
+        assert_eq!(alerts_after(&mut dag, &[0, 0, 0]), 2); // 1 0 0 0: over again
+
+        let detector = detector.lock().unwrap();
+        assert_eq!(detector.alerts[1], ChainQualityAlert { at_block: dag.selected_parent(), miner: 0, share: 0.75 });
+    }
+
+    #[test]
+    fn window_follows_the_chain_through_a_reorg() {
+        let (mut dag, detector) = watched(2, 0.9);
+        let a = mine(&mut dag, 0, 0);
+        mine(&mut dag, a, 0);
+        assert_eq!(detector.lock().unwrap().shares(), HashMap::from([(0, 1.0)]));
+
+        // A longer side chain from genesis takes over
+        let b = mine(&mut dag, 0, 1);
+        let b = mine(&mut dag, b, 2);
+        mine(&mut dag, b, 1);
+        assert_eq!(dag[dag.selected_parent()].miner(), Some(1));
+        assert_eq!(detector.lock().unwrap().shares(), rebuilt_shares(&dag, 2));
+        assert_eq!(detector.lock().unwrap().shares(), HashMap::from([(1, 0.5), (2, 0.5)]));
+    }
+
+    #[test]
+    fn window_matches_the_chain_under_network_reorgs() {
+        let (mut dag, detector) = watched(20, 0.5);
+        let config = NetworkConfig { hashrates: vec![0.4, 0.3, 0.3], latency_ms: 1500, ..NetworkConfig::default() };
+        let mut network = Network::new(config, 5);
+        for _ in 0..10 {
+            network.run(&mut dag, 30);
+            assert_eq!(detector.lock().unwrap().shares(), rebuilt_shares(&dag, 20));
+        }
+        assert!(!dag.stats.reorg_depths.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::score::depth_between;
+use toydag_core::{FINALITY_DEPTH, ToyDag};
+
+const GROUPS: usize = 2;
+const ATTACKER: u32 = GROUPS as u32; // Miner id of the attacker; the groups are 0 and 1
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Delivery {
+    at: u64,
+    to: usize,
+    id: u64,
+    parents: Vec<u64>,
+    miner: u32,
+}
+
+// Outcome of one balancing run
+#[derive(Debug, Clone, Default)]
+pub struct Balance {
+    pub agreement: f64,           // Fraction of ticks both groups had the same selected parent
+    pub mean_split: f64,          // Blue-score split between the groups' chains, averaged over ticks
+    pub max_split: u64,           // Deepest blue-score split between the groups' chains
+    pub first_final: Option<u64>, // Tick at which a shared chain block first reached FINALITY_DEPTH in both views
+    pub longest_stall: u64,       // Most ticks without the shared final prefix growing
+    pub released: usize,
+    pub withheld: usize, // Still unreleased at the end
+}
+
+// The honest network is split in two equal groups whose blocks reach each
+// other only after `latency_ms`. The attacker, holding `share` of the
+// hashrate, mines single-parent blocks on the lighter group's selected
+// parent and withholds them. Whenever both groups are about to agree on the
+// same selected parent, it releases one block to a single group so that
+// group's heaviest tip moves away again. Honest blocks and released ones are
+// relayed between the groups as usual.
+pub fn simulate(share: f64, latency_ms: u64, block_interval_ms: u64, blocks: u64, seed: u64) -> Balance {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut views: Vec<ToyDag> = (0..GROUPS)
+        .map(|_| {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag
+        })
+        .collect();
+    let mut in_flight: BinaryHeap<Reverse<Delivery>> = BinaryHeap::new();
+    let mut waiting: Vec<Vec<Delivery>> = vec![Vec::new(); GROUPS];
+    let mut stash: Vec<(u64, Vec<u64>, usize)> = Vec::new(); // Withheld block, parents, group it was aimed at
+    let mut result = Balance::default();
+
+    let mut agreed = 0;
+    let mut splits = 0;
+    let mut final_score = 0;
+    let mut last_advance = 0;
+    for tick in 0..blocks {
+        let now = tick * block_interval_ms;
+        while in_flight.peek().is_some_and(|Reverse(d)| d.at <= now) {
+            let Reverse(d) = in_flight.pop().unwrap();
+            let to = d.to;
+            waiting[to].push(d);
+            release_waiting(&mut views[to], &mut waiting[to]);
+        }
+
+        // Balance: if the groups agree, hand one group a heavier tip of its own
+        if views[0].selected_parent() == views[1].selected_parent()
+            && let Some(i) = (0..stash.len()).max_by_key(|&i| views[stash[i].2][stash[i].1[0]].past_size())
+        {
+            let (id, parents, target) = stash.swap_remove(i);
+            result.released += 1;
+            views[target].insert_block_at(id, parents.clone(), vec![], Some(ATTACKER), now);
+            in_flight.push(Reverse(Delivery { at: now + latency_ms, to: 1 - target, id, parents, miner: ATTACKER }));
+        }
+
+        let id = tick + 1;
+        if rng.gen_bool(share) {
+            let target = (0..GROUPS).min_by_key(|&g| views[g][views[g].selected_parent()].past_size()).unwrap();
+            let parents = vec![views[target].selected_parent()];
+            stash.push((id, parents, target));
+        } else {
+            let group = rng.gen_range(0..GROUPS);
+            let tips: Vec<u64> = views[group].tips().collect();
+            let parents: Vec<u64> = tips.choose_multiple(&mut rng, tips.len().min(3)).copied().collect();
+            views[group].insert_block_at(id, parents.clone(), vec![], Some(group as u32), now);
+            in_flight.push(Reverse(Delivery { at: now + latency_ms, to: 1 - group, id, parents, miner: group as u32 }));
+        }
+
+        // Measure the split, and how far up the shared chain both groups
+        // already count as final
+        let (a, b) = (&views[0], &views[1]);
+        if a.selected_parent() == b.selected_parent() {
+            agreed += 1;
+        }
+        let shared_score = a[shared_chain_tip(a, b)].blue_score();
+        let tip_scores = [a, b].map(|v| v[v.selected_parent()].blue_score());
+        let split = tip_scores.iter().map(|&s| depth_between(s, shared_score)).max().unwrap();
+        result.max_split = result.max_split.max(split);
+        splits += split;
+        let common_final = tip_scores.iter().map(|&s| s.saturating_sub(FINALITY_DEPTH)).min().unwrap().min(shared_score);
+        if common_final > final_score {
+            final_score = common_final;
+            result.first_final.get_or_insert(tick);
+            result.longest_stall = result.longest_stall.max(tick - last_advance);
+            last_advance = tick;
+        }
+    }
+    result.longest_stall = result.longest_stall.max(blocks - last_advance);
+    result.agreement = agreed as f64 / blocks.max(1) as f64;
+    result.mean_split = splits as f64 / blocks.max(1) as f64;
+    result.withheld = stash.len();
+    result
+}
+
+fn release_waiting(view: &mut ToyDag, waiting: &mut Vec<Delivery>) {
+    while let Some(i) = waiting.iter().position(|w| w.parents.iter().all(|p| view.contains(*p))) {
+        let d = waiting.swap_remove(i);
+        if !view.contains(d.id) {
+            view.insert_block_at(d.id, d.parents, vec![], Some(d.miner), d.at);
+        }
+    }
+}
+
+// Highest block on both views' selected chains. Chains are ordered by blue
+// score, so walk the higher one down until the two meet.
+fn shared_chain_tip(a: &ToyDag, b: &ToyDag) -> u64 {
+    let (mut x, mut y) = (a.selected_parent(), b.selected_parent());
+    while x != y {
+        let (sx, sy) = (a[x].blue_score(), b[y].blue_score());
+        if sx >= sy {
+            x = a[x].selected_parent().unwrap_or(x);
+        } else {
+            y = b[y].selected_parent().unwrap_or(y);
+        }
+    }
+    x
+}
+
+// Sweep the attacker's hashrate and report how long it holds finality back
+pub fn experiment(shares: &[f64], latency_ms: u64, block_interval_ms: u64, blocks: u64, seed: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "⚖️ Balancing attack: two honest groups {} ms apart, {} blocks every {} ms, finality depth {}, seed {}\n",
+        latency_ms, blocks, block_interval_ms, FINALITY_DEPTH, seed
+    );
+    let _ = writeln!(
+        out,
+        "{:>9} {:>9} {:>10} {:>9} {:>12} {:>13} {:>9} {:>9}",
+        "attacker", "agree", "mean split", "max split", "first final", "longest stall", "released", "withheld"
+    );
+    for &share in shares {
+        let b = simulate(share, latency_ms, block_interval_ms, blocks, seed);
+        let seconds = |ticks: u64| format!("{:.0} s", (ticks * block_interval_ms) as f64 / 1000.0);
+        let _ = writeln!(
+            out,
+            "{:>8.0}% {:>8.1}% {:>10.1} {:>9} {:>12} {:>13} {:>9} {:>9}",
+            share * 100.0,
+            b.agreement * 100.0,
+            b.mean_split,
+            b.max_split,
+            b.first_final.map_or("never".to_string(), seconds),
+            seconds(b.longest_stall),
+            b.released,
+            b.withheld
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Both views share 0 → 1, then each grows its own chain on top
+    #[test]
+    fn shared_chain_tip_is_where_the_chains_part() {
+        let mut views: Vec<ToyDag> = (0..2).map(|_| ToyDag::new()).collect();
+        for view in &mut views {
+            view.verbose = false;
+            view.insert_block(1, vec![0], vec![], None);
+        }
+        views[0].insert_block(2, vec![1], vec![], None);
+        views[0].insert_block(3, vec![2], vec![], None);
+        views[1].insert_block(4, vec![1], vec![], None);
+        assert_eq!(shared_chain_tip(&views[0], &views[1]), 1);
+        assert_eq!(shared_chain_tip(&views[1], &views[0]), 1);
+
+        views[1].insert_block(2, vec![1], vec![], None);
+        views[1].insert_block(3, vec![2], vec![], None);
+        assert_eq!(shared_chain_tip(&views[0], &views[1]), 3);
+    }
+
+    // Nothing can be final before the chain is FINALITY_DEPTH blue blocks
+    // deep, and honest groups alone get there about then
+    #[test]
+    fn honest_groups_reach_finality() {
+        let b = simulate(0.0, 2000, 1000, 200, 42);
+        assert_eq!((b.released, b.withheld), (0, 0));
+        let first = b.first_final.expect("finality within 200 blocks");
+        assert!((FINALITY_DEPTH..FINALITY_DEPTH + 10).contains(&first), "first final at tick {}", first);
+        assert!(b.max_split > 0 && b.mean_split > 0.0);
+    }
+
+    // 30% of 200 ticks is about 60 attacker blocks, nearly all of them
+    // handed out again as the groups keep coming back together
+    #[test]
+    fn the_attacker_releases_what_it_withheld() {
+        let b = simulate(0.3, 2000, 1000, 200, 42);
+        let mined = b.released + b.withheld;
+        assert!((40..80).contains(&mined), "{} attacker blocks", mined);
+        assert!(b.withheld < 5, "{} still withheld", b.withheld);
+        assert!(b.first_final.is_some_and(|t| t >= FINALITY_DEPTH));
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::{Color, STALE_WINDOW, ToyDag};
+
+// Grow a fork-heavy DAG with incremental virtual updates, and at a few
+// checkpoints time the old full-recomputation rule against it. Also checks that
+// both rules pick the same tip and that stored scores match a from-scratch count.
+pub fn benchmark_virtual(blocks: u64, checkpoints: u64, seed: u64) -> String {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let every = (blocks / checkpoints.max(1)).max(1);
+    let mut insert_time = Duration::ZERO;
+    let mut inserted = 0u64;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Virtual-state benchmark: {} blocks, seed {}\n", blocks, seed);
+    let _ = writeln!(
+        out,
+        "{:>8} {:>6} {:>18} {:>18} {:>10} {:>9}",
+        "blocks", "tips", "incremental/insert", "full recompute", "speedup", "agree"
+    );
+
+    for i in 1..=blocks {
+        let recent: Vec<u64> = (dag.next_id().saturating_sub(STALE_WINDOW)..dag.next_id()).collect();
+        let num_parents = rng.gen_range(1..=3);
+        let parents: Vec<u64> = recent.choose_multiple(&mut rng, num_parents).copied().collect();
+
+        let start = Instant::now();
+        dag.create_block(parents).expect("recent ids are all in the DAG");
+        if i.is_multiple_of(5) {
+            dag.stitch_if_needed();
+        }
+        insert_time += start.elapsed();
+        inserted += 1;
+
+        if i.is_multiple_of(every) || i == blocks {
+            let start = Instant::now();
+            let full = dag.heaviest_blue_tip_full();
+            let full_time = start.elapsed();
+
+            let per_insert = insert_time / inserted as u32;
+            let agree = full == dag.heaviest_blue_tip() && scores_match(&dag, dag.selected_parent());
+            let _ = writeln!(
+                out,
+                "{:>8} {:>6} {:>15.1} µs {:>15.3} ms {:>9.0}x {:>9}",
+                dag.block_count(),
+                dag.tip_count(),
+                per_insert.as_secs_f64() * 1e6,
+                full_time.as_secs_f64() * 1e3,
+                full_time.as_secs_f64() / per_insert.as_secs_f64().max(1e-9),
+                if agree { "yes" } else { "NO" }
+            );
+            insert_time = Duration::ZERO;
+            inserted = 0;
+        }
+    }
+    out
+}
+
+// Stored blue score / past size against a from-scratch walk of the past
+fn scores_match(dag: &ToyDag, id: u64) -> bool {
+    let Ok(past) = dag.past_set(id) else {
+        return false;
+    };
+    let blue = past
+        .iter()
+        .filter(|&&b| b != id && dag[b].color() == Color::Blue)
+        .count() as u64;
+    let block = &dag[id];
+    block.blue_score() == blue && block.past_size() == past.len() as u64 - 1
+}
@@ -0,0 +1,188 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+
+use rand::distributions::WeightedIndex;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use toydag_core::ToyDag;
+
+use crate::nodes::{Delivery, NodeSim, NodeSimConfig, NodeSimStats};
+use crate::replay::{self, LogEntry};
+
+const FORMAT: u32 = 1;
+
+// A node simulation stopped between two blocks, with everything it needs to
+// carry on exactly as if it never stopped: the clock, the RNG, every node's
+// view, the gossip in flight and the run's statistics. A view is kept as its
+// blocks in the order the node inserted them, which rebuilds it exactly.
+// Resuming one checkpoint under different settings branches the run into
+// what-if continuations.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    format: u32,
+    pub config: NodeSimConfig,
+    pub stats: NodeSimStats,
+    delays: Vec<Vec<Option<u64>>>,
+    hashrates: Vec<f64>,
+    rng: ChaCha12Rng,
+    next_id: u64,
+    views: Vec<Vec<LogEntry>>,
+    in_flight: Vec<Delivery>,
+    waiting: Vec<Vec<Delivery>>,
+}
+
+impl Checkpoint {
+    // Written next to `path` first and moved over it, so an interrupted save
+    // leaves the previous checkpoint whole
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        let partial = path.with_extension("partial");
+        fs::write(&partial, json).map_err(|e| format!("{}: {}", partial.display(), e))?;
+        fs::rename(&partial, path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Checkpoint, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let checkpoint: Checkpoint = serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if checkpoint.format != FORMAT {
+            return Err(format!("{}: checkpoint format {}, expected {}", path.display(), checkpoint.format, FORMAT));
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn mined(&self) -> u64 {
+        self.stats.mined
+    }
+}
+
+impl NodeSim {
+    pub fn checkpoint(&self) -> Result<Checkpoint, String> {
+        if self.sync_stats().is_some() {
+            return Err("runs with a syncing node can't be checkpointed".to_string());
+        }
+        Ok(Checkpoint {
+            format: FORMAT,
+            config: self.config.clone(),
+            stats: self.stats.clone(),
+            delays: self.delays.clone(),
+            hashrates: self.hashrates.clone(),
+            rng: self.rng.clone(),
+            next_id: self.next_id,
+            views: self.views.iter().map(arrivals).collect(),
+            in_flight: self.in_flight.iter().map(|Reverse(d)| d.clone()).collect(),
+            waiting: self.waiting.clone(),
+        })
+    }
+
+    pub fn resume(checkpoint: Checkpoint) -> Result<NodeSim, String> {
+        let miner_dist = WeightedIndex::new(&checkpoint.hashrates).map_err(|e| format!("hashrates: {}", e))?;
+        let mut views = Vec::with_capacity(checkpoint.views.len());
+        for entries in &checkpoint.views {
+            let mut view = ToyDag::new();
+            view.verbose = false;
+            for entry in entries {
+                replay::apply(&mut view, entry)?;
+            }
+            views.push(view);
+        }
+        Ok(NodeSim {
+            config: checkpoint.config,
+            stats: checkpoint.stats,
+            views,
+            delays: checkpoint.delays,
+            rng: checkpoint.rng,
+            hashrates: checkpoint.hashrates,
+            miner_dist,
+            in_flight: checkpoint.in_flight.into_iter().map(Reverse).collect(),
+            waiting: checkpoint.waiting,
+            sync: None,
+            next_id: checkpoint.next_id,
+        })
+    }
+
+    // Mine `blocks` more blocks, saving a checkpoint to `path` every `every`
+    // blocks, then drain everything in flight. How many were saved.
+    pub fn run_with_checkpoints(&mut self, blocks: u64, every: u64, path: &Path) -> Result<usize, String> {
+        let mut saved = 0;
+        for _ in 0..blocks {
+            self.step();
+            if every > 0 && self.stats.mined.is_multiple_of(every) {
+                self.checkpoint()?.save(path)?;
+                saved += 1;
+            }
+        }
+        self.deliver_until(u64::MAX);
+        Ok(saved)
+    }
+}
+
+// A view's blocks in the order they went in; the metrics timeline has a row
+// for every insertion
+fn arrivals(view: &ToyDag) -> Vec<LogEntry> {
+    view.stats
+        .timeline
+        .iter()
+        .map(|row| {
+            let block = &view[row.block];
+            LogEntry {
+                id: row.block,
+                parents: block.parents().to_vec(),
+                txs: block.txs().to_vec(),
+                miner: block.miner(),
+                timestamp: block.timestamp(),
+                version: block.version(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::{Shape, Topology};
+
+    fn sim() -> NodeSim {
+        let ring = Topology { name: "ring".to_string(), nodes: 4, shape: Shape::Ring, latency_ms: 400, bandwidth: 0, hashrates: vec![], edges: vec![] };
+        let config = NodeSimConfig { block_interval_ms: 300, ..NodeSimConfig::default() };
+        NodeSim::new(&ring, config, 11).unwrap()
+    }
+
+    // Saved at block 25 with gossip still in flight, reloaded from disk and
+    // carried on: every view and statistic ends where the unbroken run does
+    #[test]
+    fn resumed_run_matches_an_uninterrupted_one() {
+        let path = std::env::temp_dir().join(format!("toydag-checkpoint-{}.json", std::process::id()));
+        let mut whole = sim();
+        assert_eq!(whole.run_with_checkpoints(40, 25, &path), Ok(1));
+
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(checkpoint.mined(), 25);
+        assert!(!checkpoint.in_flight.is_empty());
+        let mut resumed = NodeSim::resume(checkpoint).unwrap();
+        resumed.run(15);
+
+        assert_eq!(resumed.stats.mined, whole.stats.mined);
+        assert_eq!(resumed.stats.tips, whole.stats.tips);
+        assert_eq!(resumed.stats.missing, whole.stats.missing);
+        for (a, b) in resumed.views.iter().zip(&whole.views) {
+            assert_eq!(a.ordered_blocks(), b.ordered_blocks());
+            assert_eq!(a.selected_parent(), b.selected_parent());
+            assert!(a.blocks().all(|block| b[block.id()].color() == block.color()));
+        }
+        assert_eq!(resumed.red_rate(), whole.red_rate());
+    }
+
+    #[test]
+    fn other_formats_are_refused() {
+        let path = std::env::temp_dir().join(format!("toydag-checkpoint-format-{}.json", std::process::id()));
+        let mut checkpoint = sim().checkpoint().unwrap();
+        checkpoint.format = FORMAT + 1;
+        checkpoint.save(&path).unwrap();
+        let err = Checkpoint::load(&path).err().unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(err.ends_with(&format!("checkpoint format {}, expected {}", FORMAT + 1, FORMAT)), "{}", err);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use toydag_core::ToyDag;
+use toydag_core::events::{DagEvent, Observer};
+use toydag_core::score::depth_between;
+
+// A transaction waiting for its confirmation depth
+struct Pending {
+    tx: u64,
+    carrier: u64,     // First block seen carrying it
+    blocks_at: u64,   // DAG size when it was included
+    included_at: u64, // Timestamp of the carrier
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Confirmation {
+    pub tx: u64,
+    pub blocks: u64, // Blocks added between inclusion and confirmation
+    pub time: u64,   // Timestamp units between the same two points
+}
+
+// Online confirmation-time tracker. A tx is confirmed once the chain block that
+// accepts it is `depth` blue score below the virtual; equivalently, once its
+// carrier is in the past of the chain block sitting at that depth.
+pub struct ConfirmationTracker {
+    pub depth: u64,
+    pub confirmed: Vec<Confirmation>,
+    pending: Vec<Pending>,
+    seen: HashSet<u64>, // Txs already tracked, so later copies don't restart the clock
+}
+
+impl ConfirmationTracker {
+    pub fn new(depth: u64) -> Self {
+        ConfirmationTracker { depth, confirmed: Vec::new(), pending: Vec::new(), seen: HashSet::new() }
+    }
+
+    // Highest selected-chain block at least `depth` blue score under the virtual
+    fn horizon(&self, dag: &ToyDag) -> Option<u64> {
+        let tip_score = dag[dag.selected_parent()].blue_score();
+        let mut current = Some(dag.selected_parent());
+        while let Some(id) = current {
+            let block = &dag[id];
+            if depth_between(tip_score, block.blue_score()) >= self.depth {
+                return Some(id);
+            }
+            current = block.selected_parent();
+        }
+        None
+    }
+
+    pub fn report(&self) -> String {
+        let mut blocks: Vec<u64> = self.confirmed.iter().map(|c| c.blocks).collect();
+        let mut time: Vec<u64> = self.confirmed.iter().map(|c| c.time).collect();
+        blocks.sort_unstable();
+        time.sort_unstable();
+
+        let mut out = format!(
+            "⏱ Confirmations at depth {}: {} confirmed, {} pending\n",
+            self.depth,
+            self.confirmed.len(),
+            self.pending.len()
+        );
+        if !self.confirmed.is_empty() {
+            let _ = writeln!(out, "  {:<8} {:>6} {:>6} {:>6} {:>6}", "", "p50", "p90", "p99", "max");
+            for (label, values) in [("blocks", &blocks), ("time", &time)] {
+                let _ = writeln!(
+                    out,
+                    "  {:<8} {:>6} {:>6} {:>6} {:>6}",
+                    label,
+                    percentile(values, 0.50),
+                    percentile(values, 0.90),
+                    percentile(values, 0.99),
+                    values[values.len() - 1]
+                );
+            }
+        }
+        out
+    }
+}
+
+impl Observer for ConfirmationTracker {
+    fn on_event(&mut self, dag: &ToyDag, event: &DagEvent) {
+        let DagEvent::BlockAdded { id } = *event else {
+            return;
+        };
+        let block = &dag[id];
+        let size = dag.block_count() as u64;
+        for &tx in block.txs() {
+            if self.seen.insert(tx) {
+                self.pending.push(Pending { tx, carrier: id, blocks_at: size, included_at: block.timestamp() });
+            }
+        }
+
+        let Some(horizon) = self.horizon(dag) else {
+            return;
+        };
+        let now = block.timestamp();
+        let (done, waiting): (Vec<Pending>, Vec<Pending>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|p| dag.is_ancestor(p.carrier, horizon) == Ok(true));
+        self.pending = waiting;
+        self.confirmed.extend(done.into_iter().map(|p| Confirmation {
+            tx: p.tx,
+            blocks: size - p.blocks_at,
+            time: now.saturating_sub(p.included_at),
+        }));
+    }
+}
+
+// Nearest-rank percentile of sorted values
+pub(crate) fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    // Block 1 carries tx 7 and each block after it adds one blue score on
+    // top; a second copy of the tx further up must not restart its clock
+    #[test]
+    fn confirms_at_exactly_the_depth() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let tracker = Arc::new(Mutex::new(ConfirmationTracker::new(3)));
+        dag.subscribe(tracker.clone());
+
+        let carrier = dag.create_block_with_txs(vec![0], vec![7]).unwrap();
+        let mut tip = carrier;
+        for _ in 0..2 {
+            tip = dag.create_block(vec![tip]).unwrap();
+        }
+        tip = dag.create_block_with_txs(vec![tip], vec![7]).unwrap();
+        assert_eq!(dag[tip].blue_score() - dag[carrier].blue_score(), 3);
+        {
+            let tracker = tracker.lock().unwrap();
+            assert_eq!(tracker.confirmed, vec![Confirmation { tx: 7, blocks: 3, time: 3 }]);
+            assert_eq!(tracker.pending.len(), 0);
+        }
+
+        // One short of the depth leaves the tx pending
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let tracker = Arc::new(Mutex::new(ConfirmationTracker::new(3)));
+        dag.subscribe(tracker.clone());
+        let carrier = dag.create_block_with_txs(vec![0], vec![7]).unwrap();
+        let tip = dag.create_block(vec![carrier]).unwrap();
+        dag.create_block(vec![tip]).unwrap();
+        let tracker = tracker.lock().unwrap();
+        assert!(tracker.confirmed.is_empty());
+        assert!(tracker.report().contains("0 confirmed, 1 pending"));
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let sorted: Vec<u64> = (1..=10).collect();
+        assert_eq!(percentile(&sorted, 0.50), 5);
+        assert_eq!(percentile(&sorted, 0.90), 9);
+        assert_eq!(percentile(&sorted, 0.99), 10);
+        assert_eq!(percentile(&sorted, 0.0), 1);
+    }
+}
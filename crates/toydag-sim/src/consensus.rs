@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::consensus::ConsensusProtocol;
+use toydag_core::{K, STALE_WINDOW, ToyDag};
+
+use crate::dual::{self, Engine};
+
+const SHOWN: usize = 10; // Disagreements listed in the report
+
+// Order one seeded, fork-heavy DAG under two protocols and report where their
+// pairwise verdicts differ. Pairs where one block is in the other's past must
+// agree under any sane protocol; only anticone pairs are up for debate.
+pub fn compare(first: &dyn ConsensusProtocol, second: &dyn ConsensusProtocol, blocks: u64, seed: u64) -> String {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    let mut rng = StdRng::seed_from_u64(seed);
+    for i in 1..=blocks {
+        let next = dag.next_id();
+        let recent: Vec<u64> = (next.saturating_sub(STALE_WINDOW)..next).collect();
+        let num_parents = if rng.gen_bool(0.3) { recent.len().min(2) } else { 1 };
+        let parents: Vec<u64> = recent.choose_multiple(&mut rng, num_parents).copied().collect();
+        dag.create_block(parents).expect("recent ids are all in the DAG");
+        if i.is_multiple_of(5) {
+            dag.stitch_if_needed();
+        }
+    }
+
+    let a = first.pairwise(&dag);
+    let b = second.pairwise(&dag);
+
+    let mut anticone_pairs = 0;
+    let mut undecided = 0; // Pairs the second protocol leaves unordered, e.g. orphans
+    let mut disagreements = Vec::new();
+    for (x, y, verdict) in a.pairs() {
+        if dag.is_ancestor(x, y) == Ok(false) && dag.is_ancestor(y, x) == Ok(false) {
+            anticone_pairs += 1;
+        }
+        match b.compare(x, y) {
+            None => undecided += 1,
+            Some(other) if other != verdict => disagreements.push((x, y, verdict)),
+            Some(_) => {}
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Consensus comparison: {} vs {} on {} blocks, seed {}\n",
+        first.name(),
+        second.name(),
+        dag.block_count(),
+        seed
+    );
+    let _ = writeln!(out, "  pairs             {}", a.len());
+    let _ = writeln!(out, "  anticone pairs    {}", anticone_pairs);
+    let _ = writeln!(out, "  agree             {}", a.len() - disagreements.len() - undecided);
+    let _ = writeln!(out, "  disagree          {}", disagreements.len());
+    if undecided > 0 {
+        let _ = writeln!(out, "  unordered         {} (by {})", undecided, second.name());
+    }
+
+    if !disagreements.is_empty() {
+        let _ = writeln!(out, "\nDisagreements (first {}):", SHOWN.min(disagreements.len()));
+        for &(x, y, verdict) in disagreements.iter().take(SHOWN) {
+            let (a_first, b_first) = if verdict == Ordering::Less { (x, y) } else { (y, x) };
+            let _ = writeln!(
+                out,
+                "  {:>5} vs {:<5}  {}: {} first  {}: {} first",
+                x,
+                y,
+                first.name(),
+                a_first,
+                second.name(),
+                b_first
+            );
+        }
+    }
+    out
+}
+
+// The same topologies under GHOSTDAG and the longest chain as blocks come
+// faster relative to the network delay. Throughput is counted blocks per
+// second: GHOSTDAG's blues, the longest chain's chain. An attacker mining
+// alone loses nothing to forks, so to outpace honest miners whose blocks
+// count at rate g it needs a share q with q > g(1 - q), i.e. q > g / (1 + g).
+pub fn chain_vs_dag(intervals_ms: &[u64], delay_ms: u64, blocks: u64, seed: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "⛓️  Longest chain vs GHOSTDAG: {} blocks per run, {} ms delay, seed {}\n", blocks, delay_ms, seed);
+    let _ = writeln!(
+        out,
+        "{:>9} {:>8} | {:>9} {:>7} | {:>9} {:>8} {:>7}",
+        "interval", "blocks/s", "dag bps", "attack", "chain bps", "orphaned", "attack"
+    );
+    for &interval_ms in intervals_ms {
+        let entries = dual::topology(blocks, interval_ms, delay_ms, seed);
+        let result = dual::run(Engine::Ghostdag(K), Engine::LongestChain, &entries).expect("generated topologies replay cleanly");
+        let (dag, chain) = result.engines;
+        let seconds = entries.last().map_or(0, |e| e.timestamp).max(1) as f64 / 1000.0;
+        let mined = entries.len().max(1) as f64;
+        // Genesis is in every counted set but was never mined
+        let dag_counted = dag.counted(&result.left).len().saturating_sub(1) as f64;
+        let chain_counted = chain.counted(&result.right).len().saturating_sub(1) as f64;
+        let threshold = |counted: f64| {
+            let g = counted / mined;
+            100.0 * g / (1.0 + g)
+        };
+        let _ = writeln!(
+            out,
+            "{:>6} ms {:>8.2} | {:>9.2} {:>6.1}% | {:>9.2} {:>7.1}% {:>6.1}%",
+            interval_ms,
+            mined / seconds,
+            dag_counted / seconds,
+            threshold(dag_counted),
+            chain_counted / seconds,
+            100.0 * (1.0 - chain_counted / mined),
+            threshold(chain_counted)
+        );
+    }
+    out
+}
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::ToyDag;
+use toydag_core::daa::Daa;
+use toydag_core::score::BlueWork;
+
+const ROWS_PER_PHASE: u64 = 5;
+
+// One stretch of constant total hashrate (work per ms)
+#[derive(Debug, Clone, Copy)]
+pub struct HashratePhase {
+    pub hashrate: f64,
+    pub blocks: u64,
+}
+
+// A sampled row: the averages over the blocks since the previous one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DaaSample {
+    pub block: u64,
+    pub hashrate: f64,
+    pub difficulty: BlueWork, // Work of the sampled block
+    pub interval_ms: f64,     // Mean time between blocks since the last sample
+    pub tips: usize,
+}
+
+// Mine through hashrate phases with the DAA on and watch difficulty chase the
+// target. Blocks are found as a Poisson process at hashrate / difficulty, and
+// miners only see blocks older than `delay_ms`, so the DAG keeps forking.
+// Each phase is sampled `ROWS_PER_PHASE` times.
+pub fn simulate(phases: &[HashratePhase], daa: &Daa, delay_ms: u64, seed: u64) -> Vec<DaaSample> {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag.daa = Some(daa.clone());
+    // Start at the difficulty the first phase should settle on
+    dag.block_work = phases.first().map_or(1.0, |p| p.hashrate * daa.target_interval as f64).round().max(1.0) as BlueWork;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut now = 0u64;
+    let mut samples = Vec::new();
+
+    for phase in phases {
+        let step = (phase.blocks / ROWS_PER_PHASE).max(1);
+        let mut since = now;
+        for i in 1..=phase.blocks {
+            let difficulty = daa.next_work(&dag, dag.selected_parent()) as f64;
+            let u: f64 = rng.gen_range(0.0..1.0);
+            let gap = -(1.0 - u).ln() * difficulty / phase.hashrate;
+            now += gap.round() as u64;
+
+            let parents = visible_tips(&dag, now.saturating_sub(delay_ms));
+            let id = dag.create_block_at(parents, now).expect("visible tips are in the DAG");
+
+            if i.is_multiple_of(step) {
+                samples.push(DaaSample {
+                    block: id,
+                    hashrate: phase.hashrate,
+                    difficulty: dag[id].work(),
+                    interval_ms: (now - since) as f64 / step as f64,
+                    tips: dag.tip_count(),
+                });
+                since = now;
+            }
+        }
+    }
+    samples
+}
+
+pub fn experiment(phases: &[HashratePhase], daa: Daa, delay_ms: u64, seed: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "DAA: target {} ms per blue block, window {} chain blocks, delay {} ms, seed {}\n",
+        daa.target_interval, daa.window, delay_ms, seed
+    );
+    let _ = writeln!(
+        out,
+        "{:>7} {:>9} {:>11} {:>14} {:>13}",
+        "block", "hashrate", "difficulty", "interval (ms)", "tips"
+    );
+    for s in simulate(phases, &daa, delay_ms, seed) {
+        let _ = writeln!(
+            out,
+            "{:>7} {:>9.2} {:>11} {:>14.0} {:>13}",
+            s.block, s.hashrate, s.difficulty, s.interval_ms, s.tips
+        );
+    }
+    out
+}
+
+// Tips of the DAG as it looked at `cutoff`: blocks too new to have arrived are
+// replaced by their parents until everything left is old enough to be seen
+pub(crate) fn visible_tips(dag: &ToyDag, cutoff: u64) -> Vec<u64> {
+    let mut visible = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut stack: Vec<u64> = dag.tips().collect();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        let block = &dag[id];
+        if block.timestamp() <= cutoff || block.parents().is_empty() {
+            visible.insert(id);
+        } else {
+            stack.extend(block.parents());
+        }
+    }
+
+    // Drop anything already under another visible block
+    let candidates: Vec<u64> = visible.iter().copied().collect();
+    let mut tips: Vec<u64> = candidates
+        .iter()
+        .copied()
+        .filter(|&a| !candidates.iter().any(|&b| b != a && dag.is_ancestor(a, b) == Ok(true)))
+        .collect();
+    tips.sort_unstable();
+    tips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hashrate quadruples between phases: the DAA starts each phase off
+    // target and ends it with difficulty up to match
+    #[test]
+    fn difficulty_follows_the_hashrate() {
+        let phases = [HashratePhase { hashrate: 1.0, blocks: 300 }, HashratePhase { hashrate: 4.0, blocks: 300 }];
+        let samples = simulate(&phases, &Daa::new(50, 100), 0, 3);
+        assert_eq!(samples.len(), 2 * ROWS_PER_PHASE as usize);
+        assert!(samples.iter().take(ROWS_PER_PHASE as usize).all(|s| s.hashrate == 1.0));
+        let (first, second) = (samples[ROWS_PER_PHASE as usize - 1], samples[samples.len() - 1]);
+        assert!(
+            second.difficulty as f64 > 2.5 * first.difficulty as f64,
+            "difficulty {} → {} for 4× the hashrate",
+            first.difficulty,
+            second.difficulty
+        );
+        assert!((50.0..200.0).contains(&second.interval_ms), "{} ms a block at the end", second.interval_ms);
+    }
+
+    // Block 2 is stamped too late to be seen at 15; miners then still build
+    // on block 1, which it would otherwise cover
+    #[test]
+    fn visible_tips_hide_blocks_newer_than_the_cutoff() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let a = dag.create_block_at(vec![0], 10).unwrap();
+        let b = dag.create_block_at(vec![a], 20).unwrap();
+        let c = dag.create_block_at(vec![0], 12).unwrap();
+        assert_eq!(visible_tips(&dag, 15), vec![a, c]);
+        assert_eq!(visible_tips(&dag, 20), vec![b, c]);
+        assert_eq!(visible_tips(&dag, 5), vec![0]);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::consensus::{ConsensusProtocol, LongestChain};
+use toydag_core::diff::DagDiff;
+use toydag_core::knight::KMode;
+use toydag_core::{BLOCK_VERSION, Color, K, ToyDag};
+
+use crate::ParentSelection;
+use crate::daa::visible_tips;
+use crate::replay::{self, LogEntry};
+
+const MAX_PARENTS: usize = 3;
+
+// A consensus engine to feed blocks into, written `ghostdag:15` (or just
+// `ghostdag` for the default k) or `longest-chain` on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Ghostdag(usize),
+    LongestChain,
+}
+
+impl Engine {
+    pub fn name(self) -> String {
+        match self {
+            Engine::Ghostdag(k) => format!("ghostdag k={}", k),
+            Engine::LongestChain => LongestChain.name().to_string(),
+        }
+    }
+
+    fn dag(self) -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        if let Engine::Ghostdag(k) = self {
+            dag.k_mode = KMode::Fixed(k);
+        }
+        dag
+    }
+
+    pub fn selected_chain(self, dag: &ToyDag) -> Vec<u64> {
+        match self {
+            Engine::Ghostdag(_) => dag.selected_chain(),
+            Engine::LongestChain => LongestChain.chain(dag),
+        }
+    }
+
+    // The ledger order. The longest chain orders only its chain blocks.
+    pub fn order(self, dag: &ToyDag) -> Vec<u64> {
+        match self {
+            Engine::Ghostdag(_) => dag.ordered_blocks(),
+            Engine::LongestChain => LongestChain.chain(dag),
+        }
+    }
+
+    // Blocks whose work counts: GHOSTDAG's blues, the longest chain's chain
+    pub fn counted(self, dag: &ToyDag) -> HashSet<u64> {
+        match self {
+            Engine::Ghostdag(_) => dag.blocks().filter(|b| b.color() == Color::Blue).map(|b| b.id()).collect(),
+            Engine::LongestChain => LongestChain.chain(dag).into_iter().collect(),
+        }
+    }
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (name, k) = s.split_once(':').unwrap_or((s, ""));
+        match name.trim() {
+            "ghostdag" if k.is_empty() => Ok(Engine::Ghostdag(K)),
+            "ghostdag" => k.trim().parse().map(Engine::Ghostdag).map_err(|_| format!("bad k in engine '{}'", s)),
+            "longest-chain" if k.is_empty() => Ok(Engine::LongestChain),
+            other => Err(format!("unknown engine '{}', expected ghostdag[:k] or longest-chain", other)),
+        }
+    }
+}
+
+// A block topology nobody's consensus shaped: blocks at random intervals on
+// random visible tips, so it can be handed to any engine as is
+pub fn topology(blocks: u64, interval_ms: u64, delay_ms: u64, seed: u64) -> Vec<LogEntry> {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut entries = Vec::with_capacity(blocks as usize);
+    let mut now = 0u64;
+    for _ in 0..blocks {
+        let u: f64 = rng.gen_range(0.0..1.0);
+        now += (-(1.0 - u).ln() * interval_ms as f64).round() as u64;
+        let tips = visible_tips(&dag, now.saturating_sub(delay_ms));
+        let parents = ParentSelection::Random.pick(&dag, &tips, MAX_PARENTS, &mut rng);
+        let entry = LogEntry { id: dag.next_id(), parents, txs: vec![], miner: None, timestamp: now, version: BLOCK_VERSION };
+        if replay::apply(&mut dag, &entry).expect("parents are always in the DAG") {
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+// Where two engines fed the same blocks in the same order part ways
+#[derive(Clone)]
+pub struct Dual {
+    pub engines: (Engine, Engine),
+    pub left: ToyDag,
+    pub right: ToyDag,
+    pub arrivals: usize,
+    pub tip_disagreements: usize,    // Arrivals after which the selected tips differed
+    pub first_tip_split: Option<u64>, // Block whose arrival first split them
+}
+
+pub fn run(left: Engine, right: Engine, entries: &[LogEntry]) -> Result<Dual, String> {
+    let mut dual = Dual {
+        engines: (left, right),
+        left: left.dag(),
+        right: right.dag(),
+        arrivals: 0,
+        tip_disagreements: 0,
+        first_tip_split: None,
+    };
+    for entry in entries {
+        replay::apply(&mut dual.left, entry)?;
+        replay::apply(&mut dual.right, entry)?;
+        dual.arrivals += 1;
+        if left.selected_chain(&dual.left).last() != right.selected_chain(&dual.right).last() {
+            dual.tip_disagreements += 1;
+            dual.first_tip_split.get_or_insert(entry.id);
+        }
+    }
+    Ok(dual)
+}
+
+// How far apart two total orders of the same blocks are
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderDiff {
+    pub shared_prefix: usize,
+    pub first_split: Option<(u64, u64)>, // Left's and right's block at the first differing position
+    pub moved: usize,                   // Shared blocks at a different position
+    pub max_shift: usize,
+    pub flipped_pairs: usize, // Shared pairs the two put the other way round
+    pub pairs: usize,
+}
+
+pub fn order_diff(left: &[u64], right: &[u64]) -> OrderDiff {
+    let shared_prefix = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+    let first_split = left.get(shared_prefix).zip(right.get(shared_prefix)).map(|(&l, &r)| (l, r));
+
+    let right_at: HashMap<u64, usize> = right.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let shared: Vec<(usize, usize)> = left.iter().enumerate().filter_map(|(i, id)| right_at.get(id).map(|&j| (i, j))).collect();
+    let mut diff = OrderDiff { shared_prefix, first_split, pairs: shared.len() * shared.len().saturating_sub(1) / 2, ..OrderDiff::default() };
+    for (n, &(i, j)) in shared.iter().enumerate() {
+        if i != j {
+            diff.moved += 1;
+            diff.max_shift = diff.max_shift.max(i.abs_diff(j));
+        }
+        // Shared is in left's order, so a pair is flipped when right has them descending
+        diff.flipped_pairs += shared[n + 1..].iter().filter(|&&(_, later)| later < j).count();
+    }
+    diff
+}
+
+impl Dual {
+    // The engines' views side by side in `DagDiff` terms. A block either
+    // engine refused is only on the other side; a shared block one engine
+    // counts and the other doesn't (red, or orphaned) is a color disagreement.
+    pub fn diff(&self) -> DagDiff {
+        let (left, right) = self.engines;
+        let only = |a: &ToyDag, b: &ToyDag| {
+            let mut ids: Vec<u64> = a.blocks().map(|x| x.id()).filter(|&id| b.get_block(id).is_err()).collect();
+            ids.sort_unstable();
+            ids
+        };
+        let left_counted = left.counted(&self.left);
+        let right_counted = right.counted(&self.right);
+        let mut color_disagreements: Vec<u64> = self
+            .left
+            .blocks()
+            .map(|b| b.id())
+            .filter(|&id| self.right.get_block(id).is_ok() && left_counted.contains(&id) != right_counted.contains(&id))
+            .collect();
+        color_disagreements.sort_unstable();
+
+        let left_chain = left.selected_chain(&self.left);
+        let right_chain = right.selected_chain(&self.right);
+        let shared = left_chain.iter().zip(&right_chain).take_while(|(l, r)| l == r).count();
+        DagDiff {
+            only_left: only(&self.left, &self.right),
+            only_right: only(&self.right, &self.left),
+            color_disagreements,
+            divergence: left_chain[shared - 1],
+            left_chain: left_chain[shared..].to_vec(),
+            right_chain: right_chain[shared..].to_vec(),
+        }
+    }
+
+    pub fn report(&self, limit: usize) -> String {
+        let (left, right) = self.engines;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "⚖️  Dual consensus: {} vs {} on {} arrivals, same parents in the same order\n",
+            left.name(),
+            right.name(),
+            self.arrivals
+        );
+        let _ = writeln!(
+            out,
+            "  {:<20}: after {} of {} arrivals{}",
+            "Selected tips differ",
+            self.tip_disagreements,
+            self.arrivals,
+            self.first_tip_split.map_or(String::new(), |id| format!(", first when block {} arrived", id))
+        );
+        let diff = self.diff();
+        if diff.is_empty() {
+            let _ = writeln!(out, "  Same blocks, same colors, same selected chain");
+        } else {
+            // The diff's own lines, under this report's header
+            for line in diff.report(limit).lines().skip(1) {
+                let _ = writeln!(out, "{}", line);
+            }
+        }
+
+        let order = order_diff(&left.order(&self.left), &right.order(&self.right));
+        let _ = writeln!(out, "  {:<20}: {} blocks", "Orders agree for", order.shared_prefix);
+        if let Some((l, r)) = order.first_split {
+            let _ = writeln!(out, "  {:<20}: block {} vs block {}", "First order split", l, r);
+        }
+        let _ = writeln!(out, "  {:<20}: {} (at most {} places)", "Blocks moved", order.moved, order.max_shift);
+        let _ = writeln!(
+            out,
+            "  {:<20}: {} of {} ({:.2}%)",
+            "Pairs flipped",
+            order.flipped_pairs,
+            order.pairs,
+            100.0 * order.flipped_pairs as f64 / order.pairs.max(1) as f64
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engines_parse_from_the_command_line() {
+        assert_eq!("ghostdag:3".parse(), Ok(Engine::Ghostdag(3)));
+        assert_eq!("ghostdag".parse(), Ok(Engine::Ghostdag(K)));
+        assert_eq!("longest-chain".parse(), Ok(Engine::LongestChain));
+        assert!("ghostdag:x".parse::<Engine>().is_err());
+        assert!("longest-chain:2".parse::<Engine>().is_err());
+    }
+
+    // Blocks four delays wide: a small k has to leave some of them red that a
+    // large k keeps blue, and the two settle on different chains
+    #[test]
+    fn two_ks_part_ways_on_a_wide_dag() {
+        let entries = topology(200, 1000, 4000, 42);
+        let dual = run(Engine::Ghostdag(18), Engine::Ghostdag(3), &entries).unwrap();
+        let reds = |dag: &ToyDag| dag.blocks().filter(|b| b.color() == Color::Red).count();
+        assert!(reds(&dual.left) < reds(&dual.right));
+        let diff = dual.diff();
+        assert!(!diff.color_disagreements.is_empty());
+        assert!(diff.color_disagreements.iter().any(|&id| dual.left[id].color() == Color::Blue));
+        assert!(dual.first_tip_split.is_some());
+
+        let same = run(Engine::Ghostdag(3), Engine::Ghostdag(3), &entries).unwrap();
+        assert!(same.diff().is_empty());
+        assert_eq!(same.tip_disagreements, 0);
+    }
+
+    #[test]
+    fn order_diff_counts_moves_and_flipped_pairs() {
+        let diff = order_diff(&[0, 1, 2, 3], &[0, 2, 1, 3]);
+        assert_eq!(diff, OrderDiff { shared_prefix: 1, first_split: Some((1, 2)), moved: 2, max_shift: 1, flipped_pairs: 1, pairs: 6 });
+    }
+}
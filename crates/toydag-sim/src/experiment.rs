@@ -0,0 +1,241 @@
+use std::fmt::Write as _;
+
+use toydag_core::knight::KMode;
+use toydag_core::stats::mean;
+use toydag_core::{Color, ToyDag};
+
+use crate::network::{Network, NetworkConfig};
+use crate::quality;
+
+// Parameter grid for a sweep; every combination runs once per seed
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub ks: Vec<usize>,
+    pub latencies_ms: Vec<u64>,
+    pub bps: Vec<f64>, // Block rates, in blocks per second
+    pub miners: Vec<usize>,
+    pub adversary_share: f64, // Hashrate fraction held by miner 0, the adversary; 0 for none
+    pub blocks: u64,
+    pub seeds: u64,
+    pub base_seed: u64,
+}
+
+// One grid cell, averaged over its seeds
+#[derive(Debug, Clone)]
+pub struct CellResult {
+    pub k: usize,
+    pub latency_ms: u64,
+    pub bps: f64,
+    pub miners: usize,
+    pub runs: u64,
+    pub blue_ratio: f64,
+    pub mean_tips: f64,
+    pub chain_ratio: f64,
+    pub reorgs: f64,
+    pub max_reorg_depth: f64,
+    pub chain_quality: f64,
+    pub adversary_revenue: f64,
+}
+
+const COLUMNS: [&str; 12] = [
+    "k",
+    "latency_ms",
+    "bps",
+    "miners",
+    "runs",
+    "blue_ratio",
+    "mean_tips",
+    "chain_ratio",
+    "reorgs",
+    "max_reorg_depth",
+    "chain_quality",
+    "adversary_revenue",
+];
+
+// Per-run numbers that get averaged into a cell
+struct RunMetrics {
+    blue_ratio: f64,
+    mean_tips: f64,
+    chain_ratio: f64,
+    reorgs: f64,
+    max_reorg_depth: f64,
+    chain_quality: f64,
+    adversary_revenue: f64,
+}
+
+pub fn run(grid: &Grid) -> Vec<CellResult> {
+    let mut results = Vec::new();
+    for &k in &grid.ks {
+        for &latency_ms in &grid.latencies_ms {
+            for &bps in &grid.bps {
+                for &miners in &grid.miners {
+                    let runs: Vec<RunMetrics> = (0..grid.seeds)
+                        .map(|i| run_once(k, latency_ms, bps, miners, grid.adversary_share, grid.blocks, grid.base_seed + i))
+                        .collect();
+                    let avg = |f: fn(&RunMetrics) -> f64| runs.iter().map(f).sum::<f64>() / runs.len().max(1) as f64;
+                    results.push(CellResult {
+                        k,
+                        latency_ms,
+                        bps,
+                        miners,
+                        runs: grid.seeds,
+                        blue_ratio: avg(|r| r.blue_ratio),
+                        mean_tips: avg(|r| r.mean_tips),
+                        chain_ratio: avg(|r| r.chain_ratio),
+                        reorgs: avg(|r| r.reorgs),
+                        max_reorg_depth: avg(|r| r.max_reorg_depth),
+                        chain_quality: avg(|r| r.chain_quality),
+                        adversary_revenue: avg(|r| r.adversary_revenue),
+                    });
+                }
+            }
+        }
+    }
+    results
+}
+
+fn run_once(k: usize, latency_ms: u64, bps: f64, miners: usize, adversary_share: f64, blocks: u64, seed: u64) -> RunMetrics {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag.k_mode = KMode::Fixed(k);
+
+    let config = NetworkConfig {
+        block_interval_ms: (1000.0 / bps).round().max(1.0) as u64,
+        latency_ms,
+        hashrates: hashrates(miners.max(1), adversary_share),
+        ..NetworkConfig::default()
+    };
+    Network::new(config, seed).run(&mut dag, blocks);
+
+    let total = dag.block_count() as f64;
+    let blue = dag.blocks().filter(|b| b.color() == Color::Blue).count() as f64;
+    let stats = &dag.stats;
+    let adversaries: &[u32] = if adversary_share > 0.0 { &[0] } else { &[] };
+    let quality = quality::measure(&dag, adversaries);
+    RunMetrics {
+        blue_ratio: blue / total,
+        mean_tips: mean(&stats.tip_counts),
+        chain_ratio: dag.selected_chain().len() as f64 / total,
+        reorgs: stats.reorg_depths.len() as f64,
+        max_reorg_depth: stats.reorg_depths.iter().max().copied().unwrap_or(0) as f64,
+        chain_quality: quality.chain_quality,
+        adversary_revenue: quality.adversary_revenue,
+    }
+}
+
+// Miner 0 gets `adversary_share` of the total, the rest split what's left
+fn hashrates(miners: usize, adversary_share: f64) -> Vec<f64> {
+    if adversary_share <= 0.0 || miners == 1 {
+        return vec![1.0; miners];
+    }
+    let honest = (1.0 - adversary_share) / (miners - 1) as f64;
+    std::iter::once(adversary_share).chain(std::iter::repeat_n(honest, miners - 1)).collect()
+}
+
+pub fn to_table(results: &[CellResult]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:>4} {:>10} {:>6} {:>6} {:>4} {:>10} {:>9} {:>11} {:>7} {:>15} {:>13} {:>17}",
+        COLUMNS[0],
+        COLUMNS[1],
+        COLUMNS[2],
+        COLUMNS[3],
+        COLUMNS[4],
+        COLUMNS[5],
+        COLUMNS[6],
+        COLUMNS[7],
+        COLUMNS[8],
+        COLUMNS[9],
+        COLUMNS[10],
+        COLUMNS[11]
+    );
+    for r in results {
+        let _ = writeln!(
+            out,
+            "{:>4} {:>10} {:>6.2} {:>6} {:>4} {:>10.4} {:>9.2} {:>11.4} {:>7.2} {:>15.2} {:>13.4} {:>17.4}",
+            r.k,
+            r.latency_ms,
+            r.bps,
+            r.miners,
+            r.runs,
+            r.blue_ratio,
+            r.mean_tips,
+            r.chain_ratio,
+            r.reorgs,
+            r.max_reorg_depth,
+            r.chain_quality,
+            r.adversary_revenue
+        );
+    }
+    out
+}
+
+pub fn to_csv(results: &[CellResult]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for r in results {
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{:.6},{:.6},{:.6},{:.6},{:.6},{:.6},{:.6}",
+            r.k,
+            r.latency_ms,
+            r.bps,
+            r.miners,
+            r.runs,
+            r.blue_ratio,
+            r.mean_tips,
+            r.chain_ratio,
+            r.reorgs,
+            r.max_reorg_depth,
+            r.chain_quality,
+            r.adversary_revenue
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(ks: Vec<usize>, adversary_share: f64) -> Grid {
+        Grid {
+            ks,
+            latencies_ms: vec![2000],
+            bps: vec![2.0],
+            miners: vec![4],
+            adversary_share,
+            blocks: 100,
+            seeds: 2,
+            base_seed: 42,
+        }
+    }
+
+    // Four blocks a delay apart are in each other's anticone: k = 0 turns
+    // most of them red, a k well above the width keeps all but the odd
+    // block parent selection never merges blue
+    #[test]
+    fn one_cell_per_combination_and_k_buys_blue_blocks() {
+        let results = run(&grid(vec![0, 18], 0.0));
+        assert_eq!(results.iter().map(|r| (r.k, r.runs)).collect::<Vec<_>>(), vec![(0, 2), (18, 2)]);
+        assert!(results[0].blue_ratio < 0.6, "blue ratio {} at k = 0", results[0].blue_ratio);
+        assert!(results[1].blue_ratio > 0.95, "blue ratio {} at k = 18", results[1].blue_ratio);
+        assert!(results.iter().all(|r| r.chain_quality == 1.0 && r.adversary_revenue == 0.0));
+
+        let csv = to_csv(&results);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.starts_with(&COLUMNS.join(",")));
+        assert!(csv.lines().nth(2).unwrap().starts_with("18,2000,2,4,2,"));
+    }
+
+    #[test]
+    fn the_adversary_gets_its_share_and_the_rest_split_evenly() {
+        assert_eq!(hashrates(3, 0.5), vec![0.5, 0.25, 0.25]);
+        assert_eq!(hashrates(3, 0.0), vec![1.0; 3]);
+        assert_eq!(hashrates(1, 0.4), vec![1.0]);
+
+        let results = run(&grid(vec![18], 0.4));
+        assert!(results[0].chain_quality < 1.0 && results[0].adversary_revenue > 0.0);
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::Write as _;
+
+use toydag_core::stats::mean;
+use toydag_core::{Color, ToyDag};
+
+use crate::network::{Network, NetworkConfig};
+
+// How one group of miners' blocks fared
+struct Showing {
+    hashrate: f64,
+    mined: usize,
+    blue_rate: f64,
+    chain_share: f64, // Fraction of selected-chain blocks (genesis aside) they mined
+}
+
+// Miner 0 freeloads: it holds `share` of the hashrate and mines single-parent
+// blocks on the selected tip, never merging anyone else's work. The other
+// `honest` miners split the rest and reference up to `max_parents` tips. The
+// same seed is then rerun with miner 0 honest, as a baseline.
+pub fn experiment(base: &NetworkConfig, share: f64, honest: usize, blocks: u64, seed: u64) -> String {
+    let honest = honest.max(1);
+    let hashrates: Vec<f64> =
+        std::iter::once(share).chain(std::iter::repeat_n((1.0 - share) / honest as f64, honest)).collect();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "🦥 Freeloader: {:.0}% of hashrate on the selected tip vs {} DAG-aware miners, {} blocks, latency {} ms, seed {}\n",
+        share * 100.0,
+        honest,
+        blocks,
+        base.latency_ms,
+        seed
+    );
+    let _ = writeln!(
+        out,
+        "{:<10} {:<11} {:>9} {:>7} {:>10} {:>12} {:>10}",
+        "run", "miners", "hashrate", "mined", "blue rate", "chain share", "mean tips"
+    );
+
+    for (run, freeloaders) in [("freeload", vec![0]), ("baseline", vec![])] {
+        let config = NetworkConfig { hashrates: hashrates.clone(), freeloaders, ..base.clone() };
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        Network::new(config, seed).run(&mut dag, blocks);
+
+        let tips = mean(&dag.stats.tip_counts);
+        for (label, group) in [("miner 0", showing(&dag, &hashrates, true)), ("others", showing(&dag, &hashrates, false))] {
+            let _ = writeln!(
+                out,
+                "{:<10} {:<11} {:>8.1}% {:>7} {:>9.1}% {:>11.1}% {:>10.2}",
+                run,
+                label,
+                group.hashrate * 100.0,
+                group.mined,
+                group.blue_rate * 100.0,
+                group.chain_share * 100.0,
+                tips
+            );
+        }
+    }
+    let _ = writeln!(out, "\nChain share above hashrate means the group's blocks win the selected chain more often than its work alone would.");
+    out
+}
+
+fn showing(dag: &ToyDag, hashrates: &[f64], first: bool) -> Showing {
+    let in_group = |id: u64| dag[id].miner().is_some_and(|m| (m == 0) == first);
+    let mined: Vec<u64> = dag.blocks().map(|b| b.id()).filter(|&id| in_group(id)).collect();
+    let blue = mined.iter().filter(|&&id| dag[id].color() == Color::Blue).count();
+    let chain: Vec<u64> = dag.selected_chain().into_iter().filter(|&id| id != dag.genesis()).collect();
+    let ours = chain.iter().filter(|&&id| in_group(id)).count();
+    let total: f64 = hashrates.iter().sum();
+
+    Showing {
+        hashrate: if first { hashrates[0] } else { total - hashrates[0] } / total,
+        mined: mined.len(),
+        blue_rate: blue as f64 / mined.len().max(1) as f64,
+        chain_share: ours as f64 / chain.len().max(1) as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(freeloaders: Vec<u32>) -> (Showing, Showing) {
+        let hashrates = vec![0.3, 0.35, 0.35];
+        let config = NetworkConfig { block_interval_ms: 500, latency_ms: 2000, hashrates: hashrates.clone(), freeloaders, ..NetworkConfig::default() };
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        Network::new(config, 42).run(&mut dag, 300);
+        (showing(&dag, &hashrates, true), showing(&dag, &hashrates, false))
+    }
+
+    // Single-parent blocks leave everything else in their anticone, so the
+    // freeloader's blocks go red far more often than the honest miners'
+    #[test]
+    fn freeloading_costs_blue_rate() {
+        let (freeloader, others) = run(vec![0]);
+        assert!(freeloader.mined > 0 && others.mined > 0);
+        assert!(freeloader.blue_rate + 0.2 < others.blue_rate, "freeloader {} vs others {}", freeloader.blue_rate, others.blue_rate);
+
+        let (miner0, others) = run(vec![]);
+        assert!((miner0.blue_rate - others.blue_rate).abs() < 0.2, "honest miner 0 {} vs others {}", miner0.blue_rate, others.blue_rate);
+    }
+}
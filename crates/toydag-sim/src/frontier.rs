@@ -0,0 +1,149 @@
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use toydag_core::{Color, ToyDag};
+use toydag_core::knight::KMode;
+
+use crate::confirmations::{ConfirmationTracker, percentile};
+use crate::dual;
+use crate::replay::{self, LogEntry};
+
+// One cell of the block rate × network delay grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontierPoint {
+    pub bps: f64,
+    pub delay_ms: u64,
+    pub red_rate: f64,               // Share of the DAG's blocks colored red
+    pub confirm_p50_ms: Option<u64>, // None when nothing confirmed in the run
+    pub confirm_p90_ms: Option<u64>,
+}
+
+const COLUMNS: [&str; 5] = ["bps", "delay_ms", "red_rate", "confirm_p50_ms", "confirm_p90_ms"];
+
+// The classic blockDAG trade-off: faster blocks confirm sooner, until the
+// delay lets so many pile up concurrently that a fixed k can't keep them
+// blue. Every cell mines the same seeded topology shape, one tx per block.
+pub fn run(bps: &[f64], delays_ms: &[u64], k: usize, blocks: u64, confirm_depth: u64, seed: u64) -> Vec<FrontierPoint> {
+    let mut points = Vec::with_capacity(bps.len() * delays_ms.len());
+    for &delay_ms in delays_ms {
+        for &rate in bps {
+            let interval_ms = (1000.0 / rate).round().max(1.0) as u64;
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag.k_mode = KMode::Fixed(k);
+            let tracker = Arc::new(Mutex::new(ConfirmationTracker::new(confirm_depth)));
+            dag.subscribe(tracker.clone());
+            for entry in dual::topology(blocks, interval_ms, delay_ms, seed) {
+                let entry = LogEntry { txs: vec![entry.id], ..entry };
+                replay::apply(&mut dag, &entry).expect("generated topologies replay cleanly");
+            }
+
+            let red = dag.blocks().filter(|b| b.color() == Color::Red).count();
+            let mut times: Vec<u64> = tracker.lock().unwrap().confirmed.iter().map(|c| c.time).collect();
+            times.sort_unstable();
+            let confirm = |p| (!times.is_empty()).then(|| percentile(&times, p));
+            points.push(FrontierPoint {
+                bps: rate,
+                delay_ms,
+                red_rate: red as f64 / dag.block_count() as f64,
+                confirm_p50_ms: confirm(0.50),
+                confirm_p90_ms: confirm(0.90),
+            });
+        }
+    }
+    points
+}
+
+// Two grids, delays down and block rates across: red rate, then median
+// confirmation time
+pub fn report(points: &[FrontierPoint], k: usize, blocks: u64, confirm_depth: u64, seed: u64) -> String {
+    let mut rates: Vec<f64> = Vec::new();
+    let mut delays: Vec<u64> = Vec::new();
+    for p in points {
+        if !rates.contains(&p.bps) {
+            rates.push(p.bps);
+        }
+        if !delays.contains(&p.delay_ms) {
+            delays.push(p.delay_ms);
+        }
+    }
+    let cell = |delay: u64, rate: f64| points.iter().find(|p| p.delay_ms == delay && p.bps == rate);
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "📈 Throughput vs latency at k = {}: {} blocks per cell, confirmation at depth {}, seed {}",
+        k, blocks, confirm_depth, seed
+    );
+    let header = |out: &mut String, title: &str| {
+        let _ = write!(out, "\n{}\n{:>11}", title, "delay \\ bps");
+        for rate in &rates {
+            let _ = write!(out, " {:>8}", rate);
+        }
+        let _ = writeln!(out);
+    };
+
+    header(&mut out, "Red rate");
+    for &delay in &delays {
+        let _ = write!(out, "{:>8} ms", delay);
+        for &rate in &rates {
+            let _ = write!(out, " {:>7.1}%", cell(delay, rate).map_or(0.0, |p| p.red_rate * 100.0));
+        }
+        let _ = writeln!(out);
+    }
+
+    header(&mut out, "Median confirmation time (s)");
+    for &delay in &delays {
+        let _ = write!(out, "{:>8} ms", delay);
+        for &rate in &rates {
+            match cell(delay, rate).and_then(|p| p.confirm_p50_ms) {
+                Some(ms) => _ = write!(out, " {:>8.1}", ms as f64 / 1000.0),
+                None => _ = write!(out, " {:>8}", "-"),
+            }
+        }
+        let _ = writeln!(out);
+    }
+    out
+}
+
+pub fn to_csv(points: &[FrontierPoint]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    let ms = |v: Option<u64>| v.map_or(String::new(), |v| v.to_string());
+    for p in points {
+        let _ = writeln!(out, "{},{},{:.6},{},{}", p.bps, p.delay_ms, p.red_rate, ms(p.confirm_p50_ms), ms(p.confirm_p90_ms));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reds_grow_with_rate_and_delay_and_confirmations_are_timed() {
+        let points = run(&[0.5, 8.0], &[250, 4000], 3, 150, 5, 42);
+        let at = |bps: f64, delay_ms: u64| points.iter().find(|p| p.bps == bps && p.delay_ms == delay_ms).unwrap();
+        assert_eq!(points.len(), 4);
+        assert_eq!(at(0.5, 250).red_rate, 0.0);
+        assert!(at(8.0, 4000).red_rate > at(8.0, 250).red_rate);
+        assert!(at(8.0, 4000).red_rate > at(0.5, 4000).red_rate);
+        let calm = at(0.5, 250);
+        assert!(calm.confirm_p50_ms.is_some() && calm.confirm_p50_ms <= calm.confirm_p90_ms);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_point_and_leaves_missing_times_empty() {
+        let points = [
+            FrontierPoint { bps: 0.5, delay_ms: 250, red_rate: 0.0, confirm_p50_ms: Some(1200), confirm_p90_ms: Some(3000) },
+            FrontierPoint { bps: 8.0, delay_ms: 4000, red_rate: 0.25, confirm_p50_ms: None, confirm_p90_ms: None },
+        ];
+        assert_eq!(
+            to_csv(&points),
+            "bps,delay_ms,red_rate,confirm_p50_ms,confirm_p90_ms\n0.5,250,0.000000,1200,3000\n8,4000,0.250000,,\n"
+        );
+        let report = report(&points, 3, 100, 5, 42);
+        assert!(report.contains("    4000 ms     0.0%    25.0%"));
+        assert!(report.contains("       -"));
+    }
+}
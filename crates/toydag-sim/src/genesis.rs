@@ -0,0 +1,91 @@
+use std::fmt::Write as _;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use toydag_core::{Color, Genesis, ToyDag};
+
+use crate::simulation_step;
+
+// A/B comparison across genesis specs: one independent DAG per genesis, all
+// fed the same seeded workload in the same process. Nothing is shared between
+// the instances, so any difference in the table comes from the genesis alone.
+pub fn compare(specs: &[Genesis], blocks: u64, seed: u64) -> Result<String, String> {
+    let mut out = String::new();
+    let _ = writeln!(out, "🌱 Genesis comparison: {} networks, {} blocks each, seed {}\n", specs.len(), blocks, seed);
+    let _ = writeln!(
+        out,
+        "{:>10} {:>12} {:>10} {:>21} {:>7} {:>14} {:>7} {:>5}",
+        "genesis", "timestamp", "difficulty", "ids", "chain", "blue work", "red %", "tips"
+    );
+
+    for &spec in specs {
+        let mut dag = ToyDag::with_genesis(spec).map_err(|e| e.to_string())?;
+        dag.verbose = false;
+        let mut rng = StdRng::seed_from_u64(seed);
+        for i in 1..=blocks {
+            simulation_step(&mut dag, &mut rng, i);
+        }
+
+        let reds = dag.blocks().filter(|b| b.color() == Color::Red).count();
+        let ids = format!("{}..{}", dag.genesis(), dag.next_id() - 1);
+        let _ = writeln!(
+            out,
+            "{:>10} {:>12} {:>10} {:>21} {:>7} {:>14} {:>6.1}% {:>5}",
+            spec.id,
+            spec.timestamp,
+            spec.difficulty,
+            ids,
+            dag.selected_chain().len(),
+            dag[dag.selected_parent()].blue_work(),
+            reds as f64 / dag.block_count() as f64 * 100.0,
+            dag.tip_count()
+        );
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_parse_and_refuse_what_cannot_grow() {
+        let spec: Genesis = "1000:7:4".parse().unwrap();
+        assert_eq!((spec.id, spec.timestamp, spec.difficulty), (1000, 7, 4));
+        assert_eq!("1000".parse::<Genesis>().unwrap().difficulty, 1);
+
+        let max = u64::MAX.to_string();
+        assert!(max.parse::<Genesis>().unwrap_err().contains("no id for the next block"));
+        assert!("5:0:0".parse::<Genesis>().unwrap_err().contains("zero difficulty"));
+        assert!("5:x".parse::<Genesis>().unwrap_err().contains("bad genesis timestamp"));
+
+        for id in [u64::MAX, u64::MAX - 1] {
+            let spec = Genesis { id, ..Genesis::default() };
+            assert!(ToyDag::with_genesis(spec).is_err());
+            assert!(compare(&[spec], 10, 1).is_err());
+        }
+    }
+
+    #[test]
+    fn genesis_at_the_top_of_the_id_space_does_not_panic() {
+        let spec = Genesis { id: u64::MAX - 2, ..Genesis::default() };
+        let mut dag = ToyDag::with_genesis(spec).unwrap();
+        dag.verbose = false;
+        let last = dag.create_block(vec![spec.id]).unwrap();
+        assert_eq!(last, u64::MAX - 1);
+        assert!(dag.create_block(vec![last]).is_err());
+        assert_eq!(dag.block_count(), 2);
+
+        let report = compare(&[spec], 10, 1).unwrap();
+        assert!(report.contains(&format!("{}..{}", spec.id, u64::MAX - 1)));
+    }
+
+    #[test]
+    fn compare_reports_each_spec_on_its_own_ids() {
+        let specs = [Genesis::default(), Genesis { id: 1000, timestamp: 0, difficulty: 4 }];
+        let report = compare(&specs, 20, 7).unwrap();
+        assert!(report.contains("0..20"));
+        assert!(report.contains("1000..1020"));
+    }
+}
@@ -0,0 +1,270 @@
+// Run the toy's GHOSTDAG over a header dump from a real Kaspa node and see how
+// far it agrees. A dump is a JSON array (or JSON lines) of headers, as the
+// node's RPC names them, or a CSV with a header row naming the columns.
+// Parents are the direct (level 0) parents. A dump is a window of a much
+// bigger DAG, so parents outside it are dropped and blocks left with none
+// hang off the toy genesis; blue scores then only agree up to a constant.
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use toydag_core::knight::KMode;
+use toydag_core::{BLOCK_VERSION, NewBlock, ToyDag};
+
+// One block header. Only hash, parents and timestamp are needed; the rest is
+// what the node computed, compared against the toy when present.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Header {
+    pub hash: String,
+    #[serde(alias = "directParents", alias = "parentHashes")]
+    pub parents: Vec<String>,
+    pub timestamp: u64,
+    #[serde(default, alias = "blueScore")]
+    pub blue_score: Option<u64>,
+    #[serde(default, alias = "selectedParentHash")]
+    pub selected_parent: Option<String>,
+    #[serde(default, alias = "isChainBlock")]
+    pub chain_block: Option<bool>,
+}
+
+pub fn load(path: &Path) -> Result<Vec<Header>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let headers = if path.extension().is_some_and(|e| e == "csv") {
+        parse_csv(&text)
+    } else if text.trim_start().starts_with('[') {
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    } else {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(n, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {}", n + 1, e)))
+            .collect()
+    };
+    headers.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Columns hash, parents, timestamp, then optionally blue_score,
+// selected_parent and chain_block, in any order. Parents are separated by
+// spaces or semicolons; empty optional cells mean unknown.
+fn parse_csv(text: &str) -> Result<Vec<Header>, String> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, header_row)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<&str> = header_row.split(',').map(str::trim).collect();
+    let column = |name: &str| columns.iter().position(|&c| c == name);
+    let required = |name: &str| column(name).ok_or_else(|| format!("no '{}' column", name));
+    let (hash, parents, timestamp) = (required("hash")?, required("parents")?, required("timestamp")?);
+    let (blue_score, selected_parent, chain_block) = (column("blue_score"), column("selected_parent"), column("chain_block"));
+
+    let mut headers = Vec::new();
+    for (n, line) in lines {
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        let cell = |i: Option<usize>| i.and_then(|i| cells.get(i)).copied().filter(|c| !c.is_empty());
+        headers.push(Header {
+            hash: cell(Some(hash)).ok_or_else(|| format!("line {}: no hash", n + 1))?.to_string(),
+            parents: cell(Some(parents))
+                .unwrap_or_default()
+                .split([' ', ';'])
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect(),
+            timestamp: parse(cell(Some(timestamp)), n, "timestamp")?.ok_or_else(|| format!("line {}: no timestamp", n + 1))?,
+            blue_score: parse(cell(blue_score), n, "blue score")?,
+            selected_parent: cell(selected_parent).map(str::to_string),
+            chain_block: parse(cell(chain_block), n, "chain flag")?,
+        });
+    }
+    Ok(headers)
+}
+
+fn parse<T: FromStr>(cell: Option<&str>, n: usize, name: &str) -> Result<Option<T>, String> {
+    cell.map(|c| c.parse().map_err(|_| format!("line {}: bad {} '{}'", n + 1, name, c))).transpose()
+}
+
+// The toy DAG built from a dump. Block ids follow (timestamp, hash), so the
+// same dump always maps the same way.
+pub struct Import {
+    pub dag: ToyDag,
+    pub ids: HashMap<String, u64>,
+    hashes: Vec<String>, // By id, genesis excluded
+    pub dropped_parents: usize, // Parent links pointing outside the dump
+    pub roots: usize,           // Blocks with no parent inside it, hung off genesis
+    pub refused: usize,         // Blocks the toy's rules wouldn't take
+}
+
+impl Import {
+    pub fn hash(&self, id: u64) -> Option<&str> {
+        let index = id.checked_sub(self.dag.genesis() + 1)?;
+        self.hashes.get(index as usize).map(String::as_str)
+    }
+}
+
+pub fn import(headers: &[Header], k: usize) -> Result<Import, String> {
+    let mut sorted: Vec<&Header> = headers.iter().collect();
+    sorted.sort_by(|a, b| (a.timestamp, &a.hash).cmp(&(b.timestamp, &b.hash)));
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag.k_mode = KMode::Fixed(k);
+    let mut ids = HashMap::with_capacity(sorted.len());
+    for (i, header) in sorted.iter().enumerate() {
+        if ids.insert(header.hash.clone(), dag.genesis() + 1 + i as u64).is_some() {
+            return Err(format!("block {} appears twice", header.hash));
+        }
+    }
+
+    let (mut dropped_parents, mut roots) = (0, 0);
+    let blocks: Vec<NewBlock> = sorted
+        .iter()
+        .map(|header| {
+            let mut parents: Vec<u64> = header.parents.iter().filter_map(|p| ids.get(p).copied()).collect();
+            dropped_parents += header.parents.len() - parents.len();
+            if parents.is_empty() {
+                roots += 1;
+                parents.push(dag.genesis());
+            }
+            NewBlock { id: ids[&header.hash], parents, txs: vec![], miner: None, timestamp: header.timestamp, version: BLOCK_VERSION }
+        })
+        .collect();
+    let total = blocks.len();
+    let inserted = dag.ingest_batch(blocks).map_err(|e| e.to_string())?;
+    let hashes = sorted.iter().map(|h| h.hash.clone()).collect();
+    Ok(Import { dag, ids, hashes, dropped_parents, roots, refused: total - inserted })
+}
+
+// Import a dump and set the toy's verdicts beside the node's
+pub fn compare(headers: &[Header], k: usize, show: usize) -> Result<String, String> {
+    let import = import(headers, k)?;
+    let dag = &import.dag;
+    let chain: HashSet<u64> = dag.selected_chain().into_iter().collect();
+    let known = |h: &Header| dag.contains(import.ids[&h.hash]);
+
+    // Real scores count the whole history, so line them up by the most common gap
+    let mut gaps: HashMap<i64, usize> = HashMap::new();
+    for h in headers.iter().filter(|h| known(h)) {
+        if let Some(real) = h.blue_score {
+            *gaps.entry(real as i64 - dag[import.ids[&h.hash]].blue_score() as i64).or_default() += 1;
+        }
+    }
+    let offset = gaps.iter().max_by_key(|&(gap, n)| (*n, -gap)).map_or(0, |(&gap, _)| gap);
+
+    let mut mismatches = Vec::new();
+    let (mut scores, mut score_hits, mut sps, mut sp_hits, mut flags, mut flag_hits) = (0, 0, 0, 0, 0, 0);
+    for h in headers.iter().filter(|h| known(h)) {
+        let block = &dag[import.ids[&h.hash]];
+        if let Some(real) = h.blue_score {
+            let toy = block.blue_score() as i64 + offset;
+            scores += 1;
+            score_hits += (toy == real as i64) as usize;
+            if toy != real as i64 {
+                mismatches.push(format!("{}: blue score {} here, {} on the node", h.hash, toy, real));
+            }
+        }
+        if let Some(real) = &h.selected_parent
+            && import.ids.contains_key(real)
+        {
+            let toy = block.selected_parent().and_then(|sp| import.hash(sp)).unwrap_or("genesis");
+            sps += 1;
+            sp_hits += (toy == real) as usize;
+            if toy != real {
+                mismatches.push(format!("{}: selected parent {} here, {} on the node", h.hash, toy, real));
+            }
+        }
+        if let Some(real) = h.chain_block {
+            let toy = chain.contains(&block.id());
+            flags += 1;
+            flag_hits += (toy == real) as usize;
+            if toy != real {
+                mismatches.push(format!("{}: {} the selected chain here, {} on the node", h.hash, on(toy), on(real)));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "🛰️  Kaspa import: {} headers, k={}", headers.len(), k);
+    let _ = writeln!(
+        out,
+        "   {} imported, {} refused by the toy's rules, {} roots, {} parent links outside the dump",
+        headers.len() - import.refused,
+        import.refused,
+        import.roots,
+        import.dropped_parents
+    );
+    let _ = writeln!(out, "   selected chain: {} blocks, {} tips", chain.len() - 1, dag.tip_count());
+    let _ = writeln!(out, "   {:<16} {:>8} {:>8} {:>8}", "check", "known", "agree", "rate");
+    for (name, total, hits) in [("blue score", scores, score_hits), ("selected parent", sps, sp_hits), ("chain block", flags, flag_hits)] {
+        let rate = if total == 0 { "-".to_string() } else { format!("{:.1}%", 100.0 * hits as f64 / total as f64) };
+        let _ = writeln!(out, "   {:<16} {:>8} {:>8} {:>8}", name, total, hits, rate);
+    }
+    if scores > 0 {
+        let _ = writeln!(out, "   (blue scores compared after adding {} to the toy's)", offset);
+    }
+    for line in mismatches.iter().take(show) {
+        let _ = writeln!(out, "   ✗ {}", line);
+    }
+    if mismatches.len() > show {
+        let _ = writeln!(out, "   … and {} more", mismatches.len() - show);
+    }
+    Ok(out)
+}
+
+fn on(chain: bool) -> &'static str {
+    if chain { "on" } else { "off" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_columns_come_in_any_order_and_blank_cells_are_unknown() {
+        let text = "timestamp, hash, blue_score, parents, chain_block\n\
+                    10, a, 100, x, true\n\
+                    \n\
+                    20, b, , a;x y, \n";
+        let headers = parse_csv(text).unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0], Header { hash: "a".into(), parents: vec!["x".into()], timestamp: 10, blue_score: Some(100), selected_parent: None, chain_block: Some(true) });
+        assert_eq!(headers[1].parents, vec!["a", "x", "y"]);
+        assert_eq!((headers[1].blue_score, headers[1].chain_block), (None, None));
+
+        assert_eq!(parse_csv("hash,timestamp\na,1").unwrap_err(), "no 'parents' column");
+        assert_eq!(parse_csv("hash,parents,timestamp\na,,soon").unwrap_err(), "line 2: bad timestamp 'soon'");
+        assert_eq!(parse_csv("hash,parents,timestamp\na,,").unwrap_err(), "line 2: no timestamp");
+    }
+
+    #[test]
+    fn json_takes_the_node_rpc_names() {
+        let json = r#"{"hash":"b","directParents":["a"],"timestamp":5,"blueScore":7,"selectedParentHash":"a","isChainBlock":false}"#;
+        let header: Header = serde_json::from_str(json).unwrap();
+        assert_eq!(header, Header { hash: "b".into(), parents: vec!["a".into()], timestamp: 5, blue_score: Some(7), selected_parent: Some("a".into()), chain_block: Some(false) });
+    }
+
+    fn header(hash: &str, parents: &[&str], timestamp: u64, blue_score: u64) -> Header {
+        let parents = parents.iter().map(|p| p.to_string()).collect();
+        Header { hash: hash.into(), parents, timestamp, blue_score: Some(blue_score), selected_parent: None, chain_block: None }
+    }
+
+    // Ids follow timestamps, parents outside the dump are dropped, and the
+    // node's blue scores line up with the toy's after a constant offset
+    #[test]
+    fn import_maps_a_window_onto_the_toy_genesis() {
+        let headers = [header("c", &["a", "b"], 30, 1002), header("a", &["old"], 10, 1000), header("b", &["old"], 20, 1000)];
+        let imported = import(&headers, 18).unwrap();
+        assert_eq!((imported.ids["a"], imported.ids["b"], imported.ids["c"]), (1, 2, 3));
+        assert_eq!((imported.hash(3), imported.hash(0), imported.hash(4)), (Some("c"), None, None));
+        assert_eq!((imported.dropped_parents, imported.roots, imported.refused), (2, 2, 0));
+        assert_eq!(imported.dag[3].parents(), &[1, 2]);
+
+        let report = compare(&headers, 18, 5).unwrap();
+        assert!(report.contains("blue score              3        3   100.0%"), "{}", report);
+        assert!(report.contains("after adding 999"), "{}", report);
+
+        let twice = [header("a", &[], 1, 1), header("a", &[], 2, 2)];
+        assert_eq!(import(&twice, 18).err(), Some("block a appears twice".to_string()));
+    }
+}
@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+use std::fmt::Write as _;
+
+use toydag_core::knight::{AdaptiveK, KMode};
+use toydag_core::stats::mean;
+use toydag_core::{K, ToyDag};
+
+use crate::network::{Network, NetworkConfig};
+
+const ROWS_PER_PHASE: u64 = 4;
+
+// Run the network model through phases of different latency with an adaptive
+// k, printing the effective k as it moves. Fixed K is shown alongside, on its
+// own DAG fed the same phases: once the delay grows past what K was sized for,
+// honest blocks merge more than K others and it colors them red.
+pub fn experiment(latencies: &[u64], blocks_per_phase: u64, window: usize, coverage: f64, seed: u64) -> String {
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag.k_mode = KMode::Adaptive(AdaptiveK::new(window, coverage));
+    let mut fixed = ToyDag::new();
+    fixed.verbose = false;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Adaptive k: window {}, coverage {:.0}%, fixed k = {} for reference, seed {}\n",
+        window,
+        coverage * 100.0,
+        K,
+        seed
+    );
+    let _ = writeln!(out, "{:>8} {:>11} {:>12} {:>8}", "block", "latency ms", "effective k", "vs K");
+
+    // The timeline already carries the k used for every block; sample it
+    let step = (blocks_per_phase / ROWS_PER_PHASE).max(1) as usize;
+    for (phase, &latency_ms) in latencies.iter().enumerate() {
+        let (start, fixed_start) = (dag.stats.timeline.len(), fixed.stats.timeline.len());
+        let config = NetworkConfig { latency_ms, ..NetworkConfig::default() };
+        Network::new(config.clone(), seed.wrapping_add(phase as u64)).run(&mut dag, blocks_per_phase);
+        Network::new(config, seed.wrapping_add(phase as u64)).run(&mut fixed, blocks_per_phase);
+
+        let rows = &dag.stats.timeline[start..];
+        for row in rows.iter().skip(step - 1).step_by(step) {
+            let versus = match row.k.cmp(&(K as u64)) {
+                Ordering::Greater => "above",
+                Ordering::Equal => "equal",
+                Ordering::Less => "below",
+            };
+            let _ = writeln!(out, "{:>8} {:>11} {:>12} {:>8}", row.block, latency_ms, row.k, versus);
+        }
+        let _ = writeln!(
+            out,
+            "{:>8} red rate {:.1}% adaptive, {:.1}% at fixed k = {}",
+            "",
+            red_share(&dag, start) * 100.0,
+            red_share(&fixed, fixed_start) * 100.0,
+            K
+        );
+    }
+    out
+}
+
+// Red share of the blocks inserted since timeline row `start`
+fn red_share(dag: &ToyDag, start: usize) -> f64 {
+    let rows = &dag.stats.timeline[start..];
+    rows.iter().filter(|row| !row.blue).count() as f64 / rows.len().max(1) as f64
+}
+
+// For each latency, mine `blocks` at the given block interval and read off
+// the smallest k that would have kept the red rate under `max_red_rate`. The
+// ratio of latency to block interval is what drives anticones, so this is the
+// k for that pairing. The histogram of the slowest run is shown underneath.
+pub fn recommend(latencies: &[u64], block_interval_ms: u64, blocks: u64, max_red_rate: f64, seed: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "🎯 Recommended k for a {:.2}% red rate, one block every {} ms, {} blocks per latency, seed {}\n",
+        max_red_rate * 100.0,
+        block_interval_ms,
+        blocks,
+        seed
+    );
+    let _ = writeln!(out, "{:>11} {:>12} {:>13} {:>13} {:>14}", "latency ms", "lat/interval", "mean anticone", "max anticone", "recommended k");
+
+    let mut slowest = None;
+    for &latency_ms in latencies {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.track_anticones = true;
+        let config = NetworkConfig { latency_ms, block_interval_ms, ..NetworkConfig::default() };
+        Network::new(config, seed).run(&mut dag, blocks);
+        let sizes: Vec<usize> = dag.stats.anticones.values().copied().collect();
+        let _ = writeln!(
+            out,
+            "{:>11} {:>12.1} {:>13.2} {:>13} {:>14}",
+            latency_ms,
+            latency_ms as f64 / block_interval_ms.max(1) as f64,
+            mean(&sizes),
+            sizes.iter().max().copied().unwrap_or(0),
+            dag.stats.recommend_k(max_red_rate)
+        );
+        if slowest.as_ref().is_none_or(|&(slowest, _)| latency_ms >= slowest) {
+            slowest = Some((latency_ms, dag));
+        }
+    }
+    if let Some((latency_ms, dag)) = slowest {
+        let _ = writeln!(out, "\nAt {} ms:", latency_ms);
+        out.push_str(&dag.stats.anticone_report(&dag, max_red_rate));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A calm phase then a slow one: adaptive k climbs above where it started,
+    // and each phase ends on its red rates
+    #[test]
+    fn adaptive_k_rises_with_latency_and_reports_red_rates() {
+        let report = experiment(&[500, 20000], 80, 40, 0.9, 7);
+        let ks: Vec<u64> = report
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(2)?.parse().ok())
+            .collect();
+        assert_eq!(ks.len(), 2 * ROWS_PER_PHASE as usize);
+        assert!(ks.last() > ks.first(), "k never rose: {:?}", ks);
+        assert_eq!(report.matches("red rate").count(), 2);
+    }
+}
@@ -0,0 +1,71 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use toydag_core::ToyDag;
+
+pub mod alerts;
+pub mod balance;
+pub mod bench;
+pub mod checkpoint;
+pub mod confirmations;
+pub mod consensus;
+pub mod daa;
+pub mod dual;
+pub mod experiment;
+pub mod freeloader;
+pub mod frontier;
+pub mod genesis;
+pub mod kaspa;
+pub mod knight;
+pub mod mempool;
+pub mod network;
+pub mod nodes;
+pub mod quality;
+pub mod replay;
+pub mod scenario;
+pub mod softfork;
+pub mod stitch;
+pub mod timewarp;
+pub mod topology;
+pub mod watchdog;
+
+// How an honest miner picks parents among the tips it sees. Weighted ranks
+// them by blue score and how much new anticone each brings in, the way a node
+// builds the virtual's parents (see `ToyDag::select_parents`); Random takes
+// any of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParentSelection {
+    Random,
+    #[default]
+    Weighted,
+}
+
+impl ParentSelection {
+    pub fn pick(self, dag: &ToyDag, tips: &[u64], max: usize, rng: &mut impl Rng) -> Vec<u64> {
+        match self {
+            ParentSelection::Random => tips.choose_multiple(rng, tips.len().min(max)).copied().collect(),
+            ParentSelection::Weighted => dag.select_parents(tips, max).expect("tips are in the DAG"),
+        }
+    }
+}
+
+// One tick of the default simulation: a random multi-parent block, then StitchBot
+pub fn simulation_step(dag: &mut ToyDag, rng: &mut impl Rng, i: u64) {
+    let current_tips: Vec<u64> = dag.tips().collect();
+    let num_parents = current_tips.len().min(dag.max_parents.unwrap_or(3)); // Up to 3 parents for better merging
+
+    let parents: Vec<u64> = current_tips
+        .choose_multiple(rng, num_parents)
+        .copied()
+        .collect();
+
+    // One toy tx per block; a block the DAG's merge rules refuse is just lost
+    let _ = dag.create_block_with_txs(parents, vec![i]);
+
+    // StitchBot checks every few blocks
+    if i.is_multiple_of(5) {
+        dag.stitch_if_needed();
+    }
+}
@@ -0,0 +1,167 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::ToyDag;
+use toydag_core::mempool::Mempool;
+
+#[derive(Debug, Clone)]
+pub struct MempoolConfig {
+    pub miners: usize,
+    pub block_interval_ms: u64,
+    pub latency_ms: u64,   // Between any two miners
+    pub tx_per_block: u64, // New txs per block interval, reaching every mempool at once
+    pub capacity: usize,   // Tx slots per block
+    pub max_parents: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Delivery {
+    at: u64,
+    to: usize,
+    id: u64,
+    parents: Vec<u64>,
+    txs: Vec<u64>,
+    from: usize,
+}
+
+// Throughput of one run, measured on miner 0's final view
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throughput {
+    pub seconds: f64,
+    pub blocks: usize,
+    pub included: usize,   // Tx slots filled across all blocks
+    pub unique: usize,     // Distinct txs confirmed by the total order
+    pub duplicates: usize, // Slots spent on txs an earlier block already carried
+    pub fees: u64,         // Fees of the unique txs
+    pub backlog: usize,    // Txs still in miner 0's mempool
+}
+
+// Every miner keeps its own view and mempool. Each block fills its slots with
+// the miner's highest-fee txs; miners only drop a tx once they see a block
+// carrying it, so blocks mined within one latency of each other tend to carry
+// the same top-fee txs.
+pub fn simulate(config: &MempoolConfig, blocks: u64, seed: u64) -> Throughput {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut views: Vec<ToyDag> = (0..config.miners)
+        .map(|_| {
+            let mut dag = ToyDag::new();
+            dag.verbose = false;
+            dag
+        })
+        .collect();
+    let mut pools = vec![Mempool::default(); config.miners];
+    let mut in_flight = BinaryHeap::new();
+    let mut all_fees = Vec::new();
+
+    for i in 0..blocks {
+        let now = i * config.block_interval_ms;
+        while in_flight.peek().is_some_and(|Reverse(d): &Reverse<Delivery>| d.at <= now) {
+            let Reverse(d) = in_flight.pop().unwrap();
+            // Every miner hears from every other at the same latency, so parents are always in place
+            views[d.to].insert_block_at(d.id, d.parents, d.txs.clone(), Some(d.from as u32), d.at);
+            pools[d.to].remove(&d.txs);
+        }
+
+        for _ in 0..config.tx_per_block {
+            let tx = all_fees.len() as u64;
+            let fee = rng.gen_range(1..=100);
+            all_fees.push(fee);
+            for pool in &mut pools {
+                pool.add(tx, fee);
+            }
+        }
+
+        let m = rng.gen_range(0..config.miners);
+        let tips: Vec<u64> = views[m].tips().collect();
+        let parents: Vec<u64> = tips.choose_multiple(&mut rng, tips.len().min(config.max_parents)).copied().collect();
+        let txs = pools[m].select(config.capacity);
+        let id = i + 1;
+        views[m].insert_block_at(id, parents.clone(), txs.clone(), Some(m as u32), now);
+        pools[m].remove(&txs);
+        for to in (0..config.miners).filter(|&to| to != m) {
+            let at = now + config.latency_ms;
+            in_flight.push(Reverse(Delivery { at, to, id, parents: parents.clone(), txs: txs.clone(), from: m }));
+        }
+    }
+    while let Some(Reverse(d)) = in_flight.pop() {
+        views[d.to].insert_block_at(d.id, d.parents, d.txs.clone(), Some(d.from as u32), d.at);
+        pools[d.to].remove(&d.txs);
+    }
+
+    let acceptance = views[0].tx_acceptance();
+    Throughput {
+        seconds: (blocks * config.block_interval_ms) as f64 / 1000.0,
+        blocks: views[0].block_count() - 1,
+        included: acceptance.included(),
+        unique: acceptance.accepted.len(),
+        duplicates: acceptance.duplicates,
+        fees: acceptance.accepted.iter().map(|&tx| all_fees[tx as usize]).sum(),
+        backlog: pools[0].len(),
+    }
+}
+
+// Sweep latency and compare raw tx throughput (slots filled per second) with
+// effective throughput (unique txs confirmed per second)
+pub fn experiment(base: &MempoolConfig, latencies: &[u64], blocks: u64, seed: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "🧾 Mempool: {} miners, {} blocks every {} ms, {} tx slots per block, {} new txs per interval, seed {}\n",
+        base.miners, blocks, base.block_interval_ms, base.capacity, base.tx_per_block, seed
+    );
+    let _ = writeln!(
+        out,
+        "{:>10} {:>9} {:>9} {:>8} {:>8} {:>7} {:>11} {:>8}",
+        "latency", "blocks/s", "raw tx/s", "eff tx/s", "dup rate", "unique", "fees", "backlog"
+    );
+    for &latency_ms in latencies {
+        let config = MempoolConfig { latency_ms, ..base.clone() };
+        let t = simulate(&config, blocks, seed);
+        let _ = writeln!(
+            out,
+            "{:>7} ms {:>9.2} {:>9.2} {:>8.2} {:>7.1}% {:>7} {:>11} {:>8}",
+            latency_ms,
+            t.blocks as f64 / t.seconds,
+            t.included as f64 / t.seconds,
+            t.unique as f64 / t.seconds,
+            100.0 * t.duplicates as f64 / t.included.max(1) as f64,
+            t.unique,
+            t.fees,
+            t.backlog
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(latency_ms: u64) -> MempoolConfig {
+        MempoolConfig { miners: 4, block_interval_ms: 100, latency_ms, tx_per_block: 10, capacity: 10, max_parents: 4 }
+    }
+
+    #[test]
+    fn parallel_blocks_carry_duplicates_and_unique_stays_under_raw() {
+        // Blocks a latency apart can't see each other's txs
+        let slow = simulate(&config(1000), 200, 9);
+        assert!(slow.duplicates > 0);
+        assert!(slow.unique <= slow.included);
+        assert_eq!(slow.unique + slow.duplicates, slow.included);
+        assert_eq!(slow.blocks, 200);
+
+        // Blocks that always see each other never repeat a tx
+        let instant = simulate(&config(0), 200, 9);
+        assert_eq!(instant.duplicates, 0);
+        assert_eq!(instant.unique, instant.included);
+        assert!(instant.unique > slow.unique);
+
+        let report = experiment(&config(0), &[0, 1000], 200, 9);
+        assert_eq!(report.lines().filter(|l| l.contains(" ms ")).count(), 2);
+    }
+}
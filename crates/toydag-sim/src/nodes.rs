@@ -0,0 +1,633 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::fmt::Write as _;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use toydag_core::stats::mean;
+use toydag_core::{Color, NewBlock, ToyDag};
+
+use crate::ParentSelection;
+use crate::network::transfer_ms;
+use crate::topology::Topology;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSimConfig {
+    pub block_interval_ms: u64,
+    pub block_size: u64, // Bytes
+    pub max_parents: usize,
+    pub parent_selection: ParentSelection,
+}
+
+impl Default for NodeSimConfig {
+    fn default() -> Self {
+        NodeSimConfig { block_interval_ms: 1000, block_size: 0, max_parents: 3, parent_selection: ParentSelection::default() }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeSimStats {
+    pub mined: u64,
+    pub tips: Vec<usize>,           // Tips of every node, sampled before every block
+    pub missing: Vec<usize>,        // Blocks each node has not seen yet, same samples
+    pub distinct_sinks: Vec<usize>, // How many different selected parents the nodes hold
+    pub timeline: Vec<Divergence>,  // One row per sample
+}
+
+// How far apart the mining nodes' views were at one sample
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Divergence {
+    pub at: u64,
+    pub mean_missing: f64,
+    pub max_missing: usize,
+    pub ghost_tips: usize, // Tips of some node's view that another node hasn't seen
+    pub sinks: usize,
+}
+
+// One node's DAG as it stands at `at`, next to what the others already have
+#[derive(Debug, Clone)]
+pub struct NodeView {
+    pub node: usize,
+    pub at: u64,
+    pub online: bool,
+    pub blocks: usize,
+    pub tips: Vec<u64>,
+    pub selected_parent: u64,
+    pub unseen: Vec<u64>,     // Mined somewhere, not in this view
+    pub ghost_tips: Vec<u64>, // Tips of other nodes' views missing from this one
+    pub waiting: Vec<u64>,    // Arrived before their parents
+    pub in_flight: usize,     // Gossip still on its way here
+}
+
+impl NodeView {
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "👁️  Node {} at {} ms{}", self.node, self.at, if self.online { "" } else { " (offline)" });
+        let _ = writeln!(out, "  Blocks               : {}", self.blocks);
+        let _ = writeln!(out, "  Selected parent      : {}", self.selected_parent);
+        let _ = writeln!(out, "  Tips                 : {:?}", self.tips);
+        let _ = writeln!(out, "  Ghost tips           : {:?}", self.ghost_tips);
+        let _ = writeln!(out, "  Unseen blocks        : {} {:?}", self.unseen.len(), self.unseen);
+        let _ = writeln!(out, "  Waiting for parents  : {:?}", self.waiting);
+        let _ = writeln!(out, "  Gossip in flight     : {}", self.in_flight);
+        out
+    }
+}
+
+// A node that is offline until `join_at_ms`, then catches up from its nearest
+// peer: it asks for the headers it lacks, downloads those blocks in
+// topological batches, and repeats until a header round comes back empty.
+// It only starts mining once caught up.
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    pub node: usize,
+    pub join_at_ms: u64,
+    pub header_size: u64,  // Bytes
+    pub batch_size: usize, // Blocks per download request
+    pub bandwidth: u64,    // Bytes per ms on the sync connection; 0 means unlimited
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncStats {
+    pub peer: usize,
+    pub joined_at: u64,
+    pub caught_up_at: Option<u64>,
+    pub header_rounds: u64,
+    pub batches: u64,
+    pub headers: u64,
+    pub blocks: u64,
+    pub bytes: u64, // Headers and block bodies sent to the syncing node
+}
+
+impl SyncStats {
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.caught_up_at.map(|t| t - self.joined_at)
+    }
+}
+
+enum SyncStep {
+    Join,
+    Headers(Vec<u64>),
+    Blocks(Vec<NewBlock>),
+}
+
+pub(crate) struct Sync {
+    plan: SyncPlan,
+    stats: SyncStats,
+    round_trip_ms: u64,
+    queue: VecDeque<u64>,                // Announced by headers, not downloaded yet
+    pending: Option<(u64, SyncStep)>,    // Next response and when it lands
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) struct Delivery {
+    at: u64,
+    node: usize,
+    id: u64,
+    parents: Vec<u64>,
+    miner: u32,
+}
+
+// Every node keeps its own view of the DAG. A node mines on its own tips and
+// its block reaches each other node after the shortest-path delay between them.
+pub struct NodeSim {
+    pub config: NodeSimConfig,
+    pub stats: NodeSimStats,
+    pub views: Vec<ToyDag>,
+    pub(crate) delays: Vec<Vec<Option<u64>>>,
+    pub(crate) rng: ChaCha12Rng, // What StdRng wraps, named so a checkpoint can save its state
+    pub(crate) hashrates: Vec<f64>,
+    pub(crate) miner_dist: WeightedIndex<f64>,
+    pub(crate) in_flight: BinaryHeap<Reverse<Delivery>>,
+    pub(crate) waiting: Vec<Vec<Delivery>>, // Per node, delivered before their parents
+    pub(crate) sync: Option<Sync>,
+    pub(crate) next_id: u64,
+}
+
+impl NodeSim {
+    pub fn new(topology: &Topology, config: NodeSimConfig, seed: u64) -> Result<Self, String> {
+        let delays = topology.delays(config.block_size)?;
+        let hashrates = topology.hashrates();
+        let miner_dist = WeightedIndex::new(&hashrates).map_err(|e| format!("hashrates: {}", e))?;
+        let views = (0..topology.nodes)
+            .map(|_| {
+                let mut dag = ToyDag::new();
+                dag.verbose = false;
+                dag
+            })
+            .collect();
+        Ok(NodeSim {
+            config,
+            stats: NodeSimStats::default(),
+            views,
+            delays,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            hashrates,
+            miner_dist,
+            in_flight: BinaryHeap::new(),
+            waiting: vec![Vec::new(); topology.nodes],
+            sync: None,
+            next_id: 1,
+        })
+    }
+
+    // Hold `plan.node` back until it joins and syncs from its nearest peer
+    pub fn with_sync(mut self, plan: SyncPlan, topology: &Topology) -> Result<Self, String> {
+        if plan.node >= self.views.len() {
+            return Err(format!("sync node {} is not one of the {} nodes", plan.node, self.views.len()));
+        }
+        if plan.batch_size == 0 {
+            return Err("sync batches need at least one block".to_string());
+        }
+        let latencies = topology.delays(0)?;
+        let (peer, latency) = latencies[plan.node]
+            .iter()
+            .enumerate()
+            .filter_map(|(peer, d)| Some((peer, (*d)?)))
+            .filter(|&(peer, _)| peer != plan.node)
+            .min_by_key(|&(peer, d)| (d, peer))
+            .ok_or_else(|| format!("node {} has no reachable peer to sync from", plan.node))?;
+
+        let stats = SyncStats { peer, joined_at: plan.join_at_ms, ..SyncStats::default() };
+        let pending = Some((plan.join_at_ms, SyncStep::Join));
+        self.sync = Some(Sync { plan, stats, round_trip_ms: 2 * latency, queue: VecDeque::new(), pending });
+        Ok(self)
+    }
+
+    pub fn sync_stats(&self) -> Option<&SyncStats> {
+        self.sync.as_ref().map(|s| &s.stats)
+    }
+
+    // Offline nodes neither mine nor hear gossip; a syncing node hears gossip but doesn't mine yet
+    fn online(&self, node: usize, now: u64) -> bool {
+        self.sync.as_ref().is_none_or(|s| s.plan.node != node || now >= s.plan.join_at_ms)
+    }
+
+    fn mining(&self, node: usize) -> bool {
+        self.sync.as_ref().is_none_or(|s| s.plan.node != node || s.stats.caught_up_at.is_some())
+    }
+
+    // Mine `blocks` blocks at a fixed interval, then drain everything in flight
+    pub fn run(&mut self, blocks: u64) {
+        for _ in 0..blocks {
+            self.step();
+        }
+        self.deliver_until(u64::MAX);
+    }
+
+    // Mine the next block, delivering whatever lands before it first. Views
+    // can be inspected between steps.
+    pub fn step(&mut self) {
+        let now = self.now();
+        self.deliver_until(now);
+        self.sample();
+        self.mine(now);
+    }
+
+    // Time of the next block
+    pub fn now(&self) -> u64 {
+        self.stats.mined * self.config.block_interval_ms
+    }
+
+    // What `node` holds right now, and what it is missing that others mined or hold
+    pub fn view(&self, node: usize) -> Option<NodeView> {
+        let view = self.views.get(node)?;
+        let mut tips: Vec<u64> = view.tips().collect();
+        tips.sort_unstable();
+        let mut ghost_tips: Vec<u64> = self
+            .views
+            .iter()
+            .flat_map(|other| other.tips())
+            .filter(|&id| !view.contains(id))
+            .collect::<HashSet<u64>>()
+            .into_iter()
+            .collect();
+        ghost_tips.sort_unstable();
+        let mut waiting: Vec<u64> = self.waiting[node].iter().map(|w| w.id).collect();
+        waiting.sort_unstable();
+        Some(NodeView {
+            node,
+            at: self.now(),
+            online: self.online(node, self.now()),
+            blocks: view.block_count(),
+            tips,
+            selected_parent: view.selected_parent(),
+            unseen: (1..self.next_id).filter(|&id| !view.contains(id)).collect(),
+            ghost_tips,
+            waiting,
+            in_flight: self.in_flight.iter().filter(|Reverse(d)| d.node == node).count(),
+        })
+    }
+
+    fn mine(&mut self, now: u64) {
+        let mut node = self.miner_dist.sample(&mut self.rng);
+        while !self.mining(node) {
+            node = self.miner_dist.sample(&mut self.rng);
+        }
+        let tips: Vec<u64> = self.views[node].tips().collect();
+        let parents = self.config.parent_selection.pick(&self.views[node], &tips, self.config.max_parents, &mut self.rng);
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stats.mined += 1;
+        let miner = node as u32;
+
+        self.views[node].insert_block_at(id, parents.clone(), vec![], Some(miner), now);
+        for (other, delay) in self.delays[node].iter().enumerate() {
+            if let Some(delay) = delay
+                && other != node
+            {
+                let at = now + delay;
+                self.in_flight.push(Reverse(Delivery { at, node: other, id, parents: parents.clone(), miner }));
+            }
+        }
+    }
+
+    // Process gossip and sync responses in time order up to `now`
+    pub(crate) fn deliver_until(&mut self, now: u64) {
+        loop {
+            let gossip_at = self.in_flight.peek().map(|Reverse(d)| d.at);
+            let sync_at = self.sync.as_ref().and_then(|s| s.pending.as_ref()).map(|&(at, _)| at);
+            match (gossip_at, sync_at) {
+                (_, Some(at)) if at <= now && gossip_at.is_none_or(|g| at < g) => self.sync_step(),
+                (Some(at), _) if at <= now => self.deliver_next(),
+                _ => break,
+            }
+        }
+    }
+
+    fn deliver_next(&mut self) {
+        let Reverse(delivery) = self.in_flight.pop().unwrap();
+        if !self.online(delivery.node, delivery.at) {
+            return;
+        }
+        let view = &mut self.views[delivery.node];
+        if view.contains(delivery.id) {
+            return;
+        }
+        if delivery.parents.iter().all(|p| view.contains(*p)) {
+            let node = delivery.node;
+            view.insert_block_at(delivery.id, delivery.parents, vec![], Some(delivery.miner), delivery.at);
+            self.release_waiting(node, delivery.at);
+        } else {
+            self.waiting[delivery.node].push(delivery);
+        }
+    }
+
+    // Apply the sync response that just landed and send the next request
+    fn sync_step(&mut self) {
+        let sync = self.sync.as_mut().unwrap();
+        let (now, step) = sync.pending.take().unwrap();
+        let node = sync.plan.node;
+
+        match step {
+            SyncStep::Join => {}
+            SyncStep::Headers(ids) if ids.is_empty() => {
+                sync.stats.caught_up_at = Some(now);
+                return;
+            }
+            SyncStep::Headers(ids) => sync.queue.extend(ids),
+            SyncStep::Blocks(blocks) => {
+                let view = &mut self.views[node];
+                for b in blocks {
+                    if !view.contains(b.id) {
+                        view.insert_block_at(b.id, b.parents, b.txs, b.miner, b.timestamp);
+                    }
+                }
+                self.waiting[node].retain(|w| !view.contains(w.id));
+                self.release_waiting(node, now);
+            }
+        }
+
+        let sync = self.sync.as_mut().unwrap();
+        let peer_view = &self.views[sync.stats.peer];
+        let view = &self.views[node];
+        let (at, step) = if sync.queue.is_empty() {
+            // Ask for every header the peer has that we don't, parents first
+            let mut ids: Vec<u64> = peer_view.blocks().map(|b| b.id()).filter(|&id| !view.contains(id)).collect();
+            ids.sort_unstable_by_key(|&id| (peer_view[id].topo_depth(), id));
+            let bytes = ids.len() as u64 * sync.plan.header_size;
+            sync.stats.header_rounds += 1;
+            sync.stats.headers += ids.len() as u64;
+            sync.stats.bytes += bytes;
+            (now + sync.round_trip_ms + transfer_ms(bytes, sync.plan.bandwidth), SyncStep::Headers(ids))
+        } else {
+            let take = sync.queue.len().min(sync.plan.batch_size);
+            let blocks: Vec<NewBlock> = sync
+                .queue
+                .drain(..take)
+                .map(|id| {
+                    let b = &peer_view[id];
+                    NewBlock { id, parents: b.parents().to_vec(), txs: b.txs().to_vec(), miner: b.miner(), timestamp: b.timestamp(), version: b.version() }
+                })
+                .collect();
+            let bytes = blocks.len() as u64 * self.config.block_size;
+            sync.stats.batches += 1;
+            sync.stats.blocks += blocks.len() as u64;
+            sync.stats.bytes += bytes;
+            (now + sync.round_trip_ms + transfer_ms(bytes, sync.plan.bandwidth), SyncStep::Blocks(blocks))
+        };
+        sync.pending = Some((at, step));
+    }
+
+    // Insert any of the node's waiting blocks whose parents have all arrived
+    fn release_waiting(&mut self, node: usize, now: u64) {
+        let view = &mut self.views[node];
+        let waiting = &mut self.waiting[node];
+        while let Some(i) = waiting.iter().position(|w| w.parents.iter().all(|p| view.contains(*p))) {
+            let delivery = waiting.swap_remove(i);
+            view.insert_block_at(delivery.id, delivery.parents, vec![], Some(delivery.miner), now.min(delivery.at));
+        }
+    }
+
+    // Only nodes that are mining count; a syncing node is expected to lag
+    fn sample(&mut self) {
+        let seen = self.stats.mined as usize + 1; // + genesis
+        let live: Vec<&ToyDag> = self.views.iter().enumerate().filter(|&(n, _)| self.mining(n)).map(|(_, v)| v).collect();
+        let sinks: HashSet<u64> = live.iter().map(|v| v.selected_parent()).collect();
+        let missing: Vec<usize> = live.iter().map(|v| seen.saturating_sub(v.block_count())).collect();
+        let tips: HashSet<u64> = live.iter().flat_map(|v| v.tips()).collect();
+        let ghost_tips = tips.iter().filter(|&&t| live.iter().any(|v| !v.contains(t))).count();
+        self.stats.timeline.push(Divergence {
+            at: self.now(),
+            mean_missing: mean(&missing),
+            max_missing: missing.iter().max().copied().unwrap_or(0),
+            ghost_tips,
+            sinks: sinks.len(),
+        });
+        self.stats.tips.extend(live.iter().map(|v| v.tip_count()));
+        self.stats.missing.extend(missing);
+        self.stats.distinct_sinks.push(sinks.len());
+    }
+
+    // Fraction of samples in which every node agreed on the selected parent
+    pub fn agreement(&self) -> f64 {
+        let agreed = self.stats.distinct_sinks.iter().filter(|&&n| n == 1).count();
+        agreed as f64 / self.stats.distinct_sinks.len().max(1) as f64
+    }
+
+    // Red share of each node's final view, averaged over nodes
+    pub fn red_rate(&self) -> f64 {
+        let rates: f64 = self
+            .views
+            .iter()
+            .map(|v| v.blocks().filter(|b| b.color() == Color::Red).count() as f64 / v.block_count() as f64)
+            .sum();
+        rates / self.views.len() as f64
+    }
+
+    // Whether every node ended up with every block
+    pub fn converged(&self) -> bool {
+        self.views.iter().all(|v| v.block_count() as u64 == self.stats.mined + 1)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "🌐 {} nodes mined {} blocks by {} ms", self.views.len(), self.stats.mined, self.now());
+        let _ = writeln!(out, "  Mean tips            : {:.2}", mean(&self.stats.tips));
+        let _ = writeln!(out, "  Mean missing         : {:.2}", mean(&self.stats.missing));
+        let _ = writeln!(out, "  Agreement            : {:.3}", self.agreement());
+        let _ = writeln!(out, "  Red rate             : {:.4}", self.red_rate());
+        let _ = writeln!(out, "  Converged            : {}", if self.converged() { "yes" } else { "no" });
+        out
+    }
+}
+
+// Run the same workload over each topology and compare tip divergence and red rates
+pub fn topology_experiment(topologies: &[Topology], config: &NodeSimConfig, blocks: u64, seed: u64) -> Result<String, String> {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Topology comparison: {} blocks, interval {} ms, {} B blocks, up to {} parents, seed {}\n",
+        blocks, config.block_interval_ms, config.block_size, config.max_parents, seed
+    );
+    let _ = writeln!(
+        out,
+        "{:<16} {:>5} {:>5} {:>10} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        "topology", "nodes", "links", "diameter", "mean tips", "missing", "agree", "red rate", "converged"
+    );
+
+    for topology in topologies {
+        let links = topology.links()?.len();
+        let diameter = topology.delays(config.block_size)?.iter().flatten().map(|d| d.unwrap_or(u64::MAX)).max().unwrap_or(0);
+        let mut sim = NodeSim::new(topology, config.clone(), seed)?;
+        sim.run(blocks);
+
+        let _ = writeln!(
+            out,
+            "{:<16} {:>5} {:>5} {:>10} {:>9.2} {:>9.2} {:>9.3} {:>9.4} {:>9}",
+            topology.name,
+            topology.nodes,
+            links,
+            if diameter == u64::MAX { "split".to_string() } else { format!("{} ms", diameter) },
+            mean(&sim.stats.tips),
+            mean(&sim.stats.missing),
+            sim.agreement(),
+            sim.red_rate(),
+            if sim.converged() { "yes" } else { "no" }
+        );
+    }
+    Ok(out)
+}
+
+// Run a topology with one node joining late and report how its sync went
+pub fn sync_experiment(topology: &Topology, config: &NodeSimConfig, plan: SyncPlan, blocks: u64, seed: u64) -> Result<String, String> {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Sync: node {} of {} joins at {} ms ({} blocks, interval {} ms, {} B blocks, {} B headers, batches of {}, seed {})\n",
+        plan.node,
+        topology.name,
+        plan.join_at_ms,
+        blocks,
+        config.block_interval_ms,
+        config.block_size,
+        plan.header_size,
+        plan.batch_size,
+        seed
+    );
+
+    let mut sim = NodeSim::new(topology, config.clone(), seed)?.with_sync(plan.clone(), topology)?;
+    sim.run(blocks);
+    let stats = sim.sync_stats().unwrap();
+
+    let _ = writeln!(out, "  Synced from peer     : node {}", stats.peer);
+    let _ = writeln!(out, "  Header rounds        : {}", stats.header_rounds);
+    let _ = writeln!(out, "  Headers received     : {}", stats.headers);
+    let _ = writeln!(out, "  Blocks downloaded    : {} in {} batches", stats.blocks, stats.batches);
+    let _ = writeln!(out, "  Bytes transferred    : {}", stats.bytes);
+    match stats.duration_ms() {
+        Some(ms) => {
+            let _ = writeln!(out, "  Sync duration        : {} ms", ms);
+            let _ = writeln!(out, "  Effective bandwidth  : {:.1} B/ms", stats.bytes as f64 / ms.max(1) as f64);
+        }
+        None => {
+            let _ = writeln!(out, "  Sync duration        : never caught up");
+        }
+    }
+    let synced = &sim.views[plan.node];
+    let reference = &sim.views[stats.peer];
+    let _ = writeln!(
+        out,
+        "  Final view           : {} blocks, selected parent {} ({})",
+        synced.block_count(),
+        synced.selected_parent(),
+        if synced.selected_parent() == reference.selected_parent() { "matches peer" } else { "differs from peer" }
+    );
+    Ok(out)
+}
+
+// Stop a run after `at_block` blocks to look at some nodes' views, then finish
+// it and show how far the views drifted apart over time, in about `rows` rows
+pub fn view_experiment(topology: &Topology, config: &NodeSimConfig, nodes: &[usize], at_block: u64, blocks: u64, rows: usize, seed: u64) -> Result<String, String> {
+    let mut sim = NodeSim::new(topology, config.clone(), seed)?;
+    if let Some(&node) = nodes.iter().find(|&&n| n >= sim.views.len()) {
+        return Err(format!("node {} is not one of the {} nodes", node, sim.views.len()));
+    }
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Views: {} ({} nodes), {} blocks, interval {} ms, {} B blocks, seed {}\n",
+        topology.name, topology.nodes, blocks, config.block_interval_ms, config.block_size, seed
+    );
+
+    for _ in 0..at_block.min(blocks) {
+        sim.step();
+    }
+    sim.deliver_until(sim.now());
+    for &node in nodes {
+        let _ = writeln!(out, "{}", sim.view(node).expect("checked above").report());
+    }
+    for _ in at_block.min(blocks)..blocks {
+        sim.step();
+    }
+    sim.deliver_until(u64::MAX);
+
+    let _ = writeln!(out, "{:>9} {:>12} {:>11} {:>10} {:>9}", "time (ms)", "mean missing", "max missing", "ghost tips", "sinks");
+    let timeline = &sim.stats.timeline;
+    let step = timeline.len().div_ceil(rows.max(1)).max(1);
+    for row in timeline.iter().step_by(step) {
+        let _ = writeln!(
+            out,
+            "{:>9} {:>12.2} {:>11} {:>10} {:>9}",
+            row.at, row.mean_missing, row.max_missing, row.ghost_tips, row.sinks
+        );
+    }
+    let ghosts: Vec<usize> = timeline.iter().map(|d| d.ghost_tips).collect();
+    let _ = writeln!(
+        out,
+        "\n  mean ghost tips {:.2}, nodes agreed on the selected parent {:.1}% of the time, {}",
+        mean(&ghosts),
+        100.0 * sim.agreement(),
+        if sim.converged() { "converged" } else { "did not converge" }
+    );
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::Shape;
+
+    fn ring(nodes: usize) -> Topology {
+        Topology { name: "ring".to_string(), nodes, shape: Shape::Ring, latency_ms: 100, bandwidth: 0, hashrates: vec![], edges: vec![] }
+    }
+
+    fn synced(bandwidth: u64) -> NodeSim {
+        let config = NodeSimConfig { block_size: 1000, ..NodeSimConfig::default() };
+        let plan = SyncPlan { node: 3, join_at_ms: 20_000, header_size: 80, batch_size: 5, bandwidth };
+        let mut sim = NodeSim::new(&ring(4), config, 1).unwrap().with_sync(plan, &ring(4)).unwrap();
+        sim.run(60);
+        sim
+    }
+
+    // Node 3 joins after about 20 blocks, downloads every header it was
+    // announced in batches of 5, and ends up with the same DAG as the rest
+    #[test]
+    fn late_node_syncs_from_a_neighbour_and_converges() {
+        let sim = synced(0);
+        let stats = sim.sync_stats().unwrap();
+        assert!(stats.peer == 0 || stats.peer == 2, "synced from non-neighbour {}", stats.peer);
+        assert!(stats.caught_up_at.is_some_and(|t| t > stats.joined_at));
+        assert!(stats.blocks >= 15, "only {} blocks downloaded", stats.blocks);
+        assert_eq!(stats.headers, stats.blocks);
+        assert!(stats.batches >= stats.blocks.div_ceil(5) && stats.header_rounds >= 2);
+        assert_eq!(stats.bytes, stats.headers * 80 + stats.blocks * 1000);
+        assert!(sim.converged());
+        assert_eq!(sim.views[3].selected_parent(), sim.views[0].selected_parent());
+    }
+
+    #[test]
+    fn a_thin_sync_link_takes_longer() {
+        let fast = synced(0).sync_stats().unwrap().duration_ms().unwrap();
+        let slow = synced(10).sync_stats().unwrap().duration_ms().unwrap();
+        assert!(slow > fast, "{} ms at 10 B/ms, {} ms unlimited", slow, fast);
+    }
+
+    #[test]
+    fn sync_plans_are_checked() {
+        let plan = SyncPlan { node: 4, join_at_ms: 0, header_size: 80, batch_size: 5, bandwidth: 0 };
+        let sim = NodeSim::new(&ring(4), NodeSimConfig::default(), 1).unwrap();
+        assert!(sim.with_sync(plan.clone(), &ring(4)).is_err());
+        let sim = NodeSim::new(&ring(4), NodeSimConfig::default(), 1).unwrap();
+        assert!(sim.with_sync(SyncPlan { node: 0, batch_size: 0, ..plan }, &ring(4)).is_err());
+    }
+
+    // At 50 ms blocks a ring of 8 needs four 400 ms hops to spread a block,
+    // so its anticones outgrow k; a full mesh needs one
+    #[test]
+    fn a_longer_path_turns_more_blocks_red() {
+        let config = NodeSimConfig { block_interval_ms: 50, ..NodeSimConfig::default() };
+        let red_rate = |shape: Shape| {
+            let topology = Topology { name: "t".to_string(), nodes: 8, shape, latency_ms: 400, bandwidth: 0, hashrates: vec![], edges: vec![] };
+            let mut sim = NodeSim::new(&topology, config.clone(), 42).unwrap();
+            sim.run(300);
+            sim.red_rate()
+        };
+        let (ring, mesh) = (red_rate(Shape::Ring), red_rate(Shape::Full));
+        assert!(ring > 0.2 && ring > mesh * 2.0, "ring {} vs full mesh {}", ring, mesh);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::Write as _;
+
+use toydag_core::{Color, ToyDag};
+
+// Who ended up with the chain and the rewards. Blocks without a known miner
+// count as honest; only blue blocks earn a reward, weighted by their work.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quality {
+    pub chain_blocks: usize,
+    pub chain_quality: f64, // Honest share of the selected chain
+    pub blue_blocks: usize,
+    pub blue_quality: f64,      // Honest share of the blue set
+    pub adversary_revenue: f64, // Adversary share of blue work
+}
+
+pub fn measure(dag: &ToyDag, adversaries: &[u32]) -> Quality {
+    let adversarial = |id: u64| dag[id].miner().is_some_and(|m| adversaries.contains(&m));
+
+    let chain: Vec<u64> = dag.selected_chain().into_iter().filter(|&id| id != 0).collect();
+    let adversary_chain = chain.iter().filter(|&&id| adversarial(id)).count();
+
+    let blues: Vec<u64> = dag
+        .blocks()
+        .filter(|b| b.id() != 0 && b.color() == Color::Blue)
+        .map(|b| b.id())
+        .collect();
+    let adversary_blues = blues.iter().filter(|&&id| adversarial(id)).count();
+    let total_work: f64 = blues.iter().map(|&id| dag[id].work() as f64).sum();
+    let adversary_work: f64 = blues.iter().filter(|&&id| adversarial(id)).map(|&id| dag[id].work() as f64).sum();
+
+    Quality {
+        chain_blocks: chain.len(),
+        chain_quality: 1.0 - share(adversary_chain as f64, chain.len() as f64),
+        blue_blocks: blues.len(),
+        blue_quality: 1.0 - share(adversary_blues as f64, blues.len() as f64),
+        adversary_revenue: share(adversary_work, total_work),
+    }
+}
+
+// `adversary_hashrate` is the adversaries' fraction of total hashrate; revenue
+// above it means the attack paid
+pub fn report(quality: &Quality, adversary_hashrate: f64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "🛡 Chain quality {:.1}% over {} chain blocks", quality.chain_quality * 100.0, quality.chain_blocks);
+    let _ = writeln!(out, "   Blue quality  {:.1}% over {} blue blocks", quality.blue_quality * 100.0, quality.blue_blocks);
+    let _ = writeln!(
+        out,
+        "   Adversary revenue {:.1}% on {:.1}% of hashrate ({:+.1} pts)",
+        quality.adversary_revenue * 100.0,
+        adversary_hashrate * 100.0,
+        (quality.adversary_revenue - adversary_hashrate) * 100.0
+    );
+    out
+}
+
+fn share(part: f64, total: f64) -> f64 {
+    if total == 0.0 { 0.0 } else { part / total }
+}
+
+#[cfg(test)]
+mod tests {
+    use toydag_core::knight::KMode;
+
+    use super::*;
+
+    // At k = 0 the adversary's sibling of block 1 goes red and earns
+    // nothing; its one chain block is a third of the chain and of the blues
+    #[test]
+    fn shares_count_chain_and_blue_blocks() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = KMode::Fixed(0);
+        dag.insert_block(1, vec![0], vec![], Some(0));
+        dag.insert_block(2, vec![0], vec![], Some(9));
+        dag.insert_block(3, vec![1], vec![], Some(9));
+        dag.insert_block(4, vec![3], vec![], Some(0));
+        assert_eq!(dag[2].color(), Color::Red);
+
+        let quality = measure(&dag, &[9]);
+        assert_eq!((quality.chain_blocks, quality.blue_blocks), (3, 3));
+        assert!((quality.chain_quality - 2.0 / 3.0).abs() < 1e-9);
+        assert!((quality.blue_quality - 2.0 / 3.0).abs() < 1e-9);
+        assert!((quality.adversary_revenue - 1.0 / 3.0).abs() < 1e-9);
+        assert!(report(&quality, 0.5).contains("Adversary revenue 33.3% on 50.0% of hashrate (-16.7 pts)"));
+
+        let honest = measure(&dag, &[]);
+        assert_eq!((honest.chain_quality, honest.blue_quality, honest.adversary_revenue), (1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn a_bare_genesis_has_nothing_to_share() {
+        let dag = ToyDag::new();
+        let quality = measure(&dag, &[0]);
+        assert_eq!((quality.chain_blocks, quality.blue_blocks), (0, 0));
+        assert_eq!((quality.chain_quality, quality.adversary_revenue), (1.0, 0.0));
+    }
+}
@@ -0,0 +1,603 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use toydag_core::coinbase::Coinbase;
+use toydag_core::knight::KMode;
+use toydag_core::{Color, ToyDag};
+
+use crate::ParentSelection;
+use crate::quality;
+use crate::watchdog::{OnViolation, Watchdog};
+
+// Declarative experiment description, loaded from a TOML or JSON file
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub blocks: u64, // Run length in blocks
+    #[serde(default = "default_k")]
+    pub k: usize,
+    #[serde(default = "default_stitch_threshold")]
+    pub stitch_threshold: usize,
+    #[serde(default = "default_block_interval_ms")]
+    pub block_interval_ms: u64,
+    #[serde(default = "default_max_parents")]
+    pub max_parents: usize,
+    #[serde(default)]
+    pub coinbase: Coinbase,
+    #[serde(default)]
+    pub on_finality_violation: OnViolation,
+    #[serde(default)]
+    pub parent_selection: ParentSelection, // For honest miners; attackers extend their own tip
+    #[serde(default)]
+    pub miners: Vec<Miner>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Miner {
+    pub name: String,
+    pub hashrate: f64, // Relative share; normalized across all miners
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub attacker: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    pub at: u64, // Block height at which the event fires
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Action {
+    Partition { groups: Vec<Vec<String>> },
+    Heal,
+    SetHashrate { miner: String, hashrate: f64 },
+    AttackStart { miner: String },
+    AttackStop { miner: String },
+}
+
+fn default_k() -> usize {
+    toydag_core::K
+}
+
+fn default_stitch_threshold() -> usize {
+    toydag_core::STITCH_THRESHOLD
+}
+
+fn default_block_interval_ms() -> u64 {
+    1000
+}
+
+fn default_max_parents() -> usize {
+    3
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            return serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e));
+        }
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Human-readable summary: parameters, agents, timeline, and a small schematic
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let total_hashrate: f64 = self.miners.iter().map(|m| m.hashrate).sum();
+
+        let _ = writeln!(out, "📜 Scenario: {}", self.name);
+        if !self.description.is_empty() {
+            let _ = writeln!(out, "{}", self.description);
+        }
+
+        let _ = writeln!(out, "\nParameters:");
+        let _ = writeln!(out, "  run length        {} blocks", self.blocks);
+        let _ = writeln!(out, "  k                 {}", self.k);
+        let _ = writeln!(out, "  stitch threshold  {} tips", self.stitch_threshold);
+        let _ = writeln!(out, "  block interval    {} ms", self.block_interval_ms);
+        let _ = writeln!(out, "  max parents       {}", self.max_parents);
+        let _ = writeln!(out, "  coinbase          {} per blue block, {} per red", self.coinbase.reward, self.coinbase.red_reward);
+        let _ = writeln!(out, "  parent selection  {}", format!("{:?}", self.parent_selection).to_lowercase());
+        let _ = writeln!(out, "  on violation      {}", format!("{:?}", self.on_finality_violation).to_lowercase());
+
+        let _ = writeln!(out, "\nAgents ({}):", self.miners.len());
+        for m in &self.miners {
+            let share = if total_hashrate > 0.0 { 100.0 * m.hashrate / total_hashrate } else { 0.0 };
+            let _ = writeln!(
+                out,
+                "  {} {:<12} {:>5.1}% hashrate, {} ms latency",
+                if m.attacker { "😈" } else { "⛏️" },
+                m.name,
+                share,
+                m.latency_ms
+            );
+        }
+
+        let mut events: Vec<&Event> = self.events.iter().collect();
+        events.sort_by_key(|e| e.at);
+
+        let _ = writeln!(out, "\nTimeline:");
+        if events.is_empty() {
+            let _ = writeln!(out, "  (no events — steady state for the whole run)");
+        }
+        for e in &events {
+            let _ = writeln!(out, "  @{:<6} {}", e.at, e.action.describe());
+        }
+
+        let _ = writeln!(out, "\nSchematic:");
+        out.push_str(&self.schematic(&events));
+        out
+    }
+
+    fn schematic(&self, events: &[&Event]) -> String {
+        let mut out = String::new();
+
+        // Miners fanning into the shared DAG
+        let n = self.miners.len();
+        for (i, m) in self.miners.iter().enumerate() {
+            let joint = match (i, n) {
+                (_, 1) => "───",
+                (0, _) => "──┐",
+                (i, n) if i == n - 1 => "──┘",
+                _ => "──┤",
+            };
+            let dag = if i == n / 2 { " DAG" } else { "" };
+            let _ = writeln!(out, "  [{:<12}]{}{}", m.name, joint, dag);
+        }
+
+        // Timeline bar with one marker per event
+        const WIDTH: usize = 40;
+        let mut bar = ['─'; WIDTH];
+        for e in events {
+            let pos = if self.blocks == 0 {
+                0
+            } else {
+                (e.at.min(self.blocks) as usize * (WIDTH - 1)) / self.blocks as usize
+            };
+            bar[pos] = e.action.marker();
+        }
+        let _ = writeln!(out, "\n  0 |{}| {}", bar.iter().collect::<String>(), self.blocks);
+        out
+    }
+}
+
+impl Action {
+    fn describe(&self) -> String {
+        match self {
+            Action::Partition { groups } => {
+                let groups: Vec<String> = groups.iter().map(|g| format!("{{{}}}", g.join(", "))).collect();
+                format!("partition network into {}", groups.join(" | "))
+            }
+            Action::Heal => "heal all partitions".to_string(),
+            Action::SetHashrate { miner, hashrate } => format!("{} hashrate → {}", miner, hashrate),
+            Action::AttackStart { miner } => format!("{} starts attacking", miner),
+            Action::AttackStop { miner } => format!("{} stops attacking", miner),
+        }
+    }
+
+    fn marker(&self) -> char {
+        match self {
+            Action::Partition { .. } => 'P',
+            Action::Heal => 'H',
+            Action::SetHashrate { .. } => 'R',
+            Action::AttackStart { .. } => 'A',
+            Action::AttackStop { .. } => 'a',
+        }
+    }
+}
+
+// A block on its way from the miner that made it to one other miner
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Delivery {
+    at: u64,
+    to: usize,
+    id: u64,
+    parents: Vec<u64>,
+    from: usize,
+}
+
+// Live state of a running scenario. Every miner keeps its own view and hears
+// the others through a hub: a block takes the sender's latency plus the
+// receiver's. Blocks between partition groups are held until the heal. An
+// attacking miner withholds its blocks and mines only on its own selected tip,
+// releasing everything it withheld when the attack stops.
+struct Run<'a> {
+    scenario: &'a Scenario,
+    views: Vec<ToyDag>,
+    hashrates: Vec<f64>,
+    miner_dist: WeightedIndex<f64>,
+    groups: Option<Vec<usize>>, // Partition group per miner while partitioned
+    attacking: Vec<bool>,
+    withheld: Vec<Vec<(u64, Vec<u64>)>>, // Per miner, in mining order
+    in_flight: BinaryHeap<Reverse<Delivery>>,
+    held: Vec<Delivery>,         // Stuck behind the partition
+    waiting: Vec<Vec<Delivery>>, // Per miner, delivered before their parents
+    mined: Vec<u64>,
+    rng: StdRng,
+    next_id: u64,
+    log: Vec<String>,
+}
+
+impl Scenario {
+    // Execute the scenario and report how each miner fared
+    pub fn run(&self, seed: u64) -> Result<String, String> {
+        let mut run = Run::new(self, seed)?;
+        let honest = (0..self.miners.len()).filter(|&m| !self.miners[m].attacker);
+        let mut watchdog = Watchdog::watch(&mut run.views, honest);
+        let mut events: Vec<&Event> = self.events.iter().collect();
+        events.sort_by_key(|e| e.at);
+        let mut events = events.into_iter().peekable();
+
+        let mut aborted_at = None;
+        for height in 0..self.blocks {
+            let now = height * self.block_interval_ms;
+            run.deliver_until(now);
+            while let Some(event) = events.next_if(|e| e.at <= height) {
+                run.apply(&event.action, now)?;
+                run.log.push(format!("@{:<6} {}", event.at, event.action.describe()));
+            }
+            run.mine(now);
+            if watchdog.check(&run.views, now) && self.on_finality_violation == OnViolation::Abort {
+                aborted_at = Some(height);
+                break;
+            }
+        }
+        if aborted_at.is_none() {
+            run.deliver_until(u64::MAX);
+            watchdog.check(&run.views, self.blocks * self.block_interval_ms);
+        }
+
+        let mut out = run.report(seed);
+        out.push('\n');
+        out.push_str(&watchdog.report());
+        if let Some(height) = aborted_at {
+            let _ = writeln!(out, "  run aborted at height {} of {}", height, self.blocks);
+        }
+        Ok(out)
+    }
+
+    fn miner_index(&self, name: &str) -> Result<usize, String> {
+        self.miners.iter().position(|m| m.name == name).ok_or_else(|| format!("unknown miner '{}'", name))
+    }
+}
+
+impl<'a> Run<'a> {
+    fn new(scenario: &'a Scenario, seed: u64) -> Result<Self, String> {
+        let n = scenario.miners.len();
+        if n == 0 {
+            return Err(format!("scenario '{}' has no miners", scenario.name));
+        }
+        for event in &scenario.events {
+            event.action.check(scenario)?;
+        }
+        let hashrates: Vec<f64> = scenario.miners.iter().map(|m| m.hashrate).collect();
+        let miner_dist = WeightedIndex::new(&hashrates).map_err(|e| format!("hashrates: {}", e))?;
+        let views = (0..n)
+            .map(|_| {
+                let mut dag = ToyDag::new();
+                dag.verbose = false;
+                dag.k_mode = KMode::Fixed(scenario.k);
+                dag
+            })
+            .collect();
+        Ok(Run {
+            scenario,
+            views,
+            hashrates,
+            miner_dist,
+            groups: None,
+            attacking: vec![false; n],
+            withheld: vec![Vec::new(); n],
+            in_flight: BinaryHeap::new(),
+            held: Vec::new(),
+            waiting: vec![Vec::new(); n],
+            mined: vec![0; n],
+            rng: StdRng::seed_from_u64(seed),
+            next_id: 1,
+            log: Vec::new(),
+        })
+    }
+
+    fn apply(&mut self, action: &Action, now: u64) -> Result<(), String> {
+        match action {
+            Action::Partition { groups } => {
+                // Miners named in no group are cut off on their own
+                let n = self.views.len();
+                let mut assignment: Vec<usize> = (groups.len()..groups.len() + n).collect();
+                for (g, names) in groups.iter().enumerate() {
+                    for name in names {
+                        assignment[self.scenario.miner_index(name)?] = g;
+                    }
+                }
+                self.groups = Some(assignment);
+            }
+            Action::Heal => {
+                self.groups = None;
+                for mut delivery in std::mem::take(&mut self.held) {
+                    delivery.at = now + self.scenario.miners[delivery.to].latency_ms;
+                    self.in_flight.push(Reverse(delivery));
+                }
+            }
+            Action::SetHashrate { miner, hashrate } => {
+                self.hashrates[self.scenario.miner_index(miner)?] = *hashrate;
+                self.miner_dist = WeightedIndex::new(&self.hashrates).map_err(|e| format!("hashrates: {}", e))?;
+            }
+            Action::AttackStart { miner } => self.attacking[self.scenario.miner_index(miner)?] = true,
+            Action::AttackStop { miner } => {
+                let m = self.scenario.miner_index(miner)?;
+                self.attacking[m] = false;
+                for (id, parents) in std::mem::take(&mut self.withheld[m]) {
+                    self.broadcast(m, id, parents, now);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Honest miners reference up to `max_parents` tips, or all of them once
+    // there are `stitch_threshold` or more, the way StitchBot would
+    fn mine(&mut self, now: u64) {
+        let m = self.miner_dist.sample(&mut self.rng);
+        let view = &self.views[m];
+        let parents: Vec<u64> = if self.attacking[m] {
+            vec![view.selected_parent()]
+        } else {
+            let tips: Vec<u64> = view.tips().collect();
+            let take = if tips.len() >= self.scenario.stitch_threshold { tips.len() } else { self.scenario.max_parents };
+            self.scenario.parent_selection.pick(view, &tips, take, &mut self.rng)
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.mined[m] += 1;
+        self.views[m].insert_block_at(id, parents.clone(), vec![], Some(m as u32), now);
+        if self.attacking[m] {
+            self.withheld[m].push((id, parents));
+        } else {
+            self.broadcast(m, id, parents, now);
+        }
+    }
+
+    fn broadcast(&mut self, from: usize, id: u64, parents: Vec<u64>, now: u64) {
+        let sender_latency = self.scenario.miners[from].latency_ms;
+        for (to, miner) in self.scenario.miners.iter().enumerate() {
+            if to != from {
+                let at = now + sender_latency + miner.latency_ms;
+                self.in_flight.push(Reverse(Delivery { at, to, id, parents: parents.clone(), from }));
+            }
+        }
+    }
+
+    fn deliver_until(&mut self, now: u64) {
+        while self.in_flight.peek().is_some_and(|Reverse(d)| d.at <= now) {
+            let Reverse(delivery) = self.in_flight.pop().unwrap();
+            if self.groups.as_ref().is_some_and(|g| g[delivery.from] != g[delivery.to]) {
+                self.held.push(delivery);
+                continue;
+            }
+            let to = delivery.to;
+            let view = &mut self.views[to];
+            if view.contains(delivery.id) {
+                continue;
+            }
+            if delivery.parents.iter().all(|p| view.contains(*p)) {
+                view.insert_block_at(delivery.id, delivery.parents, vec![], Some(delivery.from as u32), delivery.at);
+                self.release_waiting(to);
+            } else {
+                self.waiting[to].push(delivery);
+            }
+        }
+    }
+
+    fn release_waiting(&mut self, to: usize) {
+        let view = &mut self.views[to];
+        let waiting = &mut self.waiting[to];
+        while let Some(i) = waiting.iter().position(|w| w.parents.iter().all(|p| view.contains(*p))) {
+            let delivery = waiting.swap_remove(i);
+            view.insert_block_at(delivery.id, delivery.parents, vec![], Some(delivery.from as u32), delivery.at);
+        }
+    }
+
+    // Numbers come from the first honest miner's view, the closest thing to
+    // what the network as a whole ended up agreeing on
+    fn report(&self, seed: u64) -> String {
+        let scenario = self.scenario;
+        let attackers: Vec<u32> = (0..scenario.miners.len()).filter(|&m| scenario.miners[m].attacker).map(|m| m as u32).collect();
+        let observer = (0..scenario.miners.len()).find(|&m| !scenario.miners[m].attacker).unwrap_or(0);
+        let dag = &self.views[observer];
+
+        let mut out = String::new();
+        let _ = writeln!(out, "🎬 Scenario: {} ({} blocks, seed {})\n", scenario.name, scenario.blocks, seed);
+        if !self.log.is_empty() {
+            let _ = writeln!(out, "Events:");
+            for line in &self.log {
+                let _ = writeln!(out, "  {}", line);
+            }
+            let _ = writeln!(out);
+        }
+
+        let chain: Vec<u64> = dag.selected_chain().into_iter().filter(|&id| id != dag.genesis()).collect();
+        let total_hashrate: f64 = scenario.miners.iter().map(|m| m.hashrate).sum();
+        let _ = writeln!(out, "{:<14} {:>9} {:>7} {:>10} {:>10} {:>12}", "miner", "hashrate", "mined", "unreleased", "blue rate", "chain share");
+        for (m, miner) in scenario.miners.iter().enumerate() {
+            let ours: Vec<u64> = dag.blocks().filter(|b| b.miner() == Some(m as u32)).map(|b| b.id()).collect();
+            let blue = ours.iter().filter(|&&id| dag[id].color() == Color::Blue).count();
+            let on_chain = chain.iter().filter(|&&id| dag[id].miner() == Some(m as u32)).count();
+            let _ = writeln!(
+                out,
+                "{} {:<12} {:>8.1}% {:>7} {:>10} {:>9.1}% {:>11.1}%",
+                if miner.attacker { "😈" } else { "⛏️" },
+                miner.name,
+                100.0 * miner.hashrate / total_hashrate,
+                self.mined[m],
+                self.withheld[m].len(),
+                100.0 * blue as f64 / ours.len().max(1) as f64,
+                100.0 * on_chain as f64 / chain.len().max(1) as f64
+            );
+        }
+
+        let sinks: HashSet<u64> = self.views.iter().map(|v| v.selected_parent()).collect();
+        let _ = writeln!(out, "\nObserver          : {}", scenario.miners[observer].name);
+        let _ = writeln!(out, "Blocks in view    : {} of {}", dag.block_count() - 1, self.next_id - 1);
+        let _ = writeln!(out, "Reorgs            : {} (max depth {})", dag.stats.reorg_depths.len(), dag.stats.reorg_depths.iter().max().copied().unwrap_or(0));
+        let _ = writeln!(out, "Distinct sinks    : {}{}", sinks.len(), if self.groups.is_some() { " (still partitioned)" } else { "" });
+        out.push('\n');
+        out.push_str(&dag.earnings(&scenario.coinbase).report(|m| match m {
+            Some(m) => scenario.miners[m as usize].name.clone(),
+            None => "unknown".to_string(),
+        }));
+        if !attackers.is_empty() {
+            let share: f64 = attackers.iter().map(|&m| scenario.miners[m as usize].hashrate).sum::<f64>() / total_hashrate;
+            out.push('\n');
+            out.push_str(&quality::report(&quality::measure(dag, &attackers), share));
+        }
+        out
+    }
+}
+
+impl Action {
+    // Reject names that match no miner before anything runs
+    fn check(&self, scenario: &Scenario) -> Result<(), String> {
+        match self {
+            Action::Partition { groups } => groups.iter().flatten().try_for_each(|name| scenario.miner_index(name).map(|_| ())),
+            Action::Heal => Ok(()),
+            Action::SetHashrate { miner, .. } | Action::AttackStart { miner } | Action::AttackStop { miner } => {
+                scenario.miner_index(miner).map(|_| ())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(events: &str) -> Scenario {
+        let text = format!(
+            r#"
+name = "test"
+blocks = 100
+
+[[miners]]
+name = "alice"
+hashrate = 1.0
+latency_ms = 100
+
+[[miners]]
+name = "bob"
+hashrate = 1.0
+latency_ms = 100
+{}"#,
+            events
+        );
+        toml::from_str(&text).unwrap()
+    }
+
+    fn mine(run: &mut Run, heights: std::ops::Range<u64>) {
+        for height in heights {
+            let now = height * run.scenario.block_interval_ms;
+            run.deliver_until(now);
+            run.mine(now);
+        }
+    }
+
+    fn mined_by(view: &ToyDag, miner: u32) -> usize {
+        view.blocks().filter(|b| b.miner() == Some(miner)).count()
+    }
+
+    // Across a partition neither side sees the other's blocks; the heal
+    // sends them on and both views end up with everything
+    #[test]
+    fn partitions_hold_blocks_until_the_heal() {
+        let s = scenario("");
+        let mut run = Run::new(&s, 42).unwrap();
+        run.apply(&Action::Partition { groups: vec![vec!["alice".into()], vec!["bob".into()]] }, 0).unwrap();
+        mine(&mut run, 0..20);
+        run.deliver_until(u64::MAX);
+        assert!(run.mined.iter().all(|&m| m > 0));
+        assert_eq!(mined_by(&run.views[0], 1), 0);
+        assert_eq!(mined_by(&run.views[1], 0), 0);
+        assert_eq!(run.held.len(), 20);
+
+        run.apply(&Action::Heal, 20_000).unwrap();
+        run.deliver_until(u64::MAX);
+        assert!(run.held.is_empty());
+        for view in &run.views {
+            assert_eq!(view.block_count(), 21);
+        }
+        assert_eq!(run.views[0].selected_parent(), run.views[1].selected_parent());
+    }
+
+    // An attacker mines on its own tip and keeps it to itself until the attack stops
+    #[test]
+    fn attackers_withhold_until_they_stop() {
+        let s = scenario("");
+        let mut run = Run::new(&s, 42).unwrap();
+        run.apply(&Action::AttackStart { miner: "bob".into() }, 0).unwrap();
+        mine(&mut run, 0..20);
+        run.deliver_until(u64::MAX);
+        let withheld = run.withheld[1].len();
+        assert_eq!(withheld as u64, run.mined[1]);
+        assert!(withheld > 0);
+        assert_eq!(mined_by(&run.views[0], 1), 0);
+        assert!(run.views[1].blocks().filter(|b| b.miner() == Some(1)).all(|b| b.parents().len() == 1));
+
+        run.apply(&Action::AttackStop { miner: "bob".into() }, 20_000).unwrap();
+        run.deliver_until(u64::MAX);
+        assert!(run.withheld[1].is_empty());
+        assert_eq!(mined_by(&run.views[0], 1), withheld);
+    }
+
+    #[test]
+    fn unknown_miners_are_rejected_before_running() {
+        let s = scenario(
+            r#"
+[[events]]
+at = 10
+kind = "attack-start"
+miner = "carol"
+"#,
+        );
+        assert_eq!(s.run(42).unwrap_err(), "unknown miner 'carol'");
+        let s = Scenario { miners: vec![], ..scenario("") };
+        assert_eq!(s.run(42).unwrap_err(), "scenario 'test' has no miners");
+    }
+
+    #[test]
+    fn a_healed_run_logs_its_events_and_ends_on_one_sink() {
+        let s = scenario(
+            r#"
+[[events]]
+at = 60
+kind = "heal"
+
+[[events]]
+at = 20
+kind = "partition"
+groups = [["alice"], ["bob"]]
+"#,
+        );
+        let report = s.run(42).unwrap();
+        let partition = report.find("@20     partition network into {alice} | {bob}").unwrap();
+        let heal = report.find("@60     heal all partitions").unwrap();
+        assert!(partition < heal);
+        assert!(report.contains("Blocks in view    : 100 of 100"));
+        assert!(report.contains("Distinct sinks    : 1\n"));
+    }
+}
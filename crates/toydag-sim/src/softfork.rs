@@ -0,0 +1,214 @@
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::error::DagError;
+use toydag_core::softfork::{Phase, SoftFork};
+use toydag_core::{BLOCK_VERSION, NewBlock, ToyDag};
+
+use crate::ParentSelection;
+use crate::daa::visible_tips;
+
+const MAX_PARENTS: usize = 3;
+
+// Who mines what while a soft fork rolls out. The rest of the hashrate has
+// upgraded and signals from the start.
+#[derive(Debug, Clone)]
+pub struct RolloutConfig {
+    pub fork: SoftFork,
+    pub stragglers: f64, // Share still on the old rules; never signals
+    pub vetoers: f64,    // Share that has upgraded but won't signal, and goes along once the fork is active
+    pub upgrade_lag: Option<u64>, // Blocks after activation until stragglers upgrade; never when None
+    pub interval_ms: u64,         // Mean time between blocks
+    pub delay_ms: u64,            // How old a block must be before miners build on it
+    pub blocks: u64,
+}
+
+// One signaling period as the final selected chain tallied it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub index: u64,
+    pub signaled: u64,
+    pub phase: Phase, // After the period closed
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rollout {
+    pub periods: Vec<Period>,
+    pub locked_in: Option<u64>, // Period that locked the fork in
+    pub active_at: Option<u64>, // Block number at which the network first saw it active
+    pub straggler_blocks: u64,
+    pub refused: usize, // Straggler blocks thrown out under the new rules
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Miner {
+    Upgraded,
+    Straggler,
+    Vetoer,
+}
+
+// Mine `blocks` blocks at random intervals. Miners see blocks once they are
+// `delay_ms` old and pick their version by who they are and what phase the
+// network is in; old-version blocks are refused once the fork is active in
+// their past.
+pub fn simulate(config: &RolloutConfig, seed: u64) -> Rollout {
+    let fork = &config.fork;
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag.soft_fork = Some(fork.clone());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut result = Rollout::default();
+    let mut now = 0u64;
+
+    for i in 1..=config.blocks {
+        let u: f64 = rng.gen_range(0.0..1.0);
+        now += (-(1.0 - u).ln() * config.interval_ms as f64).round() as u64;
+        let miner = match rng.gen_range(0.0..1.0) {
+            r if r < config.stragglers => Miner::Straggler,
+            r if r < config.stragglers + config.vetoers => Miner::Vetoer,
+            _ => Miner::Upgraded,
+        };
+        let upgraded = match miner {
+            Miner::Upgraded => true,
+            Miner::Vetoer => result.active_at.is_some(),
+            Miner::Straggler => result.active_at.zip(config.upgrade_lag).is_some_and(|(at, lag)| i >= at + lag),
+        };
+        result.straggler_blocks += (miner == Miner::Straggler) as u64;
+
+        let tips = visible_tips(&dag, now.saturating_sub(config.delay_ms));
+        let parents = ParentSelection::Weighted.pick(&dag, &tips, MAX_PARENTS, &mut rng);
+        let version = if upgraded { fork.version } else { BLOCK_VERSION };
+        let block = NewBlock { id: dag.next_id(), parents, txs: vec![], miner: None, timestamp: now, version };
+        if let Err(DagError::OutdatedVersion { .. }) = dag.add_block(block) {
+            result.refused += 1;
+        }
+        if result.active_at.is_none() && dag.fork_phase() == Some(Phase::Active) {
+            result.active_at = Some(i);
+        }
+    }
+
+    // Read the periods back off the chain everyone settled on
+    let mut closed = 0;
+    for id in dag.selected_chain() {
+        let deployment = dag.deployment(id).expect("the DAG runs a soft fork");
+        if deployment.periods > closed {
+            closed = deployment.periods;
+            let period = Period { index: closed, signaled: deployment.last_signaled, phase: deployment.phase };
+            if period.phase != Phase::Signaling && result.locked_in.is_none() {
+                result.locked_in = Some(closed);
+            }
+            result.periods.push(period);
+        }
+    }
+    result
+}
+
+fn header(out: &mut String, config: &RolloutConfig, seed: u64) {
+    let _ = writeln!(
+        out,
+        "🔀 Soft fork to version {}: locks in at {:.0}% of {} blue blocks, seed {}",
+        config.fork.version,
+        config.fork.threshold * 100.0,
+        config.fork.window,
+        seed
+    );
+    let _ = writeln!(
+        out,
+        "   {:.0}% stragglers ({}), one block every {} ms, {} ms delay",
+        config.stragglers * 100.0,
+        config.upgrade_lag.map_or("never upgrade".to_string(), |lag| format!("upgrade {} blocks after activation", lag)),
+        config.interval_ms,
+        config.delay_ms
+    );
+}
+
+// One rollout, period by period
+pub fn report(config: &RolloutConfig, seed: u64) -> String {
+    let rollout = simulate(config, seed);
+    let mut out = String::new();
+    header(&mut out, config, seed);
+    let _ = writeln!(out, "   {:.0}% vetoing\n", config.vetoers * 100.0);
+    let _ = writeln!(out, "{:>7} {:>9} {:>7}  phase", "period", "signaled", "share");
+    for period in &rollout.periods {
+        let _ = writeln!(
+            out,
+            "{:>7} {:>9} {:>6.1}%  {}",
+            period.index,
+            period.signaled,
+            100.0 * period.signaled as f64 / config.fork.window.max(1) as f64,
+            period.phase.name()
+        );
+    }
+    let _ = writeln!(out);
+    match (rollout.locked_in, rollout.active_at) {
+        (Some(period), Some(block)) => {
+            let _ = writeln!(out, "Locked in by period {}, active from block {}", period, block);
+        }
+        (Some(period), None) => {
+            let _ = writeln!(out, "Locked in by period {}, not active before the run ended", period);
+        }
+        _ => {
+            let _ = writeln!(out, "Never locked in");
+        }
+    }
+    let _ = writeln!(out, "Straggler blocks refused: {} of {}", rollout.refused, rollout.straggler_blocks);
+    out
+}
+
+// The same rollout against growing shares of vetoing hashrate
+pub fn experiment(config: &RolloutConfig, veto_shares: &[f64], seed: u64) -> String {
+    let mut out = String::new();
+    header(&mut out, config, seed);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{:>6} {:>10} {:>10} {:>10} {:>9}", "veto", "signaling", "locked in", "active at", "refused");
+    for &vetoers in veto_shares {
+        let rollout = simulate(&RolloutConfig { vetoers, ..config.clone() }, seed);
+        // Mean over the periods up to the one that locked it in
+        let counted = rollout.periods.iter().position(|p| p.phase != Phase::Signaling).map_or(rollout.periods.len(), |i| i + 1);
+        let signaling: u64 = rollout.periods[..counted].iter().map(|p| p.signaled).sum();
+        let signaling_periods = counted.max(1) as u64;
+        let _ = writeln!(
+            out,
+            "{:>5.0}% {:>9.1}% {:>10} {:>10} {:>9}",
+            vetoers * 100.0,
+            100.0 * signaling as f64 / (signaling_periods * config.fork.window.max(1)) as f64,
+            rollout.locked_in.map_or("never".to_string(), |p| format!("period {}", p)),
+            rollout.active_at.map_or("-".to_string(), |b| format!("block {}", b)),
+            rollout.refused
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(threshold: f64, vetoers: f64) -> RolloutConfig {
+        let fork = SoftFork { window: 20, threshold, ..SoftFork::new(BLOCK_VERSION + 1) };
+        RolloutConfig { fork, stragglers: 0.0, vetoers, upgrade_lag: None, interval_ms: 1000, delay_ms: 0, blocks: 120 }
+    }
+
+    #[test]
+    fn signaling_right_at_the_threshold_locks_in_and_activates() {
+        // Everyone signals and the threshold asks for everyone
+        let rollout = simulate(&config(1.0, 0.0), 1);
+        assert_eq!(rollout.locked_in, Some(1));
+        assert_eq!(rollout.periods[0], Period { index: 1, signaled: 20, phase: Phase::LockedIn });
+        assert_eq!(rollout.periods[1].phase, Phase::Active);
+        assert!(rollout.active_at.is_some());
+        assert_eq!(rollout.refused, 0);
+    }
+
+    #[test]
+    fn signaling_below_the_threshold_never_locks_in() {
+        let rollout = simulate(&config(0.75, 0.5), 1);
+        assert!(rollout.periods.len() >= 3);
+        let needed = (0.75 * 20.0) as u64;
+        assert!(rollout.periods.iter().all(|p| p.signaled < needed && p.phase == Phase::Signaling));
+        assert_eq!((rollout.locked_in, rollout.active_at), (None, None));
+        assert!(report(&config(0.75, 0.5), 1).contains("Never locked in"));
+    }
+}
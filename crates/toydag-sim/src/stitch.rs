@@ -0,0 +1,82 @@
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::stats::mean;
+use toydag_core::stitch::{AdaptiveStitch, Antichain, MergeAll, RateLimited, StitchMode, StitchPolicy, TopByBlueScore};
+use toydag_core::{STALE_WINDOW, STITCH_THRESHOLD, ToyDag};
+
+// Outcome of one policy on the benchmark workload
+struct BenchResult {
+    name: String,
+    final_threshold: usize,
+    activations: usize,
+    mean_tips: f64,
+    max_tips: usize,
+    mean_merge_latency: f64,
+}
+
+// Built-in benchmark: the same seeded, fork-heavy workload under both threshold
+// modes, then the fixed threshold with each alternative tip-selection policy
+pub fn benchmark(blocks: u64, seed: u64) -> String {
+    let runs: Vec<(StitchMode, Box<dyn StitchPolicy>)> = vec![
+        (StitchMode::Fixed(STITCH_THRESHOLD), Box::new(MergeAll)),
+        (StitchMode::Adaptive(AdaptiveStitch::new(STITCH_THRESHOLD, 4.0)), Box::new(MergeAll)),
+        (StitchMode::Fixed(STITCH_THRESHOLD), Box::new(TopByBlueScore { n: 3 })),
+        (StitchMode::Fixed(STITCH_THRESHOLD), Box::new(Antichain)),
+        (StitchMode::Fixed(STITCH_THRESHOLD), Box::new(RateLimited::new(Box::new(MergeAll), 20))),
+    ];
+
+    let results: Vec<BenchResult> =
+        runs.into_iter().map(|(mode, policy)| run_workload(mode, policy, blocks, seed)).collect();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "StitchBot policy benchmark: {} blocks, seed {}\n", blocks, seed);
+    let _ = writeln!(
+        out,
+        "{:<22} {:>10} {:>12} {:>10} {:>9} {:>14}",
+        "policy", "threshold", "activations", "mean tips", "max tips", "merge latency"
+    );
+    for r in &results {
+        let _ = writeln!(
+            out,
+            "{:<22} {:>10} {:>12} {:>10.2} {:>9} {:>14.2}",
+            r.name, r.final_threshold, r.activations, r.mean_tips, r.max_tips, r.mean_merge_latency
+        );
+    }
+    out
+}
+
+fn run_workload(mode: StitchMode, policy: Box<dyn StitchPolicy>, blocks: u64, seed: u64) -> BenchResult {
+    let name = format!("{}/{}", mode.name(), policy.name());
+    let mut dag = ToyDag::new();
+    dag.stitch_mode = mode;
+    dag.stitch_policy = policy;
+    dag.verbose = false;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for i in 1..=blocks {
+        // Miners only see a stale window of recent blocks, so forks pile up
+        let recent: Vec<u64> = (dag.next_id().saturating_sub(STALE_WINDOW)..dag.next_id()).collect();
+        let num_parents = if rng.gen_bool(0.3) { recent.len().min(2) } else { 1 };
+        let parents: Vec<u64> = recent.choose_multiple(&mut rng, num_parents).copied().collect();
+
+        dag.create_block(parents).expect("recent ids are all in the DAG");
+
+        if i.is_multiple_of(5) {
+            dag.stitch_if_needed();
+        }
+    }
+
+    let stats = &dag.stats;
+    BenchResult {
+        name,
+        final_threshold: dag.stitch_mode.threshold(),
+        activations: stats.stitch_activations,
+        mean_tips: mean(&stats.tip_counts),
+        max_tips: stats.tip_counts.iter().max().copied().unwrap_or(0),
+        mean_merge_latency: mean(&stats.merge_latencies),
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt::Write as _;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use toydag_core::ToyDag;
+use toydag_core::daa::Daa;
+use toydag_core::score::BlueWork;
+
+use crate::daa::visible_tips;
+
+#[derive(Debug, Clone, Copy)]
+pub struct WarpConfig {
+    pub hashrate: f64, // Work per ms, all miners together
+    pub share: f64,    // Attacker's part of it
+    pub warp_ms: i64,
+    pub delay_ms: u64, // How old a block must be before miners build on it
+    pub blocks: u64,
+}
+
+// Outcome of one time-warp run
+#[derive(Debug, Clone, Default)]
+pub struct Warp {
+    pub mean_difficulty: f64, // Over the second half, once the DAA has settled
+    pub interval_ms: f64,     // Real time per blue block over the second half
+    pub attacker_blocks: usize,
+    pub stale_timestamps: usize, // Attacker blocks the past-median-time rule threw out
+}
+
+// Mine at constant hashrate with the DAA on, the same way `daa::experiment`
+// does. The attacker, holding `share` of the hashrate, stamps every block it finds
+// `warp_ms` away from the real time (ahead when positive, behind when
+// negative) to drag the DAA's measured block rate. Honest miners stamp the
+// real time, or just past their parents' median time if that is later, so
+// the rule never refuses them.
+pub fn simulate(daa: &Daa, config: &WarpConfig, seed: u64) -> Warp {
+    let WarpConfig { hashrate, share, warp_ms, delay_ms, blocks } = *config;
+    let mut dag = ToyDag::new();
+    dag.verbose = false;
+    dag.daa = Some(daa.clone());
+    dag.block_work = (hashrate * daa.target_interval as f64).round().max(1.0) as BlueWork;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut result = Warp::default();
+    let mut now = 0u64;
+    let (mut settled_at, mut settled_score, mut work, mut counted) = (0, 0, 0.0, 0);
+
+    for i in 1..=blocks {
+        let difficulty = daa.next_work(&dag, dag.selected_parent()) as f64;
+        let u: f64 = rng.gen_range(0.0..1.0);
+        now += (-(1.0 - u).ln() * difficulty / hashrate).round() as u64;
+        let parents = visible_tips(&dag, now.saturating_sub(delay_ms));
+        let attacker = rng.gen_bool(share);
+        let stamp = if attacker {
+            now.saturating_add_signed(warp_ms)
+        } else {
+            let median = parents.iter().map(|&p| daa.past_median_time(&dag, p)).max().unwrap_or(0);
+            if daa.median_window > 0 { now.max(median + 1) } else { now }
+        };
+
+        if i == blocks / 2 {
+            settled_at = now;
+            settled_score = dag[dag.selected_parent()].blue_score();
+        }
+        if let Ok(id) = dag.create_block_at(parents, stamp) {
+            result.attacker_blocks += attacker as usize;
+            if i > blocks / 2 {
+                work += dag[id].work() as f64;
+                counted += 1;
+            }
+        }
+    }
+
+    let blues = dag[dag.selected_parent()].blue_score().saturating_sub(settled_score).max(1);
+    result.mean_difficulty = work / counted.max(1) as f64;
+    result.interval_ms = (now - settled_at) as f64 / blues as f64;
+    result.stale_timestamps = dag.stats.stale_timestamps;
+    result
+}
+
+// Each median window against an attacker warping its stamps, next to the
+// same window with nobody warping. Window 0 is the raw-timestamp DAA.
+pub fn experiment(daa: &Daa, windows: &[usize], config: &WarpConfig, seed: u64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "⏳ Time warp: attacker with {:.0}% of the hashrate stamps blocks {:+} ms off, target {} ms, DAA window {}, seed {}\n",
+        config.share * 100.0,
+        config.warp_ms,
+        daa.target_interval,
+        daa.window,
+        seed
+    );
+    let _ = writeln!(
+        out,
+        "{:>7} {:>11} {:>11} {:>13} {:>13} {:>9} {:>8}",
+        "median", "difficulty", "honest diff", "interval (ms)", "honest (ms)", "accepted", "refused"
+    );
+    for &median_window in windows {
+        let daa = Daa { median_window, ..daa.clone() };
+        let honest = simulate(&daa, &WarpConfig { share: 0.0, ..*config }, seed);
+        let warped = simulate(&daa, config, seed);
+        let _ = writeln!(
+            out,
+            "{:>7} {:>11.0} {:>11.0} {:>13.0} {:>13.0} {:>9} {:>8}",
+            if median_window == 0 { "off".to_string() } else { median_window.to_string() },
+            warped.mean_difficulty,
+            honest.mean_difficulty,
+            warped.interval_ms,
+            honest.interval_ms,
+            warped.attacker_blocks,
+            warped.stale_timestamps
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEHIND: WarpConfig = WarpConfig { hashrate: 1.0, share: 0.3, warp_ms: -600_000, delay_ms: 0, blocks: 600 };
+
+    fn daa(median_window: usize) -> Daa {
+        Daa { median_window, ..Daa::new(50, 1000) }
+    }
+
+    // Stamps ten minutes back squeeze the DAA's window, so it reads a much
+    // faster block rate than there is and piles on difficulty
+    #[test]
+    fn raw_timestamps_let_a_warp_drag_the_daa() {
+        let honest = simulate(&daa(0), &WarpConfig { share: 0.0, ..BEHIND }, 42);
+        let warped = simulate(&daa(0), &BEHIND, 42);
+        assert_eq!(warped.stale_timestamps, 0);
+        assert!(warped.attacker_blocks > 0);
+        assert!(warped.mean_difficulty > 5.0 * honest.mean_difficulty, "{} vs {}", warped.mean_difficulty, honest.mean_difficulty);
+        assert!(warped.interval_ms > 5000.0, "{} ms a blue block", warped.interval_ms);
+    }
+
+    // The same stamps are all behind the past median time and get refused,
+    // which keeps the block rate on target
+    #[test]
+    fn the_median_rule_refuses_warped_timestamps() {
+        let warped = simulate(&daa(11), &BEHIND, 42);
+        assert_eq!(warped.attacker_blocks, 0);
+        assert!(warped.stale_timestamps > 100, "{} refused", warped.stale_timestamps);
+        assert!((900.0..1100.0).contains(&warped.interval_ms), "{} ms a blue block", warped.interval_ms);
+
+        let honest = simulate(&daa(11), &WarpConfig { share: 0.0, ..BEHIND }, 42);
+        assert_eq!(honest.stale_timestamps, 0);
+    }
+}
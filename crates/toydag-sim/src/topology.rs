@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::network::transfer_ms;
+
+// How simulated nodes are wired together, loaded from a TOML file. Every
+// link carries `latency_ms` and `bandwidth` unless an `[[edges]]` entry
+// overrides them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Topology {
+    #[serde(default)]
+    pub name: String,
+    pub nodes: usize,
+    #[serde(flatten)]
+    pub shape: Shape,
+    #[serde(default = "default_latency")]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub bandwidth: u64, // Bytes per ms; 0 means unlimited
+    #[serde(default)]
+    pub hashrates: Vec<f64>, // Relative hashrate per node; empty means equal
+    #[serde(default)]
+    pub edges: Vec<Edge>, // Extra links, or latency overrides for existing ones
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Shape {
+    Full,
+    Ring,
+    Star {
+        #[serde(default)]
+        hub: usize,
+    },
+    // Connected random graph: a random spanning tree, topped up with random
+    // links until nodes average `degree` neighbours
+    Random {
+        degree: usize,
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Edge {
+    pub a: usize,
+    pub b: usize,
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub bandwidth: Option<u64>, // Falls back to the topology's bandwidth
+}
+
+fn default_latency() -> u64 {
+    100
+}
+
+impl Topology {
+    pub fn load(path: &Path) -> Result<Topology, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut topology: Topology = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if topology.name.is_empty() {
+            topology.name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        }
+        topology.links().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(topology)
+    }
+
+    // Undirected links, one entry per pair, overrides applied
+    pub fn links(&self) -> Result<Vec<Edge>, String> {
+        let n = self.nodes;
+        if n == 0 {
+            return Err("a topology needs at least one node".to_string());
+        }
+        if !self.hashrates.is_empty() && self.hashrates.len() != n {
+            return Err(format!("{} hashrates for {} nodes", self.hashrates.len(), n));
+        }
+
+        let mut pairs: Vec<(usize, usize)> = match self.shape {
+            Shape::Full => (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))).collect(),
+            Shape::Ring if n < 3 => (1..n).map(|b| (0, b)).collect(),
+            Shape::Ring => (0..n).map(|a| (a, (a + 1) % n)).collect(),
+            Shape::Star { hub } => {
+                if hub >= n {
+                    return Err(format!("hub {} is not one of the {} nodes", hub, n));
+                }
+                (0..n).filter(|&b| b != hub).map(|b| (hub, b)).collect()
+            }
+            Shape::Random { degree, seed } => random_pairs(n, degree, seed),
+        };
+
+        let mut links: Vec<Edge> = Vec::new();
+        for edge in &self.edges {
+            if edge.a >= n || edge.b >= n || edge.a == edge.b {
+                return Err(format!("edge {}–{} is not between two distinct nodes", edge.a, edge.b));
+            }
+            pairs.retain(|&(a, b)| (a, b) != (edge.a, edge.b) && (b, a) != (edge.a, edge.b));
+            links.push(Edge { bandwidth: edge.bandwidth.or(Some(self.bandwidth)), ..*edge });
+        }
+        let bandwidth = Some(self.bandwidth);
+        links.extend(pairs.into_iter().map(|(a, b)| Edge { a, b, latency_ms: self.latency_ms, bandwidth }));
+        Ok(links)
+    }
+
+    // Shortest gossip delay of a `block_size`-byte block between every pair of
+    // nodes; None when unreachable. Relays store and forward, so every hop pays
+    // its own transfer time.
+    pub fn delays(&self, block_size: u64) -> Result<Vec<Vec<Option<u64>>>, String> {
+        let n = self.nodes;
+        let mut delay = vec![vec![None; n]; n];
+        for (i, row) in delay.iter_mut().enumerate() {
+            row[i] = Some(0);
+        }
+        for edge in self.links()? {
+            let d = Some(edge.latency_ms + transfer_ms(block_size, edge.bandwidth.unwrap_or(0)));
+            delay[edge.a][edge.b] = delay[edge.a][edge.b].min(d).or(d);
+            delay[edge.b][edge.a] = delay[edge.a][edge.b];
+        }
+
+        // Floyd–Warshall; node counts here are small
+        for k in 0..n {
+            for i in 0..n {
+                let Some(ik) = delay[i][k] else { continue };
+                let through_k = delay[k].clone();
+                for (ij, kj) in delay[i].iter_mut().zip(through_k) {
+                    if let Some(kj) = kj
+                        && ij.is_none_or(|d| ik + kj < d)
+                    {
+                        *ij = Some(ik + kj);
+                    }
+                }
+            }
+        }
+        Ok(delay)
+    }
+
+    pub fn hashrates(&self) -> Vec<f64> {
+        if self.hashrates.is_empty() { vec![1.0; self.nodes] } else { self.hashrates.clone() }
+    }
+}
+
+fn random_pairs(n: usize, degree: usize, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pairs: Vec<(usize, usize)> = (1..n).map(|b| (rng.gen_range(0..b), b)).collect();
+
+    let max_pairs = n * (n - 1) / 2;
+    let wanted = (n * degree / 2).min(max_pairs);
+    while pairs.len() < wanted {
+        let a = rng.gen_range(0..n);
+        let b = rng.gen_range(0..n);
+        let pair = (a.min(b), a.max(b));
+        if a != b && !pairs.iter().any(|&(x, y)| (x.min(y), x.max(y)) == pair) {
+            pairs.push(pair);
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology(nodes: usize, shape: Shape) -> Topology {
+        Topology { name: String::new(), nodes, shape, latency_ms: 100, bandwidth: 0, hashrates: vec![], edges: vec![] }
+    }
+
+    fn degrees(links: &[Edge], n: usize) -> Vec<usize> {
+        let mut degree = vec![0; n];
+        for edge in links {
+            degree[edge.a] += 1;
+            degree[edge.b] += 1;
+        }
+        degree
+    }
+
+    fn connected(topology: &Topology) -> bool {
+        topology.delays(0).unwrap().iter().flatten().all(Option::is_some)
+    }
+
+    #[test]
+    fn toml_loads_shape_defaults_and_overrides() {
+        let path = std::env::temp_dir().join(format!("toydag-topology-{}.toml", std::process::id()));
+        let text = r#"
+            nodes = 4
+            kind = "star"
+            hub = 2
+            bandwidth = 50
+            hashrates = [1.0, 2.0, 3.0, 4.0]
+
+            [[edges]]
+            a = 0
+            b = 2
+            latency_ms = 700
+        "#;
+        fs::write(&path, text).unwrap();
+        let topology = Topology::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(topology.name, path.file_stem().unwrap().to_string_lossy());
+        assert!(matches!(topology.shape, Shape::Star { hub: 2 }));
+        assert_eq!((topology.latency_ms, topology.bandwidth), (100, 50));
+        assert_eq!(topology.hashrates(), vec![1.0, 2.0, 3.0, 4.0]);
+
+        let links = topology.links().unwrap();
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0], Edge { a: 0, b: 2, latency_ms: 700, bandwidth: Some(50) });
+        assert!(links[1..].iter().all(|e| e.latency_ms == 100 && e.bandwidth == Some(50)));
+
+        let random: Topology = toml::from_str("nodes = 5\nkind = \"random\"\ndegree = 3").unwrap();
+        assert!(matches!(random.shape, Shape::Random { degree: 3, seed: 0 }));
+        assert!(toml::from_str::<Topology>("nodes = 5\nkind = \"tree\"").is_err());
+        assert!(toml::from_str::<Topology>("nodes = 3\nkind = \"star\"\nhub = 3").unwrap().links().is_err());
+    }
+
+    #[test]
+    fn ring_links_each_node_to_two_neighbours() {
+        let ring = topology(6, Shape::Ring);
+        let links = ring.links().unwrap();
+        assert_eq!(links.len(), 6);
+        assert_eq!(degrees(&links, 6), vec![2; 6]);
+        assert!(connected(&ring));
+        assert_eq!(ring.delays(0).unwrap()[0][3], Some(300)); // Halfway round either way
+        assert_eq!(topology(2, Shape::Ring).links().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn star_links_every_node_through_the_hub() {
+        let star = topology(5, Shape::Star { hub: 1 });
+        let links = star.links().unwrap();
+        assert_eq!(degrees(&links, 5), vec![1, 4, 1, 1, 1]);
+        assert!(connected(&star));
+        assert_eq!(star.delays(0).unwrap()[0][4], Some(200));
+    }
+
+    #[test]
+    fn random_graphs_are_connected_at_the_asked_degree() {
+        for seed in 0..5 {
+            let random = topology(12, Shape::Random { degree: 4, seed });
+            let links = random.links().unwrap();
+            assert_eq!(links.len(), 12 * 4 / 2);
+            assert!(degrees(&links, 12).iter().all(|&d| d >= 1));
+            assert!(connected(&random), "seed {}", seed);
+        }
+        // More degree than a full mesh has stops at the full mesh
+        assert_eq!(topology(4, Shape::Random { degree: 9, seed: 1 }).links().unwrap().len(), 6);
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::sync::mpsc::Receiver;
+
+use serde::Deserialize;
+
+use toydag_core::ToyDag;
+use toydag_core::events::{DagEvent, FinalityViolation};
+
+// What a run does once the watchdog fires
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnViolation {
+    #[default]
+    Flag,  // Note it in the report and carry on
+    Abort, // Stop the run right there
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    // A node's own selected chain switched past its finality point
+    Reorg { node: usize, violation: FinalityViolation },
+    // Two nodes finalized chain blocks neither of which is below the other
+    Conflict { nodes: (usize, usize), blocks: (u64, u64) },
+}
+
+impl Violation {
+    pub fn describe(&self) -> String {
+        match self {
+            Violation::Reorg { node, violation } => format!(
+                "node {} reorged {} chain blocks from {} to {}, dropping finalized block {}",
+                node, violation.reorg_depth, violation.from, violation.to, violation.finality_point
+            ),
+            Violation::Conflict { nodes, blocks } => format!(
+                "node {} finalized {} but node {} finalized {} on a conflicting chain",
+                nodes.0, blocks.0, nodes.1, blocks.1
+            ),
+        }
+    }
+}
+
+// Watches some of a set of node views for broken finality, usually the
+// honest ones: an attacker's private chain is not a network failure until it
+// is published. Each view's own events give away reorgs past its finality
+// point; whenever a node finalizes a block it is also held against every
+// other watched node's finality point, judged in the finalizing node's view,
+// and once that view has both blocks one must be in the other's past.
+pub struct Watchdog {
+    events: Vec<(usize, Receiver<DagEvent>)>,
+    conflicts: HashSet<(u64, u64)>, // Pairs already reported, lower id first
+    pub violations: Vec<(u64, Violation)>, // With the time they were caught
+}
+
+impl Watchdog {
+    pub fn watch(views: &mut [ToyDag], nodes: impl IntoIterator<Item = usize>) -> Self {
+        let events = nodes.into_iter().map(|n| (n, views[n].event_channel())).collect();
+        Watchdog { events, conflicts: HashSet::new(), violations: Vec::new() }
+    }
+
+    // Go through everything the views did since the last check. True when
+    // something new turned up.
+    pub fn check(&mut self, views: &[ToyDag], now: u64) -> bool {
+        let before = self.violations.len();
+        for i in 0..self.events.len() {
+            let (node, ref receiver) = self.events[i];
+            let events: Vec<DagEvent> = receiver.try_iter().collect();
+            for event in events {
+                match event {
+                    DagEvent::FinalityViolation(violation) => self.violations.push((now, Violation::Reorg { node, violation })),
+                    DagEvent::Finalized { id } => self.compare(views, node, id, now),
+                    _ => {}
+                }
+            }
+        }
+        self.violations.len() > before
+    }
+
+    fn compare(&mut self, views: &[ToyDag], node: usize, finalized: u64, now: u64) {
+        let view = &views[node];
+        let watched: Vec<usize> = self.events.iter().map(|&(n, _)| n).collect();
+        for other in watched {
+            let theirs = views[other].finality_point();
+            if other == node || theirs == finalized || !view.contains(theirs) {
+                continue;
+            }
+            let pair = (finalized.min(theirs), finalized.max(theirs));
+            if view.is_ancestor(theirs, finalized) == Ok(true) || view.is_ancestor(finalized, theirs) == Ok(true) || !self.conflicts.insert(pair) {
+                continue;
+            }
+            self.violations.push((now, Violation::Conflict { nodes: (node, other), blocks: (finalized, theirs) }));
+        }
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        if self.violations.is_empty() {
+            let _ = writeln!(out, "🛡️  Finality held: no violations");
+            return out;
+        }
+        let _ = writeln!(out, "🚨 Finality violations: {}", self.violations.len());
+        for (at, violation) in &self.violations {
+            let _ = writeln!(out, "  @{:<8} {}", at, violation.describe());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toydag_core::FINALITY_DEPTH;
+    use toydag_core::knight::KMode;
+
+    use super::*;
+
+    fn view() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        // Wide enough for a long private chain to stay blue beside the honest one
+        dag.k_mode = KMode::Fixed(FINALITY_DEPTH as usize + 10);
+        dag
+    }
+
+    fn extend(dag: &mut ToyDag, from: u64, blocks: u64) -> u64 {
+        (0..blocks).fold(from, |tip, _| dag.create_block(vec![tip]).unwrap())
+    }
+
+    // A longer private chain from genesis published over a finalized honest one
+    #[test]
+    fn deep_reorg_is_caught() {
+        let mut views = vec![view()];
+        let mut watchdog = Watchdog::watch(&mut views, [0]);
+        let honest = extend(&mut views[0], 0, FINALITY_DEPTH + 5);
+        assert!(!watchdog.check(&views, 1));
+
+        let private = extend(&mut views[0], 0, FINALITY_DEPTH + 10);
+        assert!(watchdog.check(&views, 2));
+        assert_eq!(views[0].selected_parent(), private);
+        assert!(matches!(
+            watchdog.violations[..],
+            [(2, Violation::Reorg { node: 0, violation: FinalityViolation { from, reorg_depth, .. } })]
+                if from == honest && reorg_depth as u64 == FINALITY_DEPTH + 5
+        ));
+        assert!(watchdog.report().contains("node 0 reorged"));
+    }
+
+    // Two nodes that see the same blocks finalize along the same chain
+    #[test]
+    fn healthy_run_stays_quiet() {
+        let mut views = vec![view(), view()];
+        let mut watchdog = Watchdog::watch(&mut views, [0, 1]);
+        for step in 0..3 {
+            for dag in &mut views {
+                let tip = dag.selected_parent();
+                extend(dag, tip, FINALITY_DEPTH);
+            }
+            assert!(!watchdog.check(&views, step));
+        }
+        assert_ne!(views[0].finality_point(), views[0].genesis());
+        assert!(watchdog.violations.is_empty());
+        assert!(watchdog.report().contains("Finality held"));
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use toydag_core::{Block, Color, ToyDag};
+
+// Graphviz DOT, edges pointing from child to parent. Nodes are filled by
+// color and selected-chain blocks get a bold outline.
+pub fn to_dot(dag: &ToyDag) -> String {
+    let chain: HashSet<u64> = dag.selected_chain().into_iter().collect();
+    let blocks = sorted_blocks(dag);
+
+    let mut out = String::from("digraph toydag {\n  rankdir=RL;\n  node [shape=box, style=filled, fontcolor=white];\n");
+    for b in &blocks {
+        let fill = match b.color() {
+            Color::Blue => "royalblue",
+            Color::Red => "firebrick",
+        };
+        let outline = if chain.contains(&b.id()) { ", penwidth=3" } else { "" };
+        let _ = writeln!(
+            out,
+            "  {} [label=\"{}\\nscore {}{}\", fillcolor={}{}];",
+            b.id(),
+            b.id(),
+            b.blue_score(),
+            b.miner().map_or(String::new(), |m| format!("\\nminer {}", m)),
+            fill,
+            outline
+        );
+    }
+    for b in &blocks {
+        for p in b.parents() {
+            let _ = writeln!(out, "  {} -> {};", b.id(), p);
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+// GraphML for Gephi and friends; same edges as DOT, attributes as typed keys
+pub fn to_graphml(dag: &ToyDag) -> String {
+    let attrs = NodeAttrs::new(dag);
+    let blocks = sorted_blocks(dag);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (key, kind) in [
+        ("color", "string"),
+        ("blue_score", "long"),
+        ("daa_score", "long"),
+        ("depth", "int"),
+        ("miner", "int"),
+        ("finalized", "boolean"),
+        ("chain", "boolean"),
+    ] {
+        let _ = writeln!(out, "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"{1}\"/>", key, kind);
+    }
+    out.push_str("  <graph id=\"toydag\" edgedefault=\"directed\">\n");
+    for b in &blocks {
+        let node = attrs.node(b);
+        let _ = writeln!(out, "    <node id=\"{}\">", node.id);
+        let _ = writeln!(out, "      <data key=\"color\">{}</data>", node.color);
+        let _ = writeln!(out, "      <data key=\"blue_score\">{}</data>", node.blue_score);
+        let _ = writeln!(out, "      <data key=\"daa_score\">{}</data>", node.daa_score);
+        let _ = writeln!(out, "      <data key=\"depth\">{}</data>", node.depth);
+        if let Some(miner) = node.miner {
+            let _ = writeln!(out, "      <data key=\"miner\">{}</data>", miner);
+        }
+        let _ = writeln!(out, "      <data key=\"finalized\">{}</data>", node.finalized);
+        let _ = writeln!(out, "      <data key=\"chain\">{}</data>", node.chain);
+        out.push_str("    </node>\n");
+    }
+    for b in &blocks {
+        for p in b.parents() {
+            let _ = writeln!(out, "    <edge source=\"{}\" target=\"{}\"/>", b.id(), p);
+        }
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+// Cytoscape.js elements JSON, ready for `cytoscape({ elements })`
+pub fn to_cytoscape(dag: &ToyDag) -> String {
+    let attrs = NodeAttrs::new(dag);
+    let blocks = sorted_blocks(dag);
+
+    let nodes = blocks.iter().map(|b| Element { data: attrs.node(b) }).collect();
+    let edges = blocks
+        .iter()
+        .flat_map(|b| {
+            b.parents().iter().map(move |&p| Element {
+                data: CytoscapeEdge {
+                    id: format!("{}-{}", b.id(), p),
+                    source: b.id().to_string(),
+                    target: p.to_string(),
+                },
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&CytoscapeGraph { elements: Elements { nodes, edges } }).unwrap()
+}
+
+fn sorted_blocks(dag: &ToyDag) -> Vec<&Block> {
+    let mut blocks: Vec<&Block> = dag.blocks().collect();
+    blocks.sort_unstable_by_key(|b| b.id());
+    blocks
+}
+
+// Per-node attributes shared by the GraphML and Cytoscape exporters
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeData {
+    id: String,
+    color: &'static str,
+    blue_score: u64,
+    daa_score: u64,
+    depth: usize, // Topological depth from genesis
+    miner: Option<u32>,
+    finalized: bool,
+    chain: bool,
+}
+
+struct NodeAttrs {
+    chain: HashSet<u64>,
+    finalized: HashSet<u64>,
+}
+
+impl NodeAttrs {
+    fn new(dag: &ToyDag) -> Self {
+        NodeAttrs {
+            chain: dag.selected_chain().into_iter().collect(),
+            finalized: dag.past_set(dag.finality_point()).unwrap_or_default(),
+        }
+    }
+
+    fn node(&self, b: &Block) -> NodeData {
+        NodeData {
+            id: b.id().to_string(),
+            color: match b.color() {
+                Color::Blue => "blue",
+                Color::Red => "red",
+            },
+            blue_score: b.blue_score(),
+            daa_score: b.daa_score(),
+            depth: b.topo_depth(),
+            miner: b.miner(),
+            finalized: self.finalized.contains(&b.id()),
+            chain: self.chain.contains(&b.id()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CytoscapeGraph {
+    elements: Elements,
+}
+
+#[derive(Serialize)]
+struct Elements {
+    nodes: Vec<Element<NodeData>>,
+    edges: Vec<Element<CytoscapeEdge>>,
+}
+
+#[derive(Serialize)]
+struct Element<T> {
+    data: T,
+}
+
+#[derive(Serialize)]
+struct CytoscapeEdge {
+    id: String,
+    source: String,
+    target: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::Value;
+
+    use toydag_core::FINALITY_DEPTH;
+
+    use super::*;
+
+    // Genesis, a fork of 1 and 2 merged by 3, then a chain long enough that
+    // the fork falls below finality
+    fn sample() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![0]).unwrap();
+        let mut tip = dag.create_block(vec![1, 2]).unwrap();
+        for _ in 0..FINALITY_DEPTH + 2 {
+            tip = dag.create_block(vec![tip]).unwrap();
+        }
+        dag
+    }
+
+    // Node id → data key → value, read back off the GraphML text
+    fn graphml_nodes(xml: &str) -> HashMap<u64, HashMap<String, String>> {
+        let mut nodes = HashMap::new();
+        for chunk in xml.split("<node id=\"").skip(1) {
+            let (id, body) = chunk.split_once('"').unwrap();
+            let body = &body[..body.find("</node>").unwrap()];
+            let data = body
+                .split("<data key=\"")
+                .skip(1)
+                .map(|d| {
+                    let (key, rest) = d.split_once("\">").unwrap();
+                    (key.to_string(), rest[..rest.find("</data>").unwrap()].to_string())
+                })
+                .collect();
+            nodes.insert(id.parse().unwrap(), data);
+        }
+        nodes
+    }
+
+    #[test]
+    fn graphml_carries_finality_chain_and_depth() {
+        let dag = sample();
+        let side = if dag[3].selected_parent() == Some(1) { 2 } else { 1 };
+        let tip = dag.selected_parent();
+        let xml = to_graphml(&dag);
+        let nodes = graphml_nodes(&xml);
+
+        assert_eq!(nodes.len(), dag.block_count());
+        assert_eq!(xml.matches("<edge ").count(), 1 + 1 + 2 + FINALITY_DEPTH as usize + 2);
+        assert_eq!(nodes[&3]["depth"], "2");
+        assert_eq!(nodes[&tip]["depth"], (FINALITY_DEPTH + 4).to_string());
+        for (id, finalized, chain) in [(0, true, true), (3, true, true), (side, true, false), (tip, false, true)] {
+            assert_eq!(nodes[&id]["finalized"], finalized.to_string(), "block {}", id);
+            assert_eq!(nodes[&id]["chain"], chain.to_string(), "block {}", id);
+        }
+        assert!(!nodes[&0].contains_key("miner"));
+    }
+
+    #[test]
+    fn cytoscape_carries_the_same_attributes_and_edges() {
+        let dag = sample();
+        let tip = dag.selected_parent();
+        let graph: Value = serde_json::from_str(&to_cytoscape(&dag)).unwrap();
+        let nodes = graph["elements"]["nodes"].as_array().unwrap();
+        let edges = graph["elements"]["edges"].as_array().unwrap();
+
+        assert_eq!(nodes.len(), dag.block_count());
+        assert_eq!(edges.len(), 1 + 1 + 2 + FINALITY_DEPTH as usize + 2);
+        assert!(edges.iter().any(|e| e["data"]["id"] == "3-1" && e["data"]["source"] == "3" && e["data"]["target"] == "1"));
+
+        let node = |id: u64| &nodes.iter().find(|n| n["data"]["id"].as_str() == Some(id.to_string().as_str())).unwrap()["data"];
+        assert_eq!((node(3)["depth"].as_u64(), node(3)["finalized"].as_bool()), (Some(2), Some(true)));
+        assert_eq!(node(tip)["finalized"], false);
+        assert_eq!(node(tip)["chain"], true);
+        assert_eq!(node(tip)["blueScore"], dag[tip].blue_score());
+    }
+}
@@ -0,0 +1,29 @@
+use toydag_core::ToyDag;
+use toydag_core::log::{self, Level, Record};
+
+pub mod export;
+pub mod render;
+pub mod report;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub fn print_dag(dag: &ToyDag) {
+    if !log::enabled(Level::Info) {
+        return;
+    }
+    let summary = format!(
+        "Blocks: {} | Tips: {} | Selected Parent: {} (color: {:?})",
+        dag.block_count(),
+        dag.tip_count(),
+        dag.selected_parent(),
+        dag[dag.selected_parent()].color(),
+    );
+
+    // Layered by topological depth; ANSI colors only when the log allows them
+    let detail = format!("{}\n{}=================\n", summary, render::render_layers(dag, log::color()));
+    log::emit(&Record { level: Level::Info, target: "dag", icon: "", message: "=== DAG State ===", detail: Some(&detail) });
+}
@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use toydag_core::{Color, ToyDag};
+
+const BLUE: &str = "\x1b[34m";
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// Layered view: one row per topological depth (genesis on top), each block
+// followed by its parent edges. Blue blocks are `[id]`, red blocks `(id)`, and
+// selected-chain blocks carry a `*`. Between rows, box-drawing lines join each
+// block to its parents one layer up; parents further up show only in the
+// block's `←` list. With `ansi` off the output is plain text, stable enough to
+// compare in snapshots.
+pub fn render_layers(dag: &ToyDag, ansi: bool) -> String {
+    let depth = topological_depths(dag);
+    let chain: HashSet<u64> = dag.selected_chain().into_iter().collect();
+
+    let max_depth = depth.values().copied().max().unwrap_or(0);
+    let mut layers: Vec<Vec<u64>> = vec![Vec::new(); max_depth + 1];
+    for (&id, &d) in &depth {
+        layers[d].push(id);
+    }
+
+    let cells: HashMap<u64, (String, String)> = dag
+        .blocks()
+        .map(|b| {
+            let on_chain = chain.contains(&b.id());
+            let node = match b.color() {
+                Color::Blue => format!("[{}]", b.id()),
+                Color::Red => format!("({})", b.id()),
+            };
+            let star = if on_chain { "*" } else { "" };
+            let mut parents = b.parents().to_vec();
+            parents.sort_unstable();
+            let edges = if parents.is_empty() {
+                String::new()
+            } else {
+                let list: Vec<String> = parents.iter().map(|p| p.to_string()).collect();
+                format!("←{}", list.join(","))
+            };
+
+            let plain = format!("{}{}{}", node, star, edges);
+            let styled = if ansi {
+                let color = if b.color() == Color::Blue { BLUE } else { RED };
+                let bold = if on_chain { BOLD } else { "" };
+                format!("{}{}{}{}{}{}", bold, color, node, star, RESET, edges)
+            } else {
+                plain.clone()
+            };
+            (b.id(), (plain, styled))
+        })
+        .collect();
+
+    let width = cells.values().map(|(plain, _)| plain.chars().count()).max().unwrap_or(0);
+    let label_width = max_depth.to_string().len();
+
+    // Lines hang off each block's first digit, just inside its bracket
+    let mut column = HashMap::new();
+    for layer in &mut layers {
+        layer.sort_unstable();
+        for (i, &id) in layer.iter().enumerate() {
+            column.insert(id, i * (width + 1) + 2);
+        }
+    }
+
+    let mut out = String::new();
+    for (d, layer) in layers.iter().enumerate() {
+        if d > 0 {
+            let _ = writeln!(out, "{:>w$} │{}", "", connectors(dag, layer, &depth, &column), w = label_width);
+        }
+        let _ = write!(out, "{:>w$} │", d, w = label_width);
+        for id in layer {
+            let (plain, styled) = &cells[id];
+            let pad = width - plain.chars().count();
+            let _ = write!(out, " {}{}", styled, " ".repeat(pad));
+        }
+        // Trim padding after the last cell so snapshots don't carry trailing spaces
+        let trimmed = out.trim_end_matches(' ').len();
+        out.truncate(trimmed);
+        out.push('\n');
+    }
+    out
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+// The row above `layer`: every edge to a parent in the layer just above comes
+// down from the parent, runs across, and drops onto the child. Each position
+// collects the directions its lines leave in and is drawn as that junction.
+fn connectors(dag: &ToyDag, layer: &[u64], depth: &HashMap<u64, usize>, column: &HashMap<u64, usize>) -> String {
+    let mut row: Vec<u8> = Vec::new();
+    for &child in layer {
+        for parent in dag[child].parents().iter().filter(|&p| depth[p] + 1 == depth[&child]) {
+            let (from, to) = (column[parent], column[&child]);
+            row.resize(row.len().max(from.max(to) + 1), 0);
+            row[from] |= UP;
+            row[to] |= DOWN;
+            if from != to {
+                let (left, right) = (from.min(to), from.max(to));
+                row[left] |= RIGHT;
+                row[right] |= LEFT;
+                for cell in &mut row[left + 1..right] {
+                    *cell |= LEFT | RIGHT;
+                }
+            }
+        }
+    }
+    row.into_iter()
+        .map(|cell| match cell {
+            0 => ' ',
+            UP => '╵',
+            DOWN => '╷',
+            c if c == UP | DOWN => '│',
+            c if c == LEFT | RIGHT => '─',
+            c if c == UP | RIGHT => '└',
+            c if c == UP | LEFT => '┘',
+            c if c == DOWN | RIGHT => '┌',
+            c if c == DOWN | LEFT => '┐',
+            c if c == UP | DOWN | RIGHT => '├',
+            c if c == UP | DOWN | LEFT => '┤',
+            c if c == UP | LEFT | RIGHT => '┴',
+            c if c == DOWN | LEFT | RIGHT => '┬',
+            _ => '┼',
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+// Longest-path depth from genesis for every block
+pub fn topological_depths(dag: &ToyDag) -> HashMap<u64, usize> {
+    dag.blocks().map(|b| (b.id(), b.topo_depth())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use toydag_core::knight::KMode;
+
+    use super::*;
+
+    // Three siblings at k = 1, the third red, and a block merging the first two
+    #[test]
+    fn small_dag_snapshot() {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.k_mode = KMode::Fixed(1);
+        for _ in 0..3 {
+            dag.create_block(vec![0]).unwrap();
+        }
+        dag.create_block(vec![1, 2]).unwrap();
+
+        let expected = "\
+0 │ [0]*
+  │  ├────────┬────────┐
+1 │ [1]*←0   [2]←0    (3)←0
+  │  ├────────┘
+2 │ [4]*←1,2
+";
+        assert_eq!(render_layers(&dag, false), expected);
+    }
+}
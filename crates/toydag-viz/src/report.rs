@@ -0,0 +1,245 @@
+// A shareable end-of-run report in one file: the run's parameters, its key
+// metrics, the DAG itself, tips over time, and how attacks and rule checks
+// came out. HTML by default, Markdown for `.md` paths.
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use toydag_core::ToyDag;
+
+use crate::export;
+
+// Summary metrics that belong under attack outcomes rather than key metrics
+const ATTACK_METRICS: [&str; 10] = [
+    "reorgs",
+    "max_reorg_depth",
+    "finality_violations",
+    "merge_depth_violations",
+    "kosherized_merges",
+    "oversized_mergesets",
+    "stale_timestamps",
+    "outdated_versions",
+    "orphaned_reds",
+    "orphaned_work",
+];
+
+type Metric = (&'static str, String);
+
+const CHART_WIDTH: usize = 640;
+const CHART_HEIGHT: usize = 120;
+const SPARK_WIDTH: usize = 80;
+
+pub struct RunReport<'a> {
+    pub title: String,
+    pub dag: &'a ToyDag,
+    pub params: Vec<(String, String)>,
+    pub sections: Vec<(String, String)>, // Other text reports, shown as is
+}
+
+impl<'a> RunReport<'a> {
+    pub fn new(title: &str, dag: &'a ToyDag) -> Self {
+        RunReport { title: title.to_string(), dag, params: Vec::new(), sections: Vec::new() }
+    }
+
+    pub fn param(&mut self, name: &str, value: impl ToString) {
+        self.params.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn section(&mut self, title: &str, text: &str) {
+        self.sections.push((title.to_string(), text.to_string()));
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let text = if path.extension().is_some_and(|ext| ext == "md") { self.to_markdown() } else { self.to_html() };
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Key metrics, then attack outcomes
+    fn metrics(&self) -> (Vec<Metric>, Vec<Metric>) {
+        self.dag.stats.summary(self.dag).into_iter().partition(|(metric, _)| !ATTACK_METRICS.contains(metric))
+    }
+
+    pub fn to_html(&self) -> String {
+        let (key, attacks) = self.metrics();
+        let dot = export::to_dot(self.dag);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+             body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             td, th {{ border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }}\n\
+             .dag svg {{ max-width: 100%; height: auto; }}\n\
+             </style>\n</head>\n<body>\n<h1>{}</h1>",
+            escape(&self.title),
+            escape(&self.title)
+        );
+        html_table(&mut out, "Parameters", self.params.iter().map(|(n, v)| (n.as_str(), v.as_str())));
+        html_table(&mut out, "Key metrics", key.iter().map(|(n, v)| (*n, v.as_str())));
+        html_table(&mut out, "Attack outcomes", attacks.iter().map(|(n, v)| (*n, v.as_str())));
+
+        let _ = writeln!(out, "<h2>DAG</h2>");
+        match svg(&dot) {
+            Some(svg) => _ = writeln!(out, "<div class=\"dag\">\n{}\n</div>", svg),
+            None => _ = writeln!(
+                out,
+                "<details>\n<summary>DOT source (install Graphviz to have it drawn here)</summary>\n<pre>{}</pre>\n</details>",
+                escape(&dot)
+            ),
+        }
+
+        let _ = writeln!(out, "<h2>Tips over time</h2>\n{}", tip_chart(&self.dag.stats.tip_counts));
+        for (title, text) in &self.sections {
+            let _ = writeln!(out, "<h2>{}</h2>\n<pre>{}</pre>", escape(title), escape(text));
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let (key, attacks) = self.metrics();
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title);
+        md_table(&mut out, "Parameters", self.params.iter().map(|(n, v)| (n.as_str(), v.as_str())));
+        md_table(&mut out, "Key metrics", key.iter().map(|(n, v)| (*n, v.as_str())));
+        md_table(&mut out, "Attack outcomes", attacks.iter().map(|(n, v)| (*n, v.as_str())));
+        let _ = writeln!(out, "## DAG\n\n```dot\n{}```\n", export::to_dot(self.dag));
+        let counts = &self.dag.stats.tip_counts;
+        let _ = writeln!(
+            out,
+            "## Tips over time\n\n`{}` (max {})\n",
+            sparkline(counts),
+            counts.iter().max().copied().unwrap_or(0)
+        );
+        for (title, text) in &self.sections {
+            let _ = writeln!(out, "## {}\n\n```\n{}```\n", title, text);
+        }
+        out
+    }
+}
+
+fn html_table<'s>(out: &mut String, title: &str, rows: impl Iterator<Item = (&'s str, &'s str)>) {
+    let _ = writeln!(out, "<h2>{}</h2>\n<table>", escape(title));
+    for (name, value) in rows {
+        let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", escape(name), escape(value));
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+fn md_table<'s>(out: &mut String, title: &str, rows: impl Iterator<Item = (&'s str, &'s str)>) {
+    let _ = writeln!(out, "## {}\n\n| | |\n|---|---|", title);
+    for (name, value) in rows {
+        let _ = writeln!(out, "| {} | {} |", name, value);
+    }
+    let _ = writeln!(out);
+}
+
+// The DAG drawn by Graphviz, when `dot` is installed
+fn svg(dot: &str) -> Option<String> {
+    let mut child = Command::new("dot").arg("-Tsvg").stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn().ok()?;
+    child.stdin.take()?.write_all(dot.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    let svg = String::from_utf8(output.stdout).ok().filter(|_| output.status.success())?;
+    // Inline SVG takes no XML prologue or doctype
+    svg.find("<svg").map(|start| svg[start..].to_string())
+}
+
+// Tip count after each insertion as an inline SVG line chart
+fn tip_chart(counts: &[usize]) -> String {
+    let max = counts.iter().max().copied().unwrap_or(0).max(1);
+    let step = CHART_WIDTH as f64 / counts.len().saturating_sub(1).max(1) as f64;
+    let points: Vec<String> = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| format!("{:.1},{:.1}", i as f64 * step, CHART_HEIGHT as f64 * (1.0 - c as f64 / max as f64)))
+        .collect();
+    format!(
+        "<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 -5 {w} {hp}\" role=\"img\">\n\
+         <title>Tips after each of {n} insertions, at most {max}</title>\n\
+         <line x1=\"0\" y1=\"{h}\" x2=\"{w}\" y2=\"{h}\" stroke=\"#ccc\"/>\n\
+         <polyline fill=\"none\" stroke=\"royalblue\" stroke-width=\"1.5\" points=\"{points}\"/>\n\
+         <text x=\"4\" y=\"10\" font-size=\"11\">{max} tips</text>\n</svg>",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+        hp = CHART_HEIGHT + 10,
+        n = counts.len(),
+        max = max,
+        points = points.join(" ")
+    )
+}
+
+// The same as one line of block characters, each the peak of its stretch
+fn sparkline(counts: &[usize]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().max().copied().unwrap_or(0).max(1);
+    let per = counts.len().div_ceil(SPARK_WIDTH).max(1);
+    counts.chunks(per).map(|chunk| BARS[(chunk.iter().max().unwrap() * (BARS.len() - 1)).div_ceil(max)]).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run() -> ToyDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        let a = dag.create_block(vec![0]).unwrap();
+        let b = dag.create_block(vec![0]).unwrap();
+        dag.create_block(vec![a, b]).unwrap();
+        dag
+    }
+
+    // Attack metrics sit in their own table, after the key ones
+    #[test]
+    fn markdown_splits_metrics_and_keeps_sections() {
+        let dag = run();
+        let mut report = RunReport::new("Toy run", &dag);
+        report.param("k", 18);
+        report.section("Audit", "all clear\n");
+        let md = report.to_markdown();
+        assert!(md.starts_with("# Toy run\n"));
+        assert!(md.contains("| k | 18 |"));
+        let (key, attacks) = (md.find("## Key metrics").unwrap(), md.find("## Attack outcomes").unwrap());
+        let reorgs = md.find("| reorgs |").unwrap();
+        assert!(key < attacks && attacks < reorgs);
+        assert!(md.contains("```dot\ndigraph"));
+        assert!(md.contains("## Audit\n\n```\nall clear\n```"));
+    }
+
+    #[test]
+    fn html_escapes_what_the_caller_passes_in() {
+        let dag = run();
+        let mut report = RunReport::new("a <b> & \"c\"", &dag);
+        report.param("seed", "<42>");
+        let html = report.to_html();
+        assert!(html.contains("<title>a &lt;b&gt; &amp; &quot;c&quot;</title>"));
+        assert!(html.contains("<tr><th>seed</th><td>&lt;42&gt;</td></tr>"));
+        assert!(html.contains("Tips after each of 3 insertions, at most 2"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn sparkline_keeps_each_stretch_peak() {
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▅█"); // Rounded up, so only zero sits on the floor
+        assert_eq!(sparkline(&[]), "");
+        let long: Vec<usize> = (0..SPARK_WIDTH * 2).map(|i| i % 2).collect();
+        assert_eq!(sparkline(&long), "█".repeat(SPARK_WIDTH));
+    }
+
+    #[test]
+    fn write_picks_markdown_by_extension() {
+        let dag = run();
+        let report = RunReport::new("Toy run", &dag);
+        let path = std::env::temp_dir().join(format!("toydag-report-{}.md", std::process::id()));
+        report.write(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(text.starts_with("# Toy run"));
+    }
+}
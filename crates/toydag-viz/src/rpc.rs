@@ -0,0 +1,312 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use toydag_core::events::{DagEvent, Observer};
+use toydag_core::score::BlueWork;
+use toydag_core::{Block, Color, ToyDag};
+
+pub type SharedDag = Arc<Mutex<ToyDag>>;
+
+type Connection = Arc<Mutex<TcpStream>>;
+
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2); // A stalled subscriber gets dropped
+const SLICE_DEPTH: usize = 10; // Parent hops in a getDagSlice without a depth
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlock {
+    pub id: u64,
+    pub parents: Vec<u64>,
+    pub color: &'static str,
+    pub blue_score: u64,
+    pub blue_work: BlueWork,
+    pub daa_score: u64,
+    pub depth: usize, // Topological depth from genesis
+    pub selected_parent: Option<u64>,
+    pub miner: Option<u32>,
+    pub timestamp: u64,
+}
+
+impl From<&Block> for RpcBlock {
+    fn from(b: &Block) -> Self {
+        RpcBlock {
+            id: b.id(),
+            parents: b.parents().to_vec(),
+            color: match b.color() {
+                Color::Blue => "blue",
+                Color::Red => "red",
+            },
+            blue_score: b.blue_score(),
+            blue_work: b.blue_work(),
+            daa_score: b.daa_score(),
+            depth: b.topo_depth(),
+            selected_parent: b.selected_parent(),
+            miner: b.miner(),
+            timestamp: b.timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DagInfo {
+    pub blocks: usize,
+    pub tips: usize,
+    pub selected_parent: u64,
+    pub virtual_parents: Vec<u64>,
+    pub blue_score: u64, // Of the virtual's selected parent
+}
+
+// Serve newline-delimited JSON-RPC 2.0 on `addr` from background threads and
+// return the bound address. Methods: getBlock, getTips, getSelectedChain,
+// getDagInfo, getDagSlice, and subscribeBlockAdded, after which the connection
+// also gets a `blockAdded` notification for every new block.
+pub fn serve(dag: SharedDag, addr: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local = listener.local_addr()?;
+    let subscribers: Arc<Mutex<Vec<Connection>>> = Arc::default();
+    let (notifications, queue) = mpsc::channel::<String>();
+    dag.lock().unwrap().subscribe(Arc::new(Mutex::new(Notifier { notifications })));
+
+    // Notifications go out from here, so a slow subscriber never holds the DAG
+    let targets = subscribers.clone();
+    thread::spawn(move || {
+        for line in queue {
+            let connections = targets.lock().unwrap().clone();
+            let stalled: Vec<Connection> = connections.into_iter().filter(|c| send(c, &line).is_err()).collect();
+            targets.lock().unwrap().retain(|c| !stalled.iter().any(|s| Arc::ptr_eq(c, s)));
+        }
+    });
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let dag = dag.clone();
+            let subscribers = subscribers.clone();
+            thread::spawn(move || {
+                // A client hanging up mid-request is not the server's problem
+                let _ = handle(stream, &dag, &subscribers);
+            });
+        }
+    });
+    Ok(local)
+}
+
+fn handle(stream: TcpStream, dag: &SharedDag, subscribers: &Mutex<Vec<Connection>>) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let connection: Connection = Arc::new(Mutex::new(stream));
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => {
+                let id = request.get("id").cloned().unwrap_or(Value::Null);
+                let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+                let outcome = if method == "subscribeBlockAdded" {
+                    subscribers.lock().unwrap().push(connection.clone());
+                    Ok("true".to_string())
+                } else {
+                    call(&dag.lock().unwrap(), method, request.get("params"))
+                };
+                match outcome {
+                    Ok(result) => format!(r#"{{"jsonrpc":"2.0","id":{},"result":{}}}"#, id, result),
+                    Err((code, message)) => error(&id, code, &message),
+                }
+            }
+            Err(e) => error(&Value::Null, PARSE_ERROR, &e.to_string()),
+        };
+        send(&connection, &reply)?;
+    }
+    Ok(())
+}
+
+// Result of one method call, already serialized
+fn call(dag: &ToyDag, method: &str, params: Option<&Value>) -> Result<String, (i64, String)> {
+    match method {
+        "getBlock" => {
+            let id = block_id(params).ok_or((INVALID_PARAMS, "expected a block id".to_string()))?;
+            if !dag.contains(id) {
+                return Err((INVALID_PARAMS, format!("unknown block {}", id)));
+            }
+            json(&RpcBlock::from(&dag[id]))
+        }
+        "getTips" => {
+            let mut tips: Vec<u64> = dag.tips().collect();
+            tips.sort_unstable();
+            json(&tips)
+        }
+        "getSelectedChain" => json(&dag.selected_chain()),
+        "getDagInfo" => json(&DagInfo {
+            blocks: dag.block_count(),
+            tips: dag.tip_count(),
+            selected_parent: dag.selected_parent(),
+            virtual_parents: dag.virtual_parents(),
+            blue_score: dag[dag.selected_parent()].blue_score(),
+        }),
+        "getDagSlice" => {
+            let (anchor, depth) = slice_params(params).ok_or((INVALID_PARAMS, "expected an anchor and a depth".to_string()))?;
+            let anchor = anchor.unwrap_or(dag.selected_parent());
+            let slice = dag.dag_slice(anchor, depth).ok_or((INVALID_PARAMS, format!("unknown anchor block {}", anchor)))?;
+            json(&slice)
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method {:?}", method))),
+    }
+}
+
+fn json(value: &impl Serialize) -> Result<String, (i64, String)> {
+    serde_json::to_string(value).map_err(|e| (INTERNAL_ERROR, e.to_string()))
+}
+
+// `{"id": n}` or `[n]`
+fn block_id(params: Option<&Value>) -> Option<u64> {
+    match params? {
+        Value::Array(items) => items.first()?.as_u64(),
+        object => object.get("id")?.as_u64(),
+    }
+}
+
+// `{"anchor": n, "depth": d}` or `[n, d]`, both optional: the anchor defaults
+// to the virtual's selected parent and the depth to `SLICE_DEPTH`. None only
+// when a param is there but isn't a number.
+fn slice_params(params: Option<&Value>) -> Option<(Option<u64>, usize)> {
+    let (anchor, depth) = match params {
+        None | Some(Value::Null) => (None, None),
+        Some(Value::Array(items)) => (items.first(), items.get(1)),
+        Some(object) => (object.get("anchor"), object.get("depth")),
+    };
+    let anchor = match anchor {
+        None | Some(Value::Null) => None,
+        Some(value) => Some(value.as_u64()?),
+    };
+    let depth = match depth {
+        None | Some(Value::Null) => SLICE_DEPTH,
+        Some(value) => usize::try_from(value.as_u64()?).ok()?,
+    };
+    Some((anchor, depth))
+}
+
+fn error(id: &Value, code: i64, message: &str) -> String {
+    let message = Value::from(message);
+    format!(r#"{{"jsonrpc":"2.0","id":{},"error":{{"code":{},"message":{}}}}}"#, id, code, message)
+}
+
+fn send(connection: &Connection, line: &str) -> io::Result<()> {
+    let mut stream = connection.lock().unwrap();
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\n")
+}
+
+// Queues a `blockAdded` notification for every subscribed connection. It runs
+// with the DAG locked, so the writing is left to the sending thread.
+struct Notifier {
+    notifications: Sender<String>,
+}
+
+impl Observer for Notifier {
+    fn on_event(&mut self, dag: &ToyDag, event: &DagEvent) {
+        let DagEvent::BlockAdded { id } = *event else {
+            return;
+        };
+        let Ok(block) = serde_json::to_string(&RpcBlock::from(&dag[id])) else {
+            return;
+        };
+        let line = format!(r#"{{"jsonrpc":"2.0","method":"blockAdded","params":{}}}"#, block);
+        let _ = self.notifications.send(line); // Only fails once the server is gone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> (SharedDag, BufReader<TcpStream>, TcpStream) {
+        let mut dag = ToyDag::new();
+        dag.verbose = false;
+        dag.create_block(vec![0]).unwrap();
+        let dag: SharedDag = Arc::new(Mutex::new(dag));
+        let addr = serve(dag.clone(), "127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        (dag, BufReader::new(stream.try_clone().unwrap()), stream)
+    }
+
+    fn exchange(reader: &mut BufReader<TcpStream>, writer: &mut TcpStream, request: &str) -> Value {
+        writeln!(writer, "{}", request).unwrap();
+        read(reader)
+    }
+
+    fn read(reader: &mut BufReader<TcpStream>) -> Value {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn requests_get_answered_over_loopback() {
+        let (_dag, mut reader, mut writer) = server();
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":7,"method":"getDagInfo"}"#);
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["result"]["blocks"], 2);
+        assert_eq!(reply["result"]["selectedParent"], 1);
+
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":8,"method":"getBlock","params":[9]}"#);
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn slices_come_back_for_an_anchor_and_depth() {
+        let (dag, mut reader, mut writer) = server();
+        let tip = {
+            let mut dag = dag.lock().unwrap();
+            let side = dag.create_block(vec![0]).unwrap();
+            dag.create_block(vec![1, side]).unwrap()
+        };
+
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":1,"method":"getDagSlice","params":{"depth":1}}"#);
+        let slice = &reply["result"];
+        assert_eq!(slice["anchor"], tip);
+        assert_eq!(slice["blocks"].as_array().unwrap().len(), 3);
+        assert_eq!(slice["edges"].as_array().unwrap().len(), 2);
+        assert_eq!(slice["virtual_selected_parent"], tip);
+
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":2,"method":"getDagSlice","params":[1, 0]}"#);
+        assert_eq!(reply["result"]["blocks"], serde_json::json!([{
+            "id": 1, "color": "blue", "blue_score": 1, "blue_work": 1, "layer": 1, "miner": null, "chain": true
+        }]));
+        assert_eq!(reply["result"]["edges"], serde_json::json!([]));
+
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":3,"method":"getDagSlice","params":[99]}"#);
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":4,"method":"getDagSlice","params":{"depth":"deep"}}"#);
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn subscribers_hear_about_new_blocks() {
+        let (dag, mut reader, mut writer) = server();
+        let reply = exchange(&mut reader, &mut writer, r#"{"jsonrpc":"2.0","id":1,"method":"subscribeBlockAdded"}"#);
+        assert_eq!(reply["result"], true);
+
+        let id = dag.lock().unwrap().create_block(vec![1]).unwrap();
+        let notification = read(&mut reader);
+        assert_eq!(notification["method"], "blockAdded");
+        assert_eq!(notification["params"]["id"], id);
+        assert_eq!(notification["params"]["parents"], serde_json::json!([1]));
+    }
+}
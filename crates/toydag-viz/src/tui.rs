@@ -0,0 +1,171 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color as TermColor, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block as Panel, Borders, List, ListItem, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+
+use toydag_core::{Color, ToyDag};
+use toydag_sim::replay::{self, LogEntry};
+use toydag_sim::simulation_step;
+
+// Live view of the default simulation. Space pauses, `s`/→ steps one block
+// while paused, `+`/`-` change speed, `q` quits.
+pub fn run(blocks: u64, tick_ms: u64) -> io::Result<()> {
+    let mut rng = rand::thread_rng();
+    show(blocks, tick_ms, move |dag, i| simulation_step(dag, &mut rng, i))
+}
+
+// Same view, stepping through a recorded block log instead of simulating.
+// Entries the DAG declines are skipped; the header count still advances.
+pub fn replay(entries: Vec<LogEntry>, tick_ms: u64) -> io::Result<()> {
+    let blocks = entries.len() as u64;
+    show(blocks, tick_ms, move |dag, i| {
+        let _ = replay::apply(dag, &entries[i as usize - 1]);
+    })
+}
+
+fn show(blocks: u64, tick_ms: u64, step: impl FnMut(&mut ToyDag, u64)) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, blocks, tick_ms, step);
+    ratatui::restore();
+    result
+}
+
+struct App {
+    dag: ToyDag,
+    produced: u64,
+    blocks: u64,
+    paused: bool,
+    tick: Duration,
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    blocks: u64,
+    tick_ms: u64,
+    mut advance: impl FnMut(&mut ToyDag, u64),
+) -> io::Result<()> {
+    let mut app = App {
+        dag: ToyDag::new(),
+        produced: 0,
+        blocks,
+        paused: false,
+        tick: Duration::from_millis(tick_ms),
+    };
+    app.dag.verbose = false; // StitchBot's narration would corrupt the screen
+    let mut last_step = Instant::now();
+
+    loop {
+        terminal.draw(|frame| draw(frame, &app))?;
+
+        let timeout = app.tick.saturating_sub(last_step.elapsed());
+        let mut step = false;
+        if event::poll(timeout)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char(' ') => app.paused = !app.paused,
+                KeyCode::Char('s') | KeyCode::Right if app.paused => step = true,
+                KeyCode::Char('+') => app.tick = (app.tick / 2).max(Duration::from_millis(10)),
+                KeyCode::Char('-') => app.tick = (app.tick * 2).min(Duration::from_secs(5)),
+                _ => {}
+            }
+        }
+
+        if !app.paused && last_step.elapsed() >= app.tick {
+            step = true;
+        }
+        if step && app.produced < app.blocks {
+            app.produced += 1;
+            advance(&mut app.dag, app.produced);
+            last_step = Instant::now();
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let dag = &app.dag;
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(8), Constraint::Length(5), Constraint::Length(1)])
+        .split(frame.area());
+
+    // Header: run counters
+    let blue = dag.blocks().filter(|b| b.color() == Color::Blue).count();
+    let red = dag.block_count() - blue;
+    let status = if app.paused { "⏸ paused" } else { "▶ running" };
+    let header = Line::from(vec![
+        Span::raw(format!("{}  blocks {}/{}  ", status, app.produced, app.blocks)),
+        Span::styled(format!("🔵 {}  ", blue), Style::default().fg(TermColor::Blue)),
+        Span::styled(format!("🔴 {}  ", red), Style::default().fg(TermColor::Red)),
+        Span::raw(format!(
+            "tips {}  selected parent {}  🦸 stitches {}  tick {} ms",
+            dag.tip_count(),
+            dag.selected_parent(),
+            dag.stats.stitch_activations,
+            app.tick.as_millis()
+        )),
+    ]);
+    frame.render_widget(Paragraph::new(header).block(Panel::default().borders(Borders::ALL).title("toyDag")), rows[0]);
+
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[1]);
+
+    // Recent blocks, newest first, with chain membership highlighted
+    let chain: std::collections::HashSet<u64> = dag.selected_chain().into_iter().collect();
+    let visible = cols[0].height.saturating_sub(2) as usize;
+    let mut recent: Vec<_> = dag.blocks().collect();
+    recent.sort_by_key(|b| std::cmp::Reverse(b.id()));
+    let items: Vec<ListItem> = recent
+        .into_iter()
+        .take(visible)
+        .map(|b| {
+            let fg = if b.color() == Color::Blue { TermColor::Blue } else { TermColor::Red };
+            let mut style = Style::default().fg(fg);
+            if chain.contains(&b.id()) {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            let marker = if chain.contains(&b.id()) { "★" } else { " " };
+            ListItem::new(format!("{} {:>5}  score {:>5}  parents {:?}", marker, b.id(), b.blue_score(), b.parents()))
+                .style(style)
+        })
+        .collect();
+    frame.render_widget(
+        List::new(items).block(Panel::default().borders(Borders::ALL).title("Blocks (★ = selected chain)")),
+        cols[0],
+    );
+
+    // Current tips
+    let mut tips: Vec<u64> = dag.tips().collect();
+    tips.sort_unstable();
+    let tip_items: Vec<ListItem> = tips
+        .iter()
+        .map(|t| {
+            let selected = *t == dag.selected_parent();
+            let style = if selected { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+            ListItem::new(format!("{:>5}  score {}", t, dag[*t].blue_score())).style(style)
+        })
+        .collect();
+    frame.render_widget(List::new(tip_items).block(Panel::default().borders(Borders::ALL).title("Tips")), cols[1]);
+
+    // Tip count over time
+    let width = rows[2].width.saturating_sub(2) as usize;
+    let history: Vec<u64> = dag.stats.tip_counts.iter().rev().take(width).rev().map(|&t| t as u64).collect();
+    frame.render_widget(
+        Sparkline::default()
+            .block(Panel::default().borders(Borders::ALL).title("Tip count"))
+            .data(&history)
+            .style(Style::default().fg(TermColor::Yellow)),
+        rows[2],
+    );
+
+    frame.render_widget(Paragraph::new("space pause · s/→ step · +/- speed · q quit"), rows[3]);
+}
@@ -0,0 +1,71 @@
+use wasm_bindgen::prelude::*;
+
+use toydag_core::ToyDag;
+
+use crate::export;
+
+// The DAG as seen from JavaScript, for an in-browser explorer. Ids cross the
+// boundary as BigInts (BigUint64Array for lists).
+#[wasm_bindgen]
+pub struct WasmDag {
+    dag: ToyDag,
+}
+
+impl Default for WasmDag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl WasmDag {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmDag {
+        let mut dag = ToyDag::new();
+        dag.verbose = false; // No stdout to narrate to
+        WasmDag { dag }
+    }
+
+    // Add a block on top of `parents`, returning its id. Bad parents come back
+    // as a JS error rather than aborting the module.
+    #[wasm_bindgen(js_name = addBlock)]
+    pub fn add_block(&mut self, parents: Vec<u64>) -> Result<u64, JsError> {
+        self.dag.create_block(parents).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    // Run StitchBot once, as the simulation does every few blocks
+    pub fn stitch(&mut self) {
+        self.dag.stitch_if_needed();
+    }
+
+    pub fn tips(&self) -> Vec<u64> {
+        let mut tips: Vec<u64> = self.dag.tips().collect();
+        tips.sort_unstable();
+        tips
+    }
+
+    #[wasm_bindgen(js_name = selectedParent)]
+    pub fn selected_parent(&self) -> u64 {
+        self.dag.selected_parent()
+    }
+
+    #[wasm_bindgen(js_name = selectedChain)]
+    pub fn selected_chain(&self) -> Vec<u64> {
+        self.dag.selected_chain()
+    }
+
+    #[wasm_bindgen(js_name = orderedBlocks)]
+    pub fn ordered_blocks(&self) -> Vec<u64> {
+        self.dag.ordered_blocks()
+    }
+
+    #[wasm_bindgen(js_name = blockCount)]
+    pub fn block_count(&self) -> usize {
+        self.dag.block_count()
+    }
+
+    #[wasm_bindgen(js_name = toDot)]
+    pub fn to_dot(&self) -> String {
+        export::to_dot(&self.dag)
+    }
+}